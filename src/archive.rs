@@ -0,0 +1,256 @@
+use crate::{DATE_FORMAT, Entry, INDENT, Todo, parse_date, subtree_end, today};
+use chrono::{Days, NaiveDate};
+use colored::*;
+use std::env;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+// Tarefas arquivadas: um grupo é uma tarefa de primeiro nível com as subtarefas que foram junto
+pub struct ArchivedTask {
+    pub completed: Option<NaiveDate>,
+    pub lines: Vec<Entry>,
+}
+
+impl ArchivedTask {
+    fn matches(&self, from: Option<NaiveDate>, to: Option<NaiveDate>, text: &str) -> bool {
+        let in_range = match self.completed {
+            Some(day) => from.is_none_or(|from| day >= from) && to.is_none_or(|to| day <= to),
+            None => from.is_none() && to.is_none(),
+        };
+        in_range
+            && (text.is_empty()
+                || self.lines.iter().any(|entry| entry.todo_entry.to_lowercase().contains(text)))
+    }
+}
+
+// Agrupa as linhas do arquivo morto a partir de cada tarefa de primeiro nível
+pub fn read_archive(lines: &[String]) -> Vec<ArchivedTask> {
+    let mut tasks: Vec<ArchivedTask> = Vec::new();
+    for line in lines {
+        let entry = Entry::read_line(line);
+        match tasks.last_mut() {
+            Some(task) if entry.depth > 0 => task.lines.push(entry),
+            _ => tasks.push(ArchivedTask {
+                completed: entry.completed,
+                lines: vec![entry],
+            }),
+        }
+    }
+    tasks
+}
+
+// TODO_AUTO_ARCHIVE=N arquiva sozinho as tarefas concluídas há pelo menos N dias
+fn auto_archive_days() -> Option<u64> {
+    env::var("TODO_AUTO_ARCHIVE").ok()?.trim().parse().ok()
+}
+
+impl Todo {
+    // Arquivo morto ao lado da lista (ex.: ~/.todo.done)
+    pub fn archive_path(&self) -> PathBuf {
+        let mut path = self.todo_path.clone().into_os_string();
+        path.push(".done");
+        PathBuf::from(path)
+    }
+
+    pub fn archived_lines(&self) -> Vec<String> {
+        // Sem arquivo morto, nada é criado só para ler
+        if !self.archive_path().exists() {
+            return Vec::new();
+        }
+        self.archive_storage().load().unwrap_or_default()
+    }
+
+    // Move para o arquivo morto as tarefas concluídas cuja árvore inteira está concluída.
+    // Com `days`, só as concluídas há pelo menos esse número de dias. Devolve quantas saíram da lista.
+    pub fn archive(&self, days: Option<u64>) -> Result<usize, String> {
+        let cutoff = days.map(|days| today() - Days::new(days));
+        // Tarefas concluídas antes de a data ser gravada contam como antigas
+        let archivable = |entry: &Entry| {
+            entry.done && cutoff.is_none_or(|cutoff| entry.completed.is_none_or(|day| day <= cutoff))
+        };
+
+        let depths = self.depths();
+        let mut entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut archived = vec![false; self.todo.len()];
+        let mut data = String::new();
+        let mut pos = 0;
+        while pos < entries.len() {
+            let end = subtree_end(&depths, pos);
+            if !entries[pos..end].iter().all(archivable) {
+                pos += 1;
+                continue;
+            }
+            for p in pos..end {
+                let entry = &mut entries[p];
+                entry.depth = depths[p] - depths[pos];
+                entry.completed.get_or_insert_with(today);
+                data.push_str(&entry.file_line());
+                archived[p] = true;
+            }
+            pos = end;
+        }
+
+        let count = archived.iter().filter(|archived| **archived).count();
+        if count == 0 {
+            return Ok(0);
+        }
+
+        // Grava primeiro o arquivo morto: se a lista falhar, a tarefa fica duplicada, não perdida
+        let lines: Vec<String> = data.lines().map(str::to_string).collect();
+        self.archive_storage().append(&lines)?;
+
+        let remaining: String = self
+            .todo
+            .iter()
+            .zip(archived)
+            .filter(|(_, archived)| !archived)
+            .map(|(line, _)| format!("{}\n", line))
+            .collect();
+        self.write_file(&remaining)?;
+        Ok(count)
+    }
+
+    // Arquivamento automático, se TODO_AUTO_ARCHIVE estiver definido
+    pub fn auto_archive(&self) -> Result<usize, String> {
+        match auto_archive_days() {
+            Some(days) => self.archive(Some(days)),
+            None => Ok(0),
+        }
+    }
+
+    // log [--from DATA] [--to DATA] [TEXTO]: tarefas arquivadas, por data de conclusão
+    pub fn log(&self, args: &[String]) -> Result<(), String> {
+        let mut from = None;
+        let mut to = None;
+        let mut words = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--from" | "--to" => {
+                    let value = args.next().ok_or(format!("Uso: todo log {} AAAA-MM-DD", arg))?;
+                    let date = parse_date(value)?;
+                    if arg == "--from" {
+                        from = Some(date);
+                    } else {
+                        to = Some(date);
+                    }
+                }
+                _ => words.push(arg.as_str()),
+            }
+        }
+        let text = words.join(" ").to_lowercase();
+
+        let mut tasks = read_archive(&self.archived_lines());
+        tasks.retain(|task| task.matches(from, to, &text));
+        tasks.sort_by_key(|task| task.completed);
+
+        let stdout = io::stdout();
+        let mut writer = BufWriter::new(stdout);
+        let mut data = String::new();
+        for task in &tasks {
+            let date = match task.completed {
+                Some(day) => day.format(DATE_FORMAT).to_string(),
+                None => "----------".to_string(),
+            };
+            for (i, entry) in task.lines.iter().enumerate() {
+                let prefix = if i == 0 { date.green().to_string() } else { " ".repeat(date.len()) };
+                data.push_str(&format!("{} {}{}\n", prefix, INDENT.repeat(entry.depth), entry.todo_entry));
+            }
+        }
+
+        writer
+            .write_all(data.as_bytes())
+            .map_err(|e| format!("Falha na gravação: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{reload, temp_list, texts};
+
+    fn days_ago(days: u64) -> String {
+        (today() - Days::new(days)).format(DATE_FORMAT).to_string()
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn only_tasks_done_long_enough_are_archived() {
+        let (_dir, todo) = temp_list(&[
+            &format!("[*] antiga done:{}", days_ago(10)),
+            &format!("[*] recente done:{}", days_ago(2)),
+            "[*] sem data",
+            "[ ] pendente",
+            &format!("[*] no limite done:{}", days_ago(7)),
+        ]);
+        assert_eq!(todo.archive(Some(7)).unwrap(), 3);
+        assert_eq!(texts(&reload(&todo)), ["recente", "pendente"]);
+        let archived: Vec<String> = read_archive(&todo.archived_lines())
+            .iter()
+            .map(|task| task.lines[0].todo_entry.clone())
+            .collect();
+        assert_eq!(archived, ["antiga", "sem data", "no limite"]);
+        // A tarefa sem data de conclusão ganha a de hoje no arquivo morto
+        assert_eq!(read_archive(&todo.archived_lines())[1].completed, Some(today()));
+
+        // Sem limite, todas as concluídas saem; sem nada para arquivar, nada muda
+        let todo = reload(&todo);
+        assert_eq!(todo.archive(None).unwrap(), 1);
+        assert_eq!(reload(&todo).archive(None).unwrap(), 0);
+        assert_eq!(texts(&reload(&todo)), ["pendente"]);
+    }
+
+    #[test]
+    fn subtrees_move_with_their_parent() {
+        let (_dir, todo) = temp_list(&[
+            "[*] pai",
+            "  [*] filha",
+            "    [*] neta",
+            "[*] outro pai",
+            "  [ ] filha pendente",
+            "  [*] filha feita",
+            "    [*] neta feita",
+        ]);
+        assert_eq!(todo.archive(None).unwrap(), 5);
+        // A subárvore concluída de um pai pendente sai sozinha, a partir do primeiro nível
+        assert_eq!(texts(&reload(&todo)), ["outro pai", "filha pendente"]);
+        let tasks = read_archive(&todo.archived_lines());
+        let groups: Vec<Vec<(usize, &str)>> = tasks
+            .iter()
+            .map(|task| task.lines.iter().map(|entry| (entry.depth, entry.todo_entry.as_str())).collect())
+            .collect();
+        assert_eq!(groups, [vec![(0, "pai"), (1, "filha"), (2, "neta")], vec![(0, "filha feita"), (1, "neta feita")]]);
+    }
+
+    #[test]
+    fn log_filters_by_date_and_text() {
+        let tasks = read_archive(&lines(&[
+            "[*] relatório done:2026-03-01",
+            "  [*] Revisar gráficos done:2026-03-01",
+            "[*] mercado done:2026-03-15",
+            "[*] antiga",
+        ]));
+        let date = |text: &str| NaiveDate::parse_from_str(text, DATE_FORMAT).ok();
+        let shown = |from: Option<NaiveDate>, to: Option<NaiveDate>, text: &str| -> Vec<String> {
+            tasks
+                .iter()
+                .filter(|task| task.matches(from, to, text))
+                .map(|task| task.lines[0].todo_entry.clone())
+                .collect()
+        };
+        assert_eq!(shown(None, None, ""), ["relatório", "mercado", "antiga"]);
+        assert_eq!(shown(date("2026-03-01"), date("2026-03-01"), ""), ["relatório"]);
+        assert_eq!(shown(date("2026-03-02"), None, ""), ["mercado"]);
+        assert_eq!(shown(None, date("2026-03-14"), ""), ["relatório"]);
+        // O texto procura também nas subtarefas, sem diferenciar maiúsculas
+        assert_eq!(shown(None, None, "gráficos"), ["relatório"]);
+        assert_eq!(shown(None, None, "nada"), Vec::<String>::new());
+        let (_dir, todo) = temp_list(&[]);
+        assert!(todo.log(&lines(&["--from", "ontem"])).is_err());
+        assert!(todo.log(&lines(&["--to"])).is_err());
+    }
+}
+
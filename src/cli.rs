@@ -1,23 +1,667 @@
-use crate::libs::*;
+use todo_todo::*;
+use colored::*;
+use std::io::{self, IsTerminal, Write};
+use std::process;
 
-pub fn run(args: Vec<String>){
-    let todo = Todo::new().expect("Não foi possível criar o TODO :(");
+// Posição de uma das opções globais. Elas só valem antes do comando: depois dele,
+// "-l" ou "--global" fazem parte do texto da tarefa (ex.: todo mk "use -l").
+fn global_option(args: &[String], names: &[&str]) -> Option<usize> {
+    let mut pos = 1;
+    while let Some(arg) = args.get(pos) {
+        if names.contains(&arg.as_str()) {
+            return Some(pos);
+        }
+        match arg.as_str() {
+            "--list" | "-l" => pos += 2,
+            "--global" => pos += 1,
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Remove "--list NOME" (ou "-l NOME") dos argumentos e devolve o nome da lista
+pub fn take_list(args: &mut Vec<String>) -> Option<String> {
+    let pos = global_option(args, &["--list", "-l"])?;
+    if pos + 1 >= args.len() {
+        eprintln!("Uso: todo --list [LISTA] [COMANDO]");
+        process::exit(1);
+    }
+    let name = args.remove(pos + 1);
+    args.remove(pos);
+    Some(name)
+}
+
+// Remove "--global" dos argumentos; com ele, listas de projeto (.todo) são ignoradas
+pub fn take_global(args: &mut Vec<String>) -> bool {
+    match global_option(args, &["--global"]) {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn open(list: Option<&str>) -> Todo {
+    match Todo::open_list(list.and_then(lists::list_name)) {
+        Ok(todo) => todo,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
+
+pub fn run(args: Vec<String>, list: Option<String>){
+    // Comandos que não dependem de uma lista aberta
+    if args.get(1).is_some_and(|command| command == "lists") {
+        if let Err(e) = run_lists(&args[2..]) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Chamado pelo git com os três arquivos: não abre lista nenhuma
+    if args.get(1).is_some_and(|command| command == "merge-driver") {
+        match merge_driver(&args[2..]) {
+            Ok(0) => return,
+            Ok(conflicts) => {
+                eprintln!("{} conflito(s) na lista; resolva entre os marcadores <<<<<<< e >>>>>>>", conflicts);
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(2);
+            }
+        }
+    }
+
+    if args.get(1).is_some_and(|command| command == "daemon") {
+        if let Err(e) = daemon::run() {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Esquecer as chaves não precisa abrir a lista
+    if args.get(1).is_some_and(|command| command == "lock") {
+        match crypto::lock() {
+            Ok(()) => println!("Chaves esquecidas: as listas criptografadas vão pedir a senha de novo"),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // Uma lista criptografada sem chave disponível pede a senha antes de ser aberta
+    let explicit = args.get(1).is_some_and(|command| command == "unlock");
+    if let Err(e) = unlock(list.as_deref(), explicit) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+    if explicit {
+        return;
+    }
+
+    // Fica rodando e lê as listas por conta própria
+    if args.get(1).is_some_and(|command| command == "remind") {
+        if let Err(e) = remind(&args[2..], list.as_deref()) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Com o daemon rodando, a lista vem da memória dele e as alterações passam por ele
+    let mut client = daemon::Client::connect();
+    let todo = match daemon::open_list(&mut client, list.as_deref().and_then(lists::list_name)) {
+        Ok(todo) => todo,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    };
 
     if args.len() > 1 {
         let command = &args[1];
-        match &command[..] {
-            "list" => todo.list(),
-            "mk" => todo.add(&args[2..]),
-            "rm" => todo.remove(&args[2..]),
-            "done" => todo.done(&args[2..]),
-            "raw" => todo.raw(&args[2..]),
-            "edit" => todo.edit(&args[2..]),
-            "sort" => todo.sort(),
-            "reset" => todo.reset(),
-            "restore" => todo.restore(),
-            "help" | "--help" | "-h" | _ => help(),            
+        // Descrita antes de aplicar: depois de um rm a tarefa não existe mais
+        let message = sync::describe(&todo, command, &args[2..]);
+        let result = match &command[..] {
+            "list" => {
+                todo.list();
+                Ok(())
+            }
+            "mk" | "sub" | "rm" | "done" | "status" | "due" | "pri" | "rec" | "block" | "unblock" | "sort"
+            | "reset" | "restore" => daemon::apply(&mut client, &todo, command, &args[2..]),
+            "mv" => move_tasks(&todo, &args[2..]),
+            "archive" => archive(&todo, &args[2..]),
+            "log" => todo.log(&args[2..]),
+            "stats" => stats(&todo, &args[2..]),
+            "note" => note(&todo, &mut client, &args[2..]),
+            "scan" => scan(&todo, &args[2..]),
+            "ical" => ical(&todo, &args[2..]),
+            "import" => import(&todo, &args[2..]),
+            "sync" => run_sync(&todo, &args[2..]),
+            "migrate" => migrate(&todo, &args[2..]),
+            "encrypt" => encrypt(&todo),
+            "decrypt" => decrypt(&todo),
+            "history" => history(&todo, &args[2..]),
+            "serve" => serve(&args[2..], list.clone()),
+            "where" => {
+                println!("{}", todo.todo_path.display());
+                Ok(())
+            }
+            "raw" => {
+                todo.raw(&args[2..]);
+                Ok(())
+            }
+            "edit" => edit(&todo, &mut client, &args[2..]),
+            "next" => {
+                todo.next();
+                Ok(())
+            }
+            _ => {
+                help();
+                Ok(())
+            }
+        };
+
+        // Comandos que alteram a lista disparam o arquivamento automático (TODO_AUTO_ARCHIVE)
+        // e, com o sync ligado, um commit
+        let result = result.and_then(|()| {
+            if !changes_list(command, &args[2..]) {
+                return Ok(());
+            }
+            let changed = open(list.as_deref());
+            // Edição ou importação cancelada não conta
+            if changed.todo == todo.todo {
+                return Ok(());
+            }
+            if command != "archive" {
+                changed.auto_archive()?;
+            }
+            // mv, archive, scan, import e edit --all gravam direto no arquivo
+            daemon::touched(&mut client, &changed);
+            sync::record(&changed, &message)
+        });
+
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            process::exit(1);
         }
     } else {
         todo.list();
     }
-}
\ No newline at end of file
+}
+
+// mv [ÍNDICE/S] [LISTA]: o último argumento é a lista de destino
+fn move_tasks(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let Some((target, indices)) = args.split_last() else {
+        return Err("Uso: todo mv [ÍNDICE/S] [LISTA]".to_string());
+    };
+    let target = Todo::open_list(lists::list_name(target))?;
+    todo.move_to(indices, &target)
+}
+
+// archive [DIAS]: só as tarefas concluídas há pelo menos DIAS dias
+fn archive(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let days = match args {
+        [] => None,
+        [days] => Some(days.parse().map_err(|_| format!("Número de dias inválido '{}'", days))?),
+        _ => return Err("Uso: todo archive [DIAS]".to_string()),
+    };
+    let count = todo.archive(days)?;
+    println!("{} tarefa(s) arquivada(s) em {}", count, todo.archive_path().display());
+    Ok(())
+}
+
+fn stats(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let stats = todo.stats();
+    match args {
+        [] => print!("{}", stats.report()),
+        [flag] if flag == "--json" => println!("{}", stats.to_json()?),
+        _ => return Err("Uso: todo stats [--json]".to_string()),
+    }
+    Ok(())
+}
+
+fn is_encrypted(todo: &Todo) -> bool {
+    todo.backend() == storage::Backend::Encrypted
+}
+
+// edit [ÍNDICE] sem texto e edit --all abrem o $EDITOR e pedem confirmação antes de salvar
+fn edit(todo: &Todo, client: &mut Option<daemon::Client>, args: &[String]) -> Result<(), String> {
+    match args {
+        [flag] if flag == "--all" => edit_all(todo),
+        [index] => {
+            let current = todo.entry_text(index)?;
+            let edited = editor::edit_text(&current, "tarefa.txt", is_encrypted(todo))?;
+            // Quebras de linha viram espaços: a tarefa continua sendo uma linha só
+            let edited = edited.split_whitespace().collect::<Vec<_>>().join(" ");
+            if edited.is_empty() {
+                return Err("O texto da tarefa não pode ficar vazio".to_string());
+            }
+            if edited == current {
+                println!("Nenhuma alteração.");
+                return Ok(());
+            }
+            println!("{} {}", "-".red(), current.red());
+            println!("{} {}", "+".green(), edited.green());
+            if confirm("Salvar?") {
+                daemon::apply(client, todo, "edit", &[index.clone(), edited])
+            } else {
+                println!("Nada foi alterado.");
+                Ok(())
+            }
+        }
+        _ => daemon::apply(client, todo, "edit", args),
+    }
+}
+
+fn edit_all(todo: &Todo) -> Result<(), String> {
+    let mut text: String = todo.todo.iter().map(|line| format!("{}\n", line)).collect();
+    let lines = loop {
+        text = editor::edit_text(&text, "lista.todo", is_encrypted(todo))?;
+        match Todo::parse_lines(&text) {
+            Ok(lines) => break lines,
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("{}", error.red());
+                }
+                if !confirm("Abrir o editor de novo para corrigir?") {
+                    return Err("Nada foi alterado.".to_string());
+                }
+            }
+        }
+    };
+
+    let changes = diff::diff(&todo.todo, &lines);
+    let (mut added, mut removed) = (0, 0);
+    for change in &changes {
+        match change {
+            diff::Change::Added(line) => {
+                added += 1;
+                println!("{} {}", "+".green(), line.green());
+            }
+            diff::Change::Removed(line) => {
+                removed += 1;
+                println!("{} {}", "-".red(), line.red());
+            }
+            diff::Change::Same(_) => {}
+        }
+    }
+    if added == 0 && removed == 0 {
+        println!("Nenhuma alteração.");
+        return Ok(());
+    }
+    println!("{} linha(s) adicionada(s), {} removida(s)", added, removed);
+    if confirm("Salvar?") {
+        todo.replace_lines(&lines)
+    } else {
+        println!("Nada foi alterado.");
+        Ok(())
+    }
+}
+
+// Comandos que podem alterar as tarefas da lista (ajuda, comando desconhecido, consultas,
+// migrate, encrypt e decrypt não mexem nelas)
+fn changes_list(command: &str, args: &[String]) -> bool {
+    match command {
+        "mk" | "sub" | "rm" | "done" | "status" | "due" | "pri" | "rec" | "block" | "unblock" | "sort" | "reset"
+        | "restore" | "mv" | "archive" | "note" | "edit" | "import" | "scan" => true,
+        "ical" => args.first().is_some_and(|action| action == "import"),
+        _ => false,
+    }
+}
+
+// Pergunta sim/não no terminal; Enter confirma, fim da entrada cancela
+fn confirm(question: &str) -> bool {
+    print!("{} [S/n] ", question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => false,
+        Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "" | "s" | "sim" | "y" | "yes"),
+    }
+}
+
+// scan [CAMINHO]: sem caminho, varre o diretório atual
+fn scan(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let root = match args {
+        [] => ".",
+        [path] => path.as_str(),
+        _ => return Err("Uso: todo scan [CAMINHO]".to_string()),
+    };
+    let summary = todo.scan(std::path::Path::new(root))?;
+    println!(
+        "{} nova(s), {} atualizada(s), {} concluída(s)",
+        summary.added, summary.updated, summary.closed
+    );
+    Ok(())
+}
+
+// ical export [ARQUIVO] (sem arquivo, na saída padrão) e ical import ARQUIVO
+fn ical(todo: &Todo, args: &[String]) -> Result<(), String> {
+    match args {
+        [action] if action == "export" => {
+            print!("{}", todo.to_ics());
+            Ok(())
+        }
+        [action, path] if action == "export" => std::fs::write(path, todo.to_ics())
+            .map_err(|e| format!("Não foi possível gravar {}: {}", path, e)),
+        [action, path] if action == "import" => {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("Não foi possível ler {}: {}", path, e))?;
+            let summary = todo.import_ics(&text)?;
+            println!(
+                "{} nova(s), {} atualizada(s), {} ignorada(s) (já estavam na lista)",
+                summary.added, summary.updated, summary.skipped
+            );
+            Ok(())
+        }
+        _ => Err("Uso: todo ical export [ARQUIVO.ics] | todo ical import ARQUIVO.ics".to_string()),
+    }
+}
+
+// import FORMATO ARQUIVO [--yes]: mostra o que entra na lista e pede confirmação
+fn import(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let yes = args.iter().any(|arg| arg == "--yes" || arg == "-y");
+    let args: Vec<&String> = args.iter().filter(|arg| *arg != "--yes" && *arg != "-y").collect();
+    let [format, path] = args[..] else {
+        return Err("Uso: todo import taskwarrior|todoist|github ARQUIVO [--yes]".to_string());
+    };
+    let format = import::Format::parse(format)?;
+    let text = std::fs::read_to_string(path).map_err(|e| format!("Não foi possível ler {}: {}", path, e))?;
+    // No CSV do Todoist, o nome do arquivo é o do projeto
+    let name = std::path::Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut preview = import::parse(format, &text, &name)?;
+    todo.check_duplicates(&mut preview);
+
+    for task in &preview.tasks {
+        if task.duplicate {
+            let indent = "  ".repeat(task.entry.depth);
+            let text = format!("{} (já está na lista)", task.entry.todo_entry);
+            println!("{}{} {}", indent, "=".dimmed(), text.dimmed());
+        } else {
+            print!("{}", task.entry.list_line(&"+".green().to_string(), &[]));
+        }
+    }
+    let new_tasks = preview.new_tasks();
+    println!(
+        "{} nova(s), {} duplicada(s), {} ignorada(s)",
+        new_tasks,
+        preview.tasks.len() - new_tasks,
+        preview.ignored
+    );
+    if new_tasks == 0 {
+        println!("Nada a importar.");
+        return Ok(());
+    }
+    if yes || confirm(&format!("Importar {} tarefa(s)?", new_tasks)) {
+        let imported = todo.import(&preview)?;
+        println!("{} tarefa(s) importada(s)", imported);
+    } else {
+        println!("Nada foi importado.");
+    }
+    Ok(())
+}
+
+// merge-driver %O %A %B: mescla as três versões e grava o resultado em %A, como o git espera.
+// Devolve quantos conflitos ficaram marcados no arquivo.
+fn merge_driver(args: &[String]) -> Result<usize, String> {
+    let [base, ours, theirs] = args else {
+        return Err("Uso: todo merge-driver %O %A %B".to_string());
+    };
+    let read = |path: &String| -> Result<Vec<String>, String> {
+        std::fs::read_to_string(path)
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .map_err(|e| format!("Não foi possível ler {}: {}", path, e))
+    };
+    let merged = merge::merge(&read(base)?, &read(ours)?, &read(theirs)?);
+    let data: String = merged.marked_lines().iter().map(|line| format!("{}\n", line)).collect();
+    std::fs::write(ours, data).map_err(|e| format!("Não foi possível gravar {}: {}", ours, e))?;
+    Ok(merged.conflicts)
+}
+
+// sync init [REMOTO] liga o commit automático; sync mescla com o remoto e envia
+fn run_sync(todo: &Todo, args: &[String]) -> Result<(), String> {
+    match args {
+        [action, rest @ ..] if action == "init" && rest.len() <= 1 => {
+            sync::init(todo, rest.first().map(String::as_str))?;
+            println!("Sync ligado em {}", todo.todo_path.parent().unwrap_or(todo.todo_path.as_path()).display());
+            Ok(())
+        }
+        [] => {
+            let summary = sync::sync(todo)?;
+            let mut done = Vec::new();
+            if summary.pulled {
+                done.push("alterações recebidas");
+            }
+            if summary.pushed {
+                done.push("alterações enviadas");
+            }
+            if done.is_empty() {
+                done.push("nada a fazer");
+            }
+            println!("Sincronizado com {}: {}", summary.upstream, done.join(", "));
+            if summary.conflicts > 0 {
+                println!(
+                    "{} conflito(s) resolvido(s) mantendo a versão local",
+                    summary.conflicts.to_string().yellow()
+                );
+            }
+            Ok(())
+        }
+        _ => Err("Uso: todo sync [init [REMOTO]]".to_string()),
+    }
+}
+
+fn read_passphrase(prompt: &str) -> Result<String, String> {
+    rpassword::prompt_password(prompt).map_err(|e| format!("Não foi possível ler a senha: {}", e))
+}
+
+// Pede a senha de uma lista criptografada e bloqueada. `todo unlock` (explicit) sempre confere
+// a senha (ou TODO_PASSPHRASE) e avisa se a chave ficou guardada no daemon.
+fn unlock(list: Option<&str>, explicit: bool) -> Result<(), String> {
+    let todo = Todo::locate(list.and_then(lists::list_name))?;
+    if explicit && todo.backend() != storage::Backend::Encrypted {
+        return Err("A lista não está criptografada".to_string());
+    }
+    // Sem terminal não há como pedir a senha: o erro aparece ao abrir a lista
+    if !explicit && (!todo.is_locked() || !io::stdin().is_terminal()) {
+        return Ok(());
+    }
+    let passphrase = match std::env::var("TODO_PASSPHRASE") {
+        Ok(passphrase) if !passphrase.is_empty() => passphrase,
+        _ => read_passphrase("Senha da lista: ")?,
+    };
+    let stored = crypto::unlock(&todo.todo_path, &passphrase)?;
+    if explicit && stored {
+        println!("Lista desbloqueada: o daemon guarda a chave até todo lock");
+    } else if explicit {
+        println!("Senha correta, mas sem o daemon rodando a chave não fica guardada: inicie todo daemon ou use TODO_PASSPHRASE");
+    }
+    Ok(())
+}
+
+// encrypt: a senha vem de TODO_PASSPHRASE ou é pedida duas vezes
+fn encrypt(todo: &Todo) -> Result<(), String> {
+    let passphrase = match std::env::var("TODO_PASSPHRASE") {
+        Ok(passphrase) if !passphrase.is_empty() => passphrase,
+        _ => {
+            let passphrase = read_passphrase("Nova senha: ")?;
+            if passphrase.is_empty() {
+                return Err("A senha não pode ficar vazia".to_string());
+            }
+            if read_passphrase("Repita a senha: ")? != passphrase {
+                return Err("As senhas não conferem".to_string());
+            }
+            passphrase
+        }
+    };
+    todo.encrypt(&passphrase)?;
+    println!("Lista criptografada: {}", todo.todo_path.display());
+    Ok(())
+}
+
+fn decrypt(todo: &Todo) -> Result<(), String> {
+    todo.decrypt()?;
+    println!("Lista gravada de novo em texto puro: {}", todo.todo_path.display());
+    Ok(())
+}
+
+// migrate text|sqlite: converte a lista no mesmo caminho
+fn migrate(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let [target] = args else {
+        return Err("Uso: todo migrate text|sqlite".to_string());
+    };
+    let target = storage::Backend::parse(target)?;
+    todo.migrate(target)?;
+    println!("Lista convertida para {}: {}", target.name(), todo.todo_path.display());
+    Ok(())
+}
+
+// history [N]: últimas N linhas adicionadas ou removidas (só no formato SQLite)
+fn history(todo: &Todo, args: &[String]) -> Result<(), String> {
+    let limit = match args {
+        [] => 20,
+        [n] => n.parse().map_err(|_| format!("Quantidade inválida '{}'", n))?,
+        _ => return Err("Uso: todo history [N]".to_string()),
+    };
+    let entries = todo.storage().history(limit)?;
+    if entries.is_empty() {
+        println!("Nenhuma alteração registrada.");
+    }
+    for entry in entries.iter().rev() {
+        if entry.added {
+            println!("{} {} {}", entry.at.dimmed(), "+".green(), entry.line.green());
+        } else {
+            println!("{} {} {}", entry.at.dimmed(), "-".red(), entry.line.red());
+        }
+    }
+    Ok(())
+}
+
+// serve [--port PORTA] [--host ENDEREÇO] [--token TOKEN]
+fn serve(args: &[String], list: Option<String>) -> Result<(), String> {
+    let usage = || "Uso: todo serve [--port PORTA] [--host ENDEREÇO] [--token TOKEN]".to_string();
+    let mut options = server::ServeOptions {
+        host: "127.0.0.1".to_string(),
+        port: server::DEFAULT_PORT,
+        token: std::env::var("TODO_SERVE_TOKEN").unwrap_or_default(),
+        list: list.as_deref().and_then(lists::list_name).map(String::from),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = args.next().ok_or_else(usage)?;
+        match arg.as_str() {
+            "--port" => options.port = value.parse().map_err(|_| format!("Porta inválida '{}'", value))?,
+            "--host" => options.host = value.clone(),
+            "--token" => options.token = value.clone(),
+            _ => return Err(usage()),
+        }
+    }
+    if options.token.is_empty() {
+        options.token = server::random_token();
+        println!("Token gerado: {}", options.token);
+    }
+    if options.host != "127.0.0.1" && options.host != "localhost" && options.host != "::1" {
+        eprintln!("{}", "Atenção: a API ficará acessível fora desta máquina".yellow());
+    }
+    server::serve(options)
+}
+
+// remind [--all] [--lead 1d,1h,0] [--at HH:MM]
+fn remind(args: &[String], list: Option<&str>) -> Result<(), String> {
+    let usage = || "Uso: todo remind [--all] [--lead 1d,1h,0] [--at HH:MM]".to_string();
+    let mut options = remind::RemindOptions::from_env(vec![list.and_then(lists::list_name).map(String::from)])?;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            // A lista principal e todas as nomeadas
+            "--all" => options.lists = std::iter::once(None).chain(lists::names()?.into_iter().map(Some)).collect(),
+            "--lead" => options.leads = remind::parse_leads(args.next().ok_or_else(usage)?)?,
+            "--at" => options.due_time = remind::parse_time(args.next().ok_or_else(usage)?)?,
+            _ => return Err(usage()),
+        }
+    }
+    remind::run(options)
+}
+
+// note [ÍNDICE] [ANOTAÇÃO]: sem o texto, edita a anotação no $EDITOR
+fn note(todo: &Todo, client: &mut Option<daemon::Client>, args: &[String]) -> Result<(), String> {
+    let Some((index, text)) = args.split_first() else {
+        return Err("Uso: todo note [ÍNDICE] [ANOTAÇÃO]".to_string());
+    };
+    let note = if text.is_empty() {
+        let current = todo.note(index)?.unwrap_or_default();
+        editor::edit_text(&current, "nota.md", is_encrypted(todo))?
+    } else {
+        text.join(" ")
+    };
+    daemon::apply(client, todo, "note", &[index.clone(), note])
+}
+
+fn run_lists(args: &[String]) -> Result<(), String> {
+    match args {
+        [] => {
+            println!("{}", lists::DEFAULT_LIST);
+            for name in lists::names()? {
+                println!("{}", name);
+            }
+            Ok(())
+        }
+        [command, name] if command == "mk" => lists::create(name),
+        [command, name] if command == "rm" => lists::delete(name),
+        [command, old, new] if command == "mv" => lists::rename(old, new),
+        _ => Err("Uso: todo lists [mk NOME | mv ANTIGO NOVO | rm NOME]".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn only_commands_that_change_tasks_trigger_the_post_step() {
+        for command in ["mk", "done", "edit", "mv", "archive", "import", "scan"] {
+            assert!(changes_list(command, &[]), "{}", command);
+        }
+        for command in ["help", "--help", "lst", "list", "next", "stats", "migrate", "encrypt", "decrypt", "sync"] {
+            assert!(!changes_list(command, &[]), "{}", command);
+        }
+        assert!(changes_list("ical", &args(&["import", "a.ics"])));
+        assert!(!changes_list("ical", &args(&["export"])));
+    }
+
+    #[test]
+    fn global_options_are_taken_before_the_command() {
+        let mut argv = args(&["todo", "--global", "-l", "trabalho", "mk", "relatório"]);
+        assert_eq!(take_list(&mut argv).as_deref(), Some("trabalho"));
+        assert!(take_global(&mut argv));
+        assert_eq!(argv, ["todo", "mk", "relatório"]);
+    }
+
+    #[test]
+    fn options_after_the_command_stay_in_the_task_text() {
+        let mut argv = args(&["todo", "mk", "use -l flag", "fix", "-l", "x", "--global"]);
+        assert_eq!(take_list(&mut argv), None);
+        assert!(!take_global(&mut argv));
+        assert_eq!(argv.len(), 7);
+
+        let mut argv = args(&["todo", "--list", "casa", "mk", "--list", "compras"]);
+        assert_eq!(take_list(&mut argv).as_deref(), Some("casa"));
+        assert_eq!(argv, ["todo", "mk", "--list", "compras"]);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::daemon;
+use argon2::Argon2;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+// Listas criptografadas: a chave sai da senha (Argon2id) e o conteúdo é cifrado e autenticado
+// (ChaCha20-Poly1305). Arquivo: MAGIC | sal | nonce | texto cifrado com a tag.
+
+pub const MAGIC: &[u8] = b"TODOENC1";
+pub const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+pub type Salt = [u8; SALT_LEN];
+pub type Key = [u8; 32];
+
+// Chaves já derivadas neste processo, pelo sal do arquivo (a derivação é lenta de propósito)
+static KEYS: Mutex<Vec<(Salt, Key)>> = Mutex::new(Vec::new());
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+pub fn new_salt() -> Salt {
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+pub fn salt_of(data: &[u8]) -> Option<Salt> {
+    data.get(MAGIC.len()..MAGIC.len() + SALT_LEN)?.try_into().ok()
+}
+
+pub fn read_salt(path: &Path) -> Result<Salt, String> {
+    let data = fs::read(path).map_err(|e| format!("Não foi possível ler {}: {}", path.display(), e))?;
+    salt_of(&data).ok_or_else(|| format!("{} não é uma lista criptografada", path.display()))
+}
+
+pub fn derive_key(passphrase: &str, salt: &Salt) -> Result<Key, String> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Não foi possível derivar a chave: {}", e))?;
+    Ok(key)
+}
+
+// Só o cache deste processo (é o que o daemon consulta quando faz papel de agente)
+pub fn cached(salt: &Salt) -> Option<Key> {
+    let keys = KEYS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    keys.iter().find(|(cached, _)| cached == salt).map(|(_, key)| *key)
+}
+
+pub fn cache(salt: Salt, key: Key) {
+    let mut keys = KEYS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    keys.retain(|(cached, _)| *cached != salt);
+    keys.push((salt, key));
+}
+
+pub fn clear_cache() {
+    KEYS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+}
+
+// Abre um arquivo criptografado com a chave do cache, da senha em TODO_PASSPHRASE ou do daemon
+// (o agente). A chave só entra no cache depois de abrir o arquivo: uma senha errada não fica
+// lembrada, e a próxima tentativa (com outra senha) deriva a chave de novo.
+pub fn open(data: &[u8]) -> Result<(Key, Vec<u8>), String> {
+    open_with(data, find_key)
+}
+
+fn find_key(salt: &Salt) -> Result<Key, String> {
+    match env::var("TODO_PASSPHRASE") {
+        Ok(passphrase) if !passphrase.is_empty() => derive_key(&passphrase, salt),
+        _ => daemon::Client::connect()
+            .and_then(|mut client| client.key(salt).ok().flatten())
+            .ok_or("A lista está criptografada e bloqueada: use todo unlock ou defina TODO_PASSPHRASE".to_string()),
+    }
+}
+
+fn open_with(data: &[u8], find_key: impl FnOnce(&Salt) -> Result<Key, String>) -> Result<(Key, Vec<u8>), String> {
+    let salt = salt_of(data).ok_or("Arquivo criptografado inválido")?;
+    if let Some(key) = cached(&salt)
+        && let Ok(plaintext) = decrypt(&key, data)
+    {
+        return Ok((key, plaintext));
+    }
+    let key = find_key(&salt)?;
+    let plaintext = decrypt(&key, data)?;
+    cache(salt, key);
+    Ok((key, plaintext))
+}
+
+pub fn encrypt(key: &Key, salt: &Salt, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let mut data = MAGIC.to_vec();
+    data.extend_from_slice(salt);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    // O cabeçalho também é autenticado: trocar o sal invalida o arquivo
+    let payload = Payload {
+        msg: plaintext,
+        aad: &data,
+    };
+    let ciphertext = ChaCha20Poly1305::new(key.into())
+        .encrypt(&nonce, payload)
+        .map_err(|_| "Não foi possível criptografar a lista".to_string())?;
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+pub fn decrypt(key: &Key, data: &[u8]) -> Result<Vec<u8>, String> {
+    let header = MAGIC.len() + SALT_LEN;
+    if !is_encrypted(data) || data.len() < header + NONCE_LEN {
+        return Err("Arquivo criptografado inválido".to_string());
+    }
+    let nonce = Nonce::from_slice(&data[header..header + NONCE_LEN]);
+    let payload = Payload {
+        msg: &data[header + NONCE_LEN..],
+        aad: &data[..header],
+    };
+    ChaCha20Poly1305::new(key.into())
+        .decrypt(nonce, payload)
+        .map_err(|_| "Senha incorreta ou arquivo corrompido".to_string())
+}
+
+// Confere a senha abrindo o arquivo e guarda a chave (veja `remember`)
+pub fn unlock(path: &Path, passphrase: &str) -> Result<bool, String> {
+    let data = fs::read(path).map_err(|e| format!("Não foi possível ler {}: {}", path.display(), e))?;
+    let salt = salt_of(&data).ok_or_else(|| format!("{} não é uma lista criptografada", path.display()))?;
+    let key = derive_key(passphrase, &salt)?;
+    decrypt(&key, &data)?;
+    Ok(remember(salt, key))
+}
+
+// Guarda a chave neste processo e no daemon; devolve false se não havia daemon
+pub fn remember(salt: Salt, key: Key) -> bool {
+    cache(salt, key);
+    match daemon::Client::connect() {
+        Some(mut client) => client.store_key(salt, key).is_ok(),
+        None => false,
+    }
+}
+
+// Esquece as chaves deste processo e do daemon
+pub fn lock() -> Result<(), String> {
+    clear_cache();
+    match daemon::Client::connect() {
+        Some(mut client) => client.forget_keys(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypted_data_round_trips_and_is_authenticated() {
+        let salt = new_salt();
+        let key = derive_key("senha certa", &salt).unwrap();
+        let data = encrypt(&key, &salt, b"[ ] segredo\n").unwrap();
+        assert!(is_encrypted(&data));
+        assert_eq!(salt_of(&data), Some(salt));
+        assert!(!data.windows(7).any(|window| window == b"segredo"));
+        assert_eq!(decrypt(&key, &data).unwrap(), b"[ ] segredo\n");
+
+        let wrong = derive_key("senha errada", &salt).unwrap();
+        assert!(decrypt(&wrong, &data).is_err());
+
+        // Qualquer byte alterado, no texto cifrado ou no cabeçalho, invalida o arquivo
+        for pos in [data.len() - 1, MAGIC.len() + SALT_LEN + NONCE_LEN, MAGIC.len()] {
+            let mut tampered = data.clone();
+            tampered[pos] ^= 1;
+            assert!(decrypt(&key, &tampered).is_err(), "{}", pos);
+        }
+        assert!(decrypt(&key, &data[..MAGIC.len() + SALT_LEN]).is_err());
+        assert!(decrypt(&key, b"[ ] texto").is_err());
+    }
+
+    #[test]
+    fn only_a_key_that_opens_the_file_is_cached() {
+        let salt = new_salt();
+        let key = derive_key("senha certa", &salt).unwrap();
+        let wrong = derive_key("senha errada", &salt).unwrap();
+        let data = encrypt(&key, &salt, b"[ ] a\n").unwrap();
+
+        assert!(open_with(&data, |_| Ok(wrong)).is_err());
+        assert_eq!(cached(&salt), None);
+        // A tentativa seguinte, com a senha certa, não esbarra na chave errada
+        assert_eq!(open_with(&data, |_| Ok(key)).unwrap(), (key, b"[ ] a\n".to_vec()));
+        assert_eq!(cached(&salt), Some(key));
+        // Com a chave no cache, a senha não é pedida de novo
+        assert!(open_with(&data, |_| Err("sem senha".to_string())).is_ok());
+    }
+}
+
@@ -0,0 +1,628 @@
+use crate::crypto::{self, Key, Salt};
+use crate::{Todo, private_dir};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// Daemon opcional que mantém as listas em memória e atende pedidos por um socket Unix.
+// Cada pedido e cada resposta é uma linha JSON. Sem o daemon, CLI e GUI leem o arquivo direto.
+
+// Lista pedida pelo cliente: os caminhos são resolvidos do lado dele (diretório atual, --list...)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Location {
+    pub path: PathBuf,
+    pub backup: PathBuf,
+    pub no_backup: bool,
+    pub project: bool,
+}
+
+impl Location {
+    pub fn of(todo: &Todo) -> Self {
+        Self {
+            path: todo.todo_path.clone(),
+            backup: todo.todo_bak.clone(),
+            no_backup: todo.no_backup,
+            project: todo.project,
+        }
+    }
+
+    fn todo(&self, lines: Vec<String>) -> Todo {
+        Todo {
+            todo: lines,
+            todo_path: self.path.clone(),
+            todo_bak: self.backup.clone(),
+            no_backup: self.no_backup,
+            project: self.project,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+enum Request {
+    Load(Location),
+    Apply {
+        location: Location,
+        command: String,
+        args: Vec<String>,
+    },
+    // Mantém a conexão aberta para receber `Reply::Changed`
+    Subscribe,
+    // A lista foi gravada direto no arquivo por um comando que não passa por `Apply`
+    Touched(Location),
+    // O daemon também guarda as chaves das listas criptografadas, como um agente
+    StoreKey { salt: Salt, key: Key },
+    Key(Salt),
+    ForgetKeys,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Reply {
+    Lines(Vec<String>),
+    Ok,
+    Error(String),
+    // Uma lista foi alterada (por um cliente ou direto no arquivo)
+    Changed(PathBuf),
+    Key(Option<Key>),
+}
+
+// TODO_SOCKET ou o diretório privado do usuário (veja `private_dir`)
+pub fn socket_path() -> Result<PathBuf, String> {
+    if let Ok(path) = env::var("TODO_SOCKET") {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(private_dir()?.join("todo-todo.sock"))
+}
+
+trait Connection: Read + Write + Send {}
+impl<T: Read + Write + Send> Connection for T {}
+
+pub struct Client {
+    reader: BufReader<Box<dyn Connection>>,
+    broken: bool,
+}
+
+impl Client {
+    // Conecta ao daemon, se ele estiver rodando
+    pub fn connect() -> Option<Self> {
+        Self::open(Some(Duration::from_secs(10)))
+    }
+
+    fn open(timeout: Option<Duration>) -> Option<Self> {
+        Self::open_at(&socket_path().ok()?, timeout)
+    }
+
+    fn open_at(path: &Path, timeout: Option<Duration>) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            let stream = std::os::unix::net::UnixStream::connect(path).ok()?;
+            // Um socket de outro usuário no mesmo caminho não recebe listas nem chaves
+            if !is_trusted(peer_uid(&stream), own_uid()) {
+                return None;
+            }
+            stream.set_read_timeout(timeout).ok()?;
+            let stream: Box<dyn Connection> = Box::new(stream);
+            Some(Self {
+                reader: BufReader::new(stream),
+                broken: false,
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (path, timeout);
+            None
+        }
+    }
+
+    // A conexão caiu: quem usa o cliente deve voltar a acessar o arquivo direto
+    pub fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    fn send(&mut self, request: &Request) -> Result<(), String> {
+        let mut line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.reader.get_mut().write_all(line.as_bytes()).map_err(|e| {
+            self.broken = true;
+            format!("Conexão com o daemon perdida: {}", e)
+        })
+    }
+
+    fn receive(&mut self) -> Result<Reply, String> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(n) if n > 0 => serde_json::from_str(&line).map_err(|e| format!("Resposta inválida do daemon: {}", e)),
+            Ok(_) => {
+                self.broken = true;
+                Err("O daemon encerrou a conexão".to_string())
+            }
+            Err(e) => {
+                self.broken = true;
+                Err(format!("Conexão com o daemon perdida: {}", e))
+            }
+        }
+    }
+
+    fn request(&mut self, request: &Request) -> Result<Reply, String> {
+        self.send(request)?;
+        self.receive()
+    }
+
+    pub fn load(&mut self, location: &Location) -> Result<Todo, String> {
+        match self.request(&Request::Load(location.clone()))? {
+            Reply::Lines(lines) => Ok(location.todo(lines)),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    pub fn apply(&mut self, location: &Location, command: &str, args: &[String]) -> Result<(), String> {
+        let request = Request::Apply {
+            location: location.clone(),
+            command: command.to_string(),
+            args: args.to_vec(),
+        };
+        match self.request(&request)? {
+            Reply::Ok => Ok(()),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    pub fn touched(&mut self, location: &Location) -> Result<(), String> {
+        match self.request(&Request::Touched(location.clone()))? {
+            Reply::Ok => Ok(()),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    pub fn store_key(&mut self, salt: Salt, key: Key) -> Result<(), String> {
+        match self.request(&Request::StoreKey { salt, key })? {
+            Reply::Ok => Ok(()),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    pub fn key(&mut self, salt: &Salt) -> Result<Option<Key>, String> {
+        match self.request(&Request::Key(*salt))? {
+            Reply::Key(key) => Ok(key),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    pub fn forget_keys(&mut self) -> Result<(), String> {
+        match self.request(&Request::ForgetKeys)? {
+            Reply::Ok => Ok(()),
+            Reply::Error(e) => Err(e),
+            _ => Err("Resposta inesperada do daemon".to_string()),
+        }
+    }
+
+    // Abre uma conexão só para avisos de alteração e bloqueia até `on_change` devolver false
+    // ou a conexão cair. Devolve false se o daemon não estiver rodando.
+    pub fn subscribe(on_change: impl FnMut(PathBuf) -> bool) -> bool {
+        // Os avisos podem demorar: sem limite de espera nesta conexão
+        Self::listen(Self::open(None), on_change)
+    }
+
+    fn listen(client: Option<Self>, mut on_change: impl FnMut(PathBuf) -> bool) -> bool {
+        let Some(mut client) = client else {
+            return false;
+        };
+        if client.send(&Request::Subscribe).is_err() {
+            return false;
+        }
+        while let Ok(reply) = client.receive() {
+            if let Reply::Changed(path) = reply
+                && !on_change(path)
+            {
+                break;
+            }
+        }
+        true
+    }
+}
+
+// Abre a lista pelo daemon, se houver conexão, ou direto do arquivo.
+// Se a conexão cair, o cliente é descartado e as próximas chamadas usam o arquivo.
+pub fn open_list(client: &mut Option<Client>, list: Option<&str>) -> Result<Todo, String> {
+    load(client, Todo::locate(list)?)
+}
+
+// Lê uma lista já localizada (veja `Todo::locate`), pelo daemon ou direto do arquivo
+pub fn load(client: &mut Option<Client>, todo: Todo) -> Result<Todo, String> {
+    if let Some(connection) = client {
+        let result = connection.load(&Location::of(&todo));
+        if !connection.is_broken() {
+            return result;
+        }
+        *client = None;
+    }
+    todo.load()
+}
+
+// Aplica um comando pelo daemon ou direto no arquivo, como `open_list`
+pub fn apply(client: &mut Option<Client>, todo: &Todo, command: &str, args: &[String]) -> Result<(), String> {
+    if let Some(connection) = client {
+        let result = connection.apply(&Location::of(todo), command, args);
+        if !connection.is_broken() {
+            return result;
+        }
+        *client = None;
+    }
+    todo.apply(command, args)
+}
+
+// Avisa o daemon que a lista foi gravada direto no arquivo (mv, archive, import...), para que ele
+// a releia e avise as janelas sem esperar a próxima verificação do disco
+pub fn touched(client: &mut Option<Client>, todo: &Todo) {
+    if let Some(connection) = client {
+        let _ = connection.touched(&Location::of(todo));
+        if connection.is_broken() {
+            *client = None;
+        }
+    }
+}
+
+// Usuário do processo do outro lado do socket
+#[cfg(unix)]
+fn peer_uid(stream: &std::os::unix::net::UnixStream) -> Option<u32> {
+    use std::os::fd::AsRawFd;
+    let fd = stream.as_raw_fd();
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let mut cred = libc::ucred { pid: 0, uid: 0, gid: 0 };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        let result = unsafe {
+            libc::getsockopt(
+                fd,
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                (&mut cred as *mut libc::ucred).cast(),
+                &mut len,
+            )
+        };
+        (result == 0).then_some(cred.uid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let (mut uid, mut gid) = (0, 0);
+        let result = unsafe { libc::getpeereid(fd, &mut uid, &mut gid) };
+        (result == 0).then_some(uid)
+    }
+}
+
+#[cfg(unix)]
+fn own_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+// Os dois lados do socket só conversam se forem do mesmo usuário
+#[cfg(unix)]
+fn is_trusted(peer: Option<u32>, own: u32) -> bool {
+    peer == Some(own)
+}
+
+#[cfg(unix)]
+mod server {
+    use super::{Location, Reply, Request, crypto, is_trusted, own_uid, peer_uid, socket_path};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::Shutdown;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::{UnixListener, UnixStream};
+    use std::path::{Path, PathBuf};
+    use std::sync::mpsc::{self, SyncSender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    // Intervalo entre verificações de alterações feitas direto nos arquivos
+    const DISK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+    // Cada janela inscrita recebe os avisos pela sua própria thread. Uma janela que para de ler
+    // acumula avisos até este limite (ou trava a escrita por este tempo) e é desconectada,
+    // sem segurar os pedidos dos outros clientes.
+    const SUBSCRIBER_BACKLOG: usize = 64;
+    const SUBSCRIBER_WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    type DiskStamp = Option<(SystemTime, u64)>;
+
+    fn disk_stamp(path: &Path) -> DiskStamp {
+        let meta = fs::metadata(path).ok()?;
+        Some((meta.modified().ok()?, meta.len()))
+    }
+
+    #[derive(Default)]
+    struct State {
+        // Listas em memória, com o carimbo do arquivo quando foram lidas
+        lists: HashMap<PathBuf, (Vec<String>, DiskStamp)>,
+        subscribers: Vec<SyncSender<String>>,
+    }
+
+    impl State {
+        // Linhas da lista, relendo o arquivo só se ele mudou desde a última leitura
+        fn lines(&mut self, location: &Location) -> Result<Vec<String>, String> {
+            let stamp = disk_stamp(&location.path);
+            if let Some((lines, cached)) = self.lists.get(&location.path)
+                && stamp.is_some()
+                && *cached == stamp
+            {
+                return Ok(lines.clone());
+            }
+            let todo = location.todo(Vec::new()).load()?;
+            let stamp = disk_stamp(&location.path);
+            self.lists.insert(location.path.clone(), (todo.todo.clone(), stamp));
+            Ok(todo.todo)
+        }
+
+        fn apply(&mut self, location: &Location, command: &str, args: &[String]) -> Result<(), String> {
+            let todo = location.todo(self.lines(location)?);
+            let result = todo.apply(command, args);
+            // Relê mesmo em caso de erro: o comando pode ter gravado parte da alteração
+            self.lists.remove(&location.path);
+            let _ = self.lines(location);
+            self.notify(&location.path);
+            result
+        }
+
+        fn touched(&mut self, location: &Location) {
+            self.lists.remove(&location.path);
+            self.notify(&location.path);
+        }
+
+        fn subscribe(&mut self, mut stream: UnixStream) {
+            if stream.set_write_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT)).is_err() {
+                return;
+            }
+            let (sender, receiver) = mpsc::sync_channel::<String>(SUBSCRIBER_BACKLOG);
+            thread::spawn(move || {
+                for line in receiver {
+                    if stream.write_all(line.as_bytes()).is_err() {
+                        break;
+                    }
+                }
+                // Descartada (ou a conexão caiu): a janela vê o fim da conexão e volta ao arquivo
+                let _ = stream.shutdown(Shutdown::Both);
+            });
+            self.subscribers.push(sender);
+        }
+
+        fn notify(&mut self, path: &Path) {
+            let Ok(mut line) = serde_json::to_string(&Reply::Changed(path.to_path_buf())) else {
+                return;
+            };
+            line.push('\n');
+            self.subscribers
+                .retain(|subscriber| subscriber.try_send(line.clone()).is_ok());
+        }
+
+        // Avisa sobre listas alteradas por quem não passou pelo daemon
+        fn check_disk(&mut self) {
+            let changed: Vec<PathBuf> = self
+                .lists
+                .iter()
+                .filter(|(path, (_, stamp))| disk_stamp(path) != *stamp)
+                .map(|(path, _)| path.clone())
+                .collect();
+            for path in changed {
+                // As linhas são relidas no próximo pedido
+                self.lists.remove(&path);
+                self.notify(&path);
+            }
+        }
+    }
+
+    fn serve_client(stream: UnixStream, state: Arc<Mutex<State>>) {
+        let Ok(writer) = stream.try_clone() else {
+            return;
+        };
+        let mut writer = writer;
+        let reader = BufReader::new(stream);
+        for line in reader.lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            let reply = match serde_json::from_str::<Request>(&line) {
+                Ok(Request::Subscribe) => {
+                    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    if let Ok(subscriber) = writer.try_clone() {
+                        state.subscribe(subscriber);
+                    }
+                    continue;
+                }
+                Ok(Request::Load(location)) => {
+                    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    match state.lines(&location) {
+                        Ok(lines) => Reply::Lines(lines),
+                        Err(e) => Reply::Error(e),
+                    }
+                }
+                Ok(Request::Apply { location, command, args }) => {
+                    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    match state.apply(&location, &command, &args) {
+                        Ok(()) => Reply::Ok,
+                        Err(e) => Reply::Error(e),
+                    }
+                }
+                Ok(Request::Touched(location)) => {
+                    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    state.touched(&location);
+                    Reply::Ok
+                }
+                Ok(Request::StoreKey { salt, key }) => {
+                    crypto::cache(salt, key);
+                    Reply::Ok
+                }
+                Ok(Request::Key(salt)) => Reply::Key(crypto::cached(&salt)),
+                Ok(Request::ForgetKeys) => {
+                    crypto::clear_cache();
+                    // As listas criptografadas também estão abertas na memória
+                    let mut state = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    state.lists.clear();
+                    Reply::Ok
+                }
+                Err(e) => Reply::Error(format!("Pedido inválido: {}", e)),
+            };
+            let Ok(mut line) = serde_json::to_string(&reply) else {
+                return;
+            };
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).is_err() {
+                return;
+            }
+        }
+    }
+
+    pub fn run() -> Result<(), String> {
+        let path = socket_path()?;
+        let listener = bind(&path)?;
+        println!("Daemon escutando em {}", path.display());
+
+        let state = Arc::new(Mutex::new(State::default()));
+        let watcher = Arc::clone(&state);
+        thread::spawn(move || {
+            loop {
+                thread::sleep(DISK_POLL_INTERVAL);
+                watcher.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).check_disk();
+            }
+        });
+        serve(listener, state);
+        Ok(())
+    }
+
+    fn bind(path: &Path) -> Result<UnixListener, String> {
+        if UnixStream::connect(path).is_ok() {
+            return Err(format!("O daemon já está rodando em {}", path.display()));
+        }
+        // Socket que sobrou de um daemon encerrado
+        let _ = fs::remove_file(path);
+        // Só o próprio usuário conecta: o daemon guarda as listas e as chaves. Com a umask 077
+        // o socket já nasce com 0600, sem intervalo em que outro usuário consiga se conectar.
+        let umask = unsafe { libc::umask(0o077) };
+        let listener = UnixListener::bind(path);
+        unsafe { libc::umask(umask) };
+        let listener =
+            listener.map_err(|e| format!("Não foi possível criar o socket {}: {}", path.display(), e))?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("Não foi possível proteger o socket {}: {}", path.display(), e))?;
+        Ok(listener)
+    }
+
+    fn serve(listener: UnixListener, state: Arc<Mutex<State>>) {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            // Mesmo com as permissões do socket, confere quem se conectou
+            if !is_trusted(peer_uid(&stream), own_uid()) {
+                continue;
+            }
+            let state = Arc::clone(&state);
+            thread::spawn(move || serve_client(stream, state));
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::daemon::Client;
+        use crate::tests::temp_list;
+        use std::io::Read;
+        use std::time::Instant;
+
+        fn start(dir: &Path) -> (PathBuf, Arc<Mutex<State>>) {
+            let path = dir.join("todo-todo.sock");
+            let listener = bind(&path).unwrap();
+            let state = Arc::new(Mutex::new(State::default()));
+            let served = Arc::clone(&state);
+            thread::spawn(move || serve(listener, served));
+            (path, state)
+        }
+
+        fn subscribers(state: &Mutex<State>) -> usize {
+            state.lock().unwrap().subscribers.len()
+        }
+
+        // Espera o daemon registrar a inscrição, que não tem resposta
+        fn wait_for_subscribers(state: &Mutex<State>, count: usize) {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            while subscribers(state) != count {
+                assert!(Instant::now() < deadline, "inscrição não registrada");
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+
+        #[test]
+        fn only_the_same_user_is_trusted() {
+            assert!(is_trusted(Some(1000), 1000));
+            assert!(!is_trusted(Some(0), 1000));
+            assert!(!is_trusted(None, 1000));
+            assert!(is_trusted(Some(own_uid()), own_uid()));
+        }
+
+        #[test]
+        fn changes_are_applied_and_announced() {
+            let (dir, todo) = temp_list(&["[ ] pão"]);
+            let (path, state) = start(dir.path());
+            let location = Location::of(&todo);
+
+            let (sender, receiver) = mpsc::channel();
+            let subscriber = Client::open_at(&path, None);
+            thread::spawn(move || Client::listen(subscriber, |changed| sender.send(changed).is_ok()));
+            wait_for_subscribers(&state, 1);
+
+            let mut client = Client::open_at(&path, Some(Duration::from_secs(10))).unwrap();
+            assert_eq!(client.load(&location).unwrap().todo, ["[ ] pão"]);
+            client.apply(&location, "mk", &["leite".to_string()]).unwrap();
+            assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), todo.todo_path);
+            assert_eq!(todo.reload().unwrap().todo.len(), 2);
+            assert_eq!(client.load(&location).unwrap().todo, todo.reload().unwrap().todo);
+
+            // Erros do comando voltam ao cliente, e a conexão continua valendo
+            assert!(client.apply(&location, "desconhecido", &[]).is_err());
+            assert!(!client.is_broken());
+            client.touched(&location).unwrap();
+            assert_eq!(receiver.recv_timeout(Duration::from_secs(5)).unwrap(), todo.todo_path);
+        }
+
+        #[test]
+        fn a_subscriber_that_stops_reading_does_not_block_the_others() {
+            let (dir, todo) = temp_list(&["[ ] pão"]);
+            let (path, state) = start(dir.path());
+            let location = Location::of(&todo);
+
+            // Inscreve-se e nunca lê os avisos
+            let mut stalled = UnixStream::connect(&path).unwrap();
+            stalled.write_all(b"\"Subscribe\"\n").unwrap();
+            wait_for_subscribers(&state, 1);
+
+            let mut client = Client::open_at(&path, Some(Duration::from_secs(10))).unwrap();
+            let started = Instant::now();
+            for _ in 0..5000 {
+                client.touched(&location).unwrap();
+            }
+            assert!(started.elapsed() < Duration::from_secs(5));
+            // Com os avisos acumulados, a inscrição foi descartada
+            assert_eq!(subscribers(&state), 0);
+
+            // Depois de esgotar o tempo de escrita, o daemon fecha a conexão
+            let mut rest = Vec::new();
+            stalled.set_read_timeout(Some(SUBSCRIBER_WRITE_TIMEOUT * 3)).unwrap();
+            stalled.read_to_end(&mut rest).unwrap();
+        }
+    }
+}
+
+// Roda o daemon em primeiro plano até o processo ser encerrado
+pub fn run() -> Result<(), String> {
+    #[cfg(unix)]
+    return server::run();
+    #[cfg(not(unix))]
+    Err("O daemon só está disponível em sistemas Unix".to_string())
+}
@@ -0,0 +1,117 @@
+// Diferença linha a linha entre duas versões da lista (maior subsequência comum)
+pub enum Change<'a> {
+    Same(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+}
+
+// Acima disso (linhas antigas x novas no trecho alterado), a tabela da subsequência comum
+// ficaria grande demais: o trecho inteiro sai como removido e adicionado
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+pub fn diff<'a>(old: &'a [String], new: &'a [String]) -> Vec<Change<'a>> {
+    // O começo e o fim em comum ficam fora da tabela
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (middle_old, middle_new) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    let mut changes: Vec<Change> = old[..prefix].iter().map(|line| Change::Same(line)).collect();
+    if middle_old.len().saturating_mul(middle_new.len()) <= MAX_DIFF_CELLS {
+        changes.extend(lcs_diff(middle_old, middle_new));
+    } else {
+        changes.extend(middle_old.iter().map(|line| Change::Removed(line)));
+        changes.extend(middle_new.iter().map(|line| Change::Added(line)));
+    }
+    changes.extend(old[old.len() - suffix..].iter().map(|line| Change::Same(line)));
+    changes
+}
+
+fn lcs_diff<'a>(old: &'a [String], new: &'a [String]) -> Vec<Change<'a>> {
+    // lcs[i][j]: tamanho da maior subsequência comum entre old[i..] e new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(&old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            changes.push(Change::Removed(&old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(&new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    // Diferença em notação compacta: " a" igual, "+a" adicionada, "-a" removida
+    fn render(old: &str, new: &str) -> Vec<String> {
+        let (old, new) = (lines(old), lines(new));
+        diff(&old, &new)
+            .into_iter()
+            .map(|change| match change {
+                Change::Same(line) => format!(" {}", line),
+                Change::Added(line) => format!("+{}", line),
+                Change::Removed(line) => format!("-{}", line),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn equal_lists_have_no_changes() {
+        assert_eq!(render("a b c", "a b c"), [" a", " b", " c"]);
+        assert!(render("", "").is_empty());
+    }
+
+    #[test]
+    fn insertions_and_removals_keep_the_common_lines() {
+        assert_eq!(render("a c", "a b c"), [" a", "+b", " c"]);
+        assert_eq!(render("a b c", "a c"), [" a", "-b", " c"]);
+        assert_eq!(render("", "a b"), ["+a", "+b"]);
+        assert_eq!(render("a b", ""), ["-a", "-b"]);
+    }
+
+    #[test]
+    fn replaced_lines_are_removed_then_added() {
+        assert_eq!(render("a b c", "a x c"), [" a", "-b", "+x", " c"]);
+        assert_eq!(render("a b c d", "b a d c"), ["-a", " b", "-c", "+a", " d", "+c"]);
+    }
+
+    #[test]
+    fn huge_changes_fall_back_to_replacing_the_middle() {
+        let old: Vec<String> = (0..2100).map(|n| format!("velha {}", n)).collect();
+        let mut new: Vec<String> = (0..2100).map(|n| format!("nova {}", n)).collect();
+        new[0] = old[0].clone();
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1 + 2099 * 2);
+        assert!(matches!(changes[0], Change::Same("velha 0")));
+        assert!(matches!(changes[1], Change::Removed("velha 1")));
+        assert!(matches!(changes[2100], Change::Added("nova 1")));
+    }
+}
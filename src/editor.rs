@@ -0,0 +1,112 @@
+use crate::private_dir;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn editor_command() -> String {
+    env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad".to_string() } else { "vi".to_string() })
+}
+
+// Arquivo temporário apagado quando sai de escopo, inclusive em erro ou pânico
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// Cria o arquivo do zero (nunca reaproveita nem segue um link existente), legível só pelo usuário
+fn create_temp(name: &str, contents: &str) -> Result<TempFile, String> {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.subsec_nanos());
+    let path = private_dir()?.join(format!("todo-todo-{}-{}-{}", std::process::id(), nanos, name));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&path)
+        .map_err(|e| format!("Não foi possível criar o arquivo temporário: {}", e))?;
+    let temp = TempFile(path);
+    file.write_all(contents.as_bytes())
+        .map_err(|e| format!("Não foi possível gravar o arquivo temporário: {}", e))?;
+    Ok(temp)
+}
+
+// Abre `initial` no $EDITOR (ou $VISUAL) e devolve o texto salvo.
+// `name` vira o fim do nome do arquivo temporário, para o editor reconhecer o tipo.
+// Com `secret` (lista criptografada), só edita se o arquivo temporário puder ficar protegido.
+pub fn edit_text(initial: &str, name: &str, secret: bool) -> Result<String, String> {
+    if secret && !cfg!(unix) {
+        return Err(
+            "Nesta plataforma o texto de uma lista criptografada não pode ir para o editor: passe o texto no comando"
+                .to_string(),
+        );
+    }
+    let temp = create_temp(name, initial)?;
+    let path = &temp.0;
+
+    // O editor pode vir com argumentos, como "code --wait"
+    let command = editor_command();
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or("A variável EDITOR está vazia")?;
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| format!("Não foi possível abrir o editor '{}': {}", command, e));
+
+    match status {
+        Ok(status) if status.success() => {
+            fs::read_to_string(path).map_err(|e| format!("Não foi possível ler o arquivo editado: {}", e))
+        }
+        Ok(_) => Err("O editor terminou com erro; nada foi alterado".to_string()),
+        Err(e) => Err(e),
+    }
+}
+
+// Abre um arquivo na linha indicada, sem esperar o programa terminar.
+// TODO_OPEN_COMMAND define o comando, com {file} e {line} (ex.: "code -g {file}:{line}");
+// sem ele, usa o programa padrão do sistema, que abre o arquivo no começo.
+pub fn open_location(path: &Path, line: usize) -> Result<(), String> {
+    let file = path.to_string_lossy();
+    let words: Vec<String> = match env::var("TODO_OPEN_COMMAND") {
+        Ok(template) => template
+            .split_whitespace()
+            .map(|word| word.replace("{file}", &file).replace("{line}", &line.to_string()))
+            .collect(),
+        Err(_) if cfg!(windows) => vec!["explorer".to_string(), file.to_string()],
+        Err(_) if cfg!(target_os = "macos") => vec!["open".to_string(), file.to_string()],
+        Err(_) => vec!["xdg-open".to_string(), file.to_string()],
+    };
+    let (program, args) = words.split_first().ok_or("A variável TODO_OPEN_COMMAND está vazia")?;
+    Command::new(program)
+        .args(args)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("Não foi possível abrir {}: {}", file, e))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn temp_files_are_private_and_removed_on_drop() {
+        let temp = create_temp("nota.md", "segredo").unwrap();
+        let path = temp.0.clone();
+        assert!(path.to_string_lossy().ends_with("-nota.md"));
+        assert_eq!(fs::metadata(&path).unwrap().mode() & 0o777, 0o600);
+        assert_eq!(fs::metadata(path.parent().unwrap()).unwrap().mode() & 0o077, 0);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "segredo");
+        drop(temp);
+        assert!(!path.exists());
+    }
+}
+
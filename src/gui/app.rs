@@ -2,66 +2,606 @@ use eframe::egui;
 use eframe::App;
 use eframe::NativeOptions;
 use egui::{Vec2, ViewportBuilder};
-use crate::libs::*;
-use std::sync::{Arc, Mutex};
+use todo_todo::*;
+use todo_todo::stats::Stats;
+use std::collections::HashSet;
+use super::board::{Board, Grouping};
+use super::calendar::Calendar;
+use super::prefs::{Prefs, View};
+use super::status::{self, Severity, StatusLog};
+use super::worker::{Command, Event, Worker};
+
+// Lista criptografada esperando a senha (None = principal)
+struct Unlock {
+    list: Option<String>,
+    passphrase: String,
+}
+
+// Ações destrutivas que esperam confirmação do usuário
+enum Confirm {
+    Reset,
+    Restore,
+    Remove(Vec<String>),
+}
 
 pub struct TodoApp {
-    todo: Arc<Mutex<Todo>>,
+    todo: Todo,
+    worker: Worker,
     new_task: String,
+    new_parent: String,
     edit_task: String,
     edit_index: String,
     select_index: String,
-    status_messages: String,
+    done_children: bool,
+    collapsed: HashSet<String>,
+    status: StatusLog,
+    show_history: bool,
+    confirm: Option<Confirm>,
     show_raw_todo: bool,
     show_raw_done: bool,
     //error_message: String,
     dark_mode: bool,
+    font_scale: f32,
+    applied_font_scale: Option<f32>,
+    filter: String,
+    view: View,
+    board: Board,
+    calendar: Calendar,
+    window_size: Option<[f32; 2]>,
+    window_pos: Option<[f32; 2]>,
+    external_change: bool,
+    // Lista aberta (None = principal) e listas nomeadas existentes
+    list: Option<String>,
+    list_names: Vec<String>,
+    new_list: String,
+    move_target: Option<String>,
+    show_archive: bool,
+    archive_lines: Vec<String>,
+    archive_filter: String,
+    // Tarefa aberta no painel de detalhes e o texto da anotação sendo editado
+    detail: Option<String>,
+    note_text: String,
+    unlock: Option<Unlock>,
 }
 
 impl TodoApp {
-    pub fn new(todo: Todo) -> Self {
+    pub fn new(todo: Todo, list: Option<String>, ctx: egui::Context, prefs: Prefs) -> Self {
+        let mut worker = Worker::spawn(&todo, ctx);
+        let unlock = todo.is_locked().then(|| Unlock {
+            list: list.clone(),
+            passphrase: String::new(),
+        });
+        // Sem --list, reabre a última lista usada
+        if list.is_none() && prefs.list.is_some() && unlock.is_none() {
+            worker.send(Command::SwitchList(prefs.list.clone()));
+        }
         Self {
-            todo: Arc::new(Mutex::new(todo)),
+            todo,
+            worker,
             new_task: String::new(),
+            new_parent: String::new(),
             edit_task: String::new(),
             edit_index: String::new(),
             select_index: String::new(),
-            status_messages: String::new(),
-            show_raw_done: false,
-            show_raw_todo: false,
+            done_children: false,
+            collapsed: HashSet::new(),
+            status: StatusLog::default(),
+            show_history: false,
+            confirm: None,
+            show_raw_done: prefs.show_raw_done,
+            show_raw_todo: prefs.show_raw_todo,
             //error_message: String::new(),
-            dark_mode: true,
+            dark_mode: prefs.dark_mode,
+            font_scale: prefs.font_scale,
+            applied_font_scale: None,
+            filter: prefs.filter,
+            view: prefs.view,
+            board: Board::new(Grouping::State),
+            calendar: Calendar::new(),
+            window_size: prefs.window_size,
+            window_pos: prefs.window_pos,
+            external_change: false,
+            list,
+            list_names: Vec::new(),
+            new_list: String::new(),
+            move_target: None,
+            show_archive: false,
+            archive_lines: Vec::new(),
+            archive_filter: String::new(),
+            detail: None,
+            note_text: String::new(),
+            unlock,
+        }
+    }
+
+    fn prefs(&self) -> Prefs {
+        Prefs {
+            dark_mode: self.dark_mode,
+            window_size: self.window_size,
+            window_pos: self.window_pos,
+            show_raw_todo: self.show_raw_todo,
+            show_raw_done: self.show_raw_done,
+            font_scale: self.font_scale,
+            filter: self.filter.clone(),
+            view: self.view,
+            list: self.list.clone(),
+        }
+    }
+
+    // Guarda a geometria atual da janela para salvar ao sair
+    fn track_window(&mut self, ctx: &egui::Context) {
+        let (inner, outer) = ctx.input(|i| (i.viewport().inner_rect, i.viewport().outer_rect));
+        if let Some(rect) = inner {
+            self.window_size = Some([rect.width(), rect.height()]);
+        }
+        if let Some(rect) = outer {
+            self.window_pos = Some([rect.min.x, rect.min.y]);
+        }
+    }
+
+    // Escala as fontes a partir dos tamanhos padrão do egui
+    fn apply_font_scale(&mut self, ctx: &egui::Context) {
+        if self.applied_font_scale == Some(self.font_scale) {
+            return;
+        }
+        let defaults = egui::Style::default().text_styles;
+        let scale = self.font_scale;
+        ctx.all_styles_mut(|style| {
+            for (text_style, font) in style.text_styles.iter_mut() {
+                if let Some(default) = defaults.get(text_style) {
+                    font.size = default.size * scale;
+                }
+            }
+        });
+        self.applied_font_scale = Some(scale);
+    }
+
+    fn set_status(&mut self, severity: Severity, message: String){
+        self.status.push(severity, message);
+    }
+
+    // Texto da tarefa no índice informado (1, 2, 2.1...), se existir
+    fn task_text(&self, index: &str) -> Option<String> {
+        let pos = *self.todo.positions(&[index.to_string()]).first()?;
+        Some(Entry::read_line(&self.todo.todo[pos]).todo_entry)
+    }
+
+    // Avisa sobre índices que não correspondem a nenhuma tarefa
+    fn warn_invalid_indices(&mut self, indices: &[String]) {
+        let invalid: Vec<&str> = indices
+            .iter()
+            .filter(|index| self.task_text(index).is_none())
+            .map(String::as_str)
+            .collect();
+        if !invalid.is_empty() {
+            self.set_status(Severity::Warning, format!("Índices ignorados (não existem): {}", invalid.join(" ")));
         }
     }
 
-    fn set_status(&mut self, message: String){
-        self.status_messages = message;
+    fn has_unsaved_edits(&self) -> bool {
+        !self.edit_index.is_empty() || !self.edit_task.is_empty() || !self.select_index.is_empty()
+    }
+
+    // Aplica os resultados que a thread de persistência já entregou
+    fn handle_events(&mut self) {
+        for event in self.worker.poll() {
+            match event {
+                Event::Finished { command, result, todo } => {
+                    if let Some(todo) = todo {
+                        self.todo = todo;
+                    }
+                    match result {
+                        Ok(()) => {
+                            if let Command::SwitchList(list) | Command::Unlock(list, _) = &command {
+                                self.unlock = None;
+                                // Índices digitados e tarefas recolhidas eram da lista anterior
+                                self.list = list.clone();
+                                self.collapsed.clear();
+                                self.select_index.clear();
+                                self.detail = None;
+                            }
+                            let (severity, message) = success_message(&command, self.todo.no_backup);
+                            self.set_status(severity, message);
+                        }
+                        Err(e) => {
+                            // Trocar para uma lista criptografada e bloqueada abre o pedido de senha
+                            if let Command::SwitchList(list) | Command::Unlock(list, _) = &command
+                                && Todo::locate(list.as_deref()).is_ok_and(|todo| todo.is_locked())
+                            {
+                                self.unlock = Some(Unlock {
+                                    list: list.clone(),
+                                    passphrase: String::new(),
+                                });
+                            }
+                            self.set_status(Severity::Error, e);
+                        }
+                    }
+                }
+                Event::ExternalChange(todo) => {
+                    // Os índices digitados nos campos podem não apontar mais para as mesmas tarefas
+                    if self.has_unsaved_edits() {
+                        self.external_change = true;
+                    }
+                    self.todo = todo;
+                }
+                Event::Lists(names) => self.list_names = names,
+                Event::Archive(lines) => self.archive_lines = lines,
+            }
+        }
+    }
+
+    // Tarefas arquivadas, das mais recentes para as mais antigas, com busca por texto
+    fn show_archive(&mut self, ui: &mut egui::Ui) {
+        let archive = read_archive(&self.archive_lines);
+        ui.horizontal(|ui| {
+            ui.label(format!("🗄 Arquivo ({}):", archive.len()));
+            ui.label("Buscar:");
+            ui.text_edit_singleline(&mut self.archive_filter);
+        });
+        let filter = self.archive_filter.to_lowercase();
+        egui::ScrollArea::vertical()
+            .id_salt("archive_scroll")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for task in archive.iter().rev() {
+                    let matches = task
+                        .lines
+                        .iter()
+                        .any(|entry| entry.todo_entry.to_lowercase().contains(&filter));
+                    if !matches {
+                        continue;
+                    }
+                    let date = task.completed.map_or("—".to_string(), |day| day.format("%d/%m/%Y").to_string());
+                    for (i, entry) in task.lines.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if i == 0 {
+                                ui.weak(&date);
+                            }
+                            ui.add_space(16.0 * entry.depth as f32);
+                            ui.colored_label(egui::Color32::GRAY, &entry.todo_entry);
+                        });
+                    }
+                }
+            });
+    }
+
+    // Painel com o texto completo da tarefa e um editor para a anotação
+    fn show_detail(&mut self, ui: &mut egui::Ui) {
+        let Some(index) = self.detail.clone() else {
+            return;
+        };
+        ui.heading(format!("Tarefa {}", index));
+        match self.task_text(&index) {
+            Some(text) => ui.label(egui::RichText::new(text).strong()),
+            None => ui.colored_label(Severity::Warning.color(), "A tarefa não existe mais"),
+        };
+        ui.separator();
+        ui.label("Anotação:");
+        egui::ScrollArea::vertical()
+            .id_salt("note_scroll")
+            .max_height(ui.available_height() - 40.0)
+            .show(ui, |ui| {
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.note_text)
+                        .desired_width(f32::INFINITY)
+                        .desired_rows(10),
+                );
+            });
+        ui.horizontal(|ui| {
+            if ui.button("Salvar anotação").clicked() {
+                self.worker.send(Command::SetNote(index.clone(), self.note_text.clone()));
+            }
+            if ui.button("Fechar").clicked() {
+                self.detail = None;
+            }
+        });
     }
 
-    fn refresh_todo(&mut self){
-        if let Ok(mut todo) = self.todo.lock(){
-            if let Ok(new_todo) = Todo::new(){
-                *todo = new_todo;
+    // Painel lateral com as listas: clicar troca a lista aberta
+    fn show_lists(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Listas");
+        ui.separator();
+        let mut switch = None;
+        let names = std::iter::once(None).chain(self.list_names.iter().cloned().map(Some));
+        for name in names {
+            let label = name.as_deref().unwrap_or(lists::DEFAULT_LIST);
+            if ui.selectable_label(self.list == name, label).clicked() && self.list != name {
+                switch = Some(name);
             }
         }
+        if let Some(name) = switch {
+            self.worker.send(Command::SwitchList(name));
+        }
+
+        ui.separator();
+        ui.add(egui::TextEdit::singleline(&mut self.new_list).hint_text("Nova lista"));
+        if ui.button("Criar lista").clicked() && !self.new_list.trim().is_empty() {
+            self.worker.send(Command::CreateList(self.new_list.trim().to_string()));
+            self.new_list.clear();
+        }
     }
 }
 
-pub fn run(todo: Todo) {
-    let app = TodoApp::new(todo);
+impl TodoApp {
+    // Lista principal, com o filtro de texto e o dia selecionado no calendário
+    fn show_list(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Lista de Tarefas:");
+            ui.label("Filtro:");
+            ui.text_edit_singleline(&mut self.filter);
+            if !self.filter.is_empty() && ui.button("Limpar").clicked() {
+                self.filter.clear();
+            }
+            if let Some(day) = self.calendar.selected_day {
+                ui.separator();
+                ui.label(format!("Dia: {}", day.format("%d/%m/%Y")));
+                if ui.button("✖").clicked() {
+                    self.calendar.selected_day = None;
+                }
+            }
+        });
+        let filter = self.filter.to_lowercase();
+        let day = self.calendar.selected_day;
+        let labels = self.todo.index_labels();
+        let depths = self.todo.depths();
+        let blockers = self.todo.blockers();
+
+        egui::ScrollArea::vertical()
+            .id_salt("main_scroll")
+            .show(ui, |ui| {
+                // Profundidade da tarefa recolhida cujas subtarefas estão sendo puladas
+                let mut hidden_below: Option<usize> = None;
+                for (pos, line) in self.todo.todo.iter().enumerate() {
+                    let depth = depths[pos];
+                    if hidden_below.is_some_and(|hidden| depth > hidden) {
+                        continue;
+                    }
+                    hidden_below = None;
+
+                    let index = &labels[pos];
+                    let has_children = subtree_end(&depths, pos) > pos + 1;
+                    let collapsed = self.collapsed.contains(index);
+                    if has_children && collapsed {
+                        hidden_below = Some(depth);
+                    }
+
+                    let entry = Entry::read_line(line);
+                    if !filter.is_empty() && !entry.todo_entry.to_lowercase().contains(&filter) {
+                        continue;
+                    }
+                    if day.is_some() && entry.due != day {
+                        continue;
+                    }
+                    let status_icon = if entry.done { "✅" } else { "⭕" };
+                    let task_text = entry.todo_entry.clone();
+
+                    ui.horizontal(|ui| {
+                        ui.add_space(16.0 * depth as f32);
+                        if has_children {
+                            let arrow = if collapsed { "▶" } else { "▼" };
+                            if ui.small_button(arrow).clicked() {
+                                if collapsed {
+                                    self.collapsed.remove(index);
+                                } else {
+                                    self.collapsed.insert(index.clone());
+                                }
+                            }
+                        }
+                        ui.label(index);
+                        ui.label(status_icon);
+                        if let Some(priority) = entry.priority {
+                            ui.strong(format!("({})", priority));
+                        }
+
+                        let blocked_by = &blockers[pos];
+                        if entry.done {
+                            ui.colored_label(egui::Color32::GRAY, task_text);
+                        } else if !blocked_by.is_empty() {
+                            // Tarefas bloqueadas aparecem esmaecidas
+                            ui.weak(format!("🔒 {}", task_text))
+                                .on_hover_text(format!("Bloqueada por {}", blocked_by.join(", ")));
+                        } else {
+                            ui.label(task_text);
+                        }
+                        if let Some(status) = &entry.status {
+                            ui.weak(format!("({})", status));
+                        }
+                        if let Some(recur) = &entry.recur {
+                            ui.weak(format!("↻ {}", recur));
+                        }
+                        if let Some(due) = entry.due {
+                            let text = format!("📅 {}", due.format("%d/%m/%Y"));
+                            if entry.is_overdue() {
+                                ui.colored_label(Severity::Error.color(), text);
+                            } else {
+                                ui.weak(text);
+                            }
+                        }
+                        if let Some(source) = &entry.source
+                            && ui.small_button(format!("↗ {}", source)).on_hover_text("Abrir no editor").clicked()
+                        {
+                            self.worker.send(Command::OpenSource(index.clone()));
+                        }
+                        // Abre o painel de detalhes; o ícone cheio indica que já há anotação
+                        let icon = if entry.note.is_some() { "📝" } else { "🗒" };
+                        if ui.small_button(icon).on_hover_text("Detalhes").clicked() {
+                            self.detail = Some(index.clone());
+                            self.note_text = entry.note.clone().unwrap_or_default();
+                        }
+                    });
+                }
+            });
+    }
+
+    // Pedido de senha de uma lista criptografada; a senha é conferida na thread de persistência
+    fn show_unlock(&mut self, ctx: &egui::Context) {
+        let busy = self.worker.is_busy();
+        let Some(unlock) = &mut self.unlock else {
+            return;
+        };
+
+        let mut submitted = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(egui::Id::new("unlock_dialog")).show(ctx, |ui| {
+            ui.heading("🔒 Lista criptografada");
+            ui.label(format!(
+                "Digite a senha da lista '{}'.",
+                unlock.list.as_deref().unwrap_or(lists::DEFAULT_LIST)
+            ));
+            let response = ui.add(egui::TextEdit::singleline(&mut unlock.passphrase).password(true));
+            response.request_focus();
+            let entered = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+            ui.separator();
+            ui.horizontal(|ui| {
+                let button = ui.add_enabled(!busy, egui::Button::new("Desbloquear"));
+                submitted = !busy && !unlock.passphrase.is_empty() && (button.clicked() || entered);
+                cancelled = ui.button("Cancelar").clicked();
+            });
+            if busy {
+                ui.spinner();
+            }
+        });
+
+        if submitted {
+            let passphrase = std::mem::take(&mut unlock.passphrase);
+            self.worker.send(Command::Unlock(unlock.list.clone(), passphrase));
+        } else if cancelled || modal.should_close() {
+            self.unlock = None;
+        }
+    }
+
+    // Diálogo modal para Reset, Restaurar e remoção em lote, listando o que será perdido
+    fn show_confirm(&mut self, ctx: &egui::Context) {
+        let Some(confirm) = &self.confirm else {
+            return;
+        };
+
+        let (title, warning, lost, command) = match confirm {
+            Confirm::Reset => {
+                let warning = if self.todo.no_backup {
+                    "TODO_NOBACKUP está definido: nenhum backup será criado e a lista não poderá ser restaurada."
+                } else {
+                    "Um backup será criado, substituindo o backup anterior."
+                };
+                let lost: Vec<String> = self.todo.todo.iter().map(|line| Entry::read_line(line).todo_entry).collect();
+                ("Resetar a lista?", warning, lost, Command::Reset)
+            }
+            Confirm::Restore => {
+                let lost: Vec<String> = self.todo.todo.iter().map(|line| Entry::read_line(line).todo_entry).collect();
+                (
+                    "Restaurar do backup?",
+                    "A lista atual será substituída pelo conteúdo do backup.",
+                    lost,
+                    Command::Restore,
+                )
+            }
+            Confirm::Remove(indices) => {
+                // Subtarefas são removidas junto com a tarefa
+                let depths = self.todo.depths();
+                let lost: Vec<String> = self
+                    .todo
+                    .positions(indices)
+                    .into_iter()
+                    .flat_map(|pos| pos..subtree_end(&depths, pos))
+                    .map(|pos| Entry::read_line(&self.todo.todo[pos]).todo_entry)
+                    .collect();
+                ("Remover tarefas?", "As tarefas abaixo serão removidas.", lost, Command::Remove(indices.clone()))
+            }
+        };
+
+        let mut confirmed = false;
+        let mut cancelled = false;
+        let modal = egui::Modal::new(egui::Id::new("confirm_dialog")).show(ctx, |ui| {
+            ui.heading(title);
+            ui.colored_label(Severity::Warning.color(), warning);
+            ui.separator();
+            if lost.is_empty() {
+                ui.label("Nenhuma tarefa será perdida.");
+            } else {
+                ui.label(format!("Serão perdidas {} tarefa(s):", lost.len()));
+                egui::ScrollArea::vertical()
+                    .id_salt("confirm_scroll")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for task in &lost {
+                            ui.label(format!("• {}", task));
+                        }
+                    });
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                confirmed = ui.button("Confirmar").clicked();
+                cancelled = ui.button("Cancelar").clicked();
+            });
+        });
+
+        if confirmed {
+            self.worker.send(command);
+            self.confirm = None;
+            self.select_index.clear();
+        } else if cancelled || modal.should_close() {
+            self.confirm = None;
+        }
+    }
+}
+
+fn success_message(command: &Command, no_backup: bool) -> (Severity, String) {
+    let message = match command {
+        Command::Add(task) => format!("Tarefa '{}' adicionada!", task),
+        Command::AddSub(parent, task) => format!("Subtarefa '{}' adicionada em {}!", task, parent),
+        Command::Edit(index, task) => format!("Tarefa {} editada para '{}'!", index, task),
+        Command::Done(..) => "Status das tarefas alterado!".to_string(),
+        Command::Remove(_) => "Tarefas removidas!".to_string(),
+        Command::SetStatus(index, Some(status)) => format!("Tarefa {} movida para '{}'!", index, status),
+        Command::SetStatus(index, None) => format!("Status da tarefa {} removido!", index),
+        Command::SetDue(index, Some(due)) => format!("Tarefa {} agendada para {}!", index, due.format("%d/%m/%Y")),
+        Command::SetDue(index, None) => format!("Data da tarefa {} removida!", index),
+        Command::SetNote(index, note) if note.trim().is_empty() => format!("Anotação da tarefa {} removida!", index),
+        Command::SetNote(index, _) => format!("Anotação da tarefa {} salva!", index),
+        Command::OpenSource(index) => format!("Abrindo o código da tarefa {}...", index),
+        Command::Sort => "Lista ordenada (pendentes primeiro)!".to_string(),
+        Command::Reset if no_backup => {
+            return (
+                Severity::Warning,
+                "Lista resetada sem backup (TODO_NOBACKUP definido)!".to_string(),
+            );
+        }
+        Command::Reset => "Lista resetada (backup criado)!".to_string(),
+        Command::Restore => "Lista restaurada do backup!".to_string(),
+        Command::Reload => "Lista atualizada!".to_string(),
+        Command::SwitchList(list) => format!("Lista '{}' aberta!", list.as_deref().unwrap_or(lists::DEFAULT_LIST)),
+        Command::CreateList(name) => format!("Lista '{}' criada!", name),
+        Command::Archive => "Tarefas concluídas arquivadas!".to_string(),
+        Command::LoadArchive => "Arquivo atualizado!".to_string(),
+        Command::Unlock(list, _) => format!("Lista '{}' desbloqueada!", list.as_deref().unwrap_or(lists::DEFAULT_LIST)),
+        Command::Move(_, target) => {
+            format!("Tarefas movidas para a lista '{}'!", target.as_deref().unwrap_or(lists::DEFAULT_LIST))
+        }
+    };
+    (Severity::Info, message)
+}
+
+pub fn run(todo: Todo, list: Option<String>) {
+    let prefs = Prefs::load();
+    let list = list.filter(|name| lists::list_name(name).is_some());
+    let [width, height] = prefs.window_size.unwrap_or([800.0, 600.0]);
+    let mut viewport = ViewportBuilder::default()
+        //.with_always_on_top(false)
+        .with_maximized(false)
+        .with_decorations(true)
+        .with_drag_and_drop(true)
+        //.with_icon(None)
+        .with_inner_size(Vec2::new(width, height))
+        //.with_min_inner_size(None)
+        //.with_max_inner_size(None)
+        .with_resizable(true)
+        .with_transparent(false);
+    if let Some([x, y]) = prefs.window_pos {
+        viewport = viewport.with_position(egui::pos2(x, y));
+    }
+
     let native_options = NativeOptions {
-        viewport: ViewportBuilder::default()
-            //.with_always_on_top(false)
-            .with_maximized(false)
-            .with_decorations(true)
-            .with_drag_and_drop(true)
-            //.with_icon(None)
-            //.with_position(None)
-            .with_inner_size(Vec2::new(800.0, 600.0))
-            //.with_min_inner_size(None)
-            //.with_max_inner_size(None)
-            .with_resizable(true)
-            .with_transparent(false),
+        viewport,
         vsync: true,
         multisampling: 0,
         depth_buffer: 0,
@@ -72,13 +612,63 @@ pub fn run(todo: Todo) {
     eframe::run_native(
         "TodoTodo GUI",
         native_options,
-        Box::new(|_cc| Ok(Box::new(app))),
+        Box::new(|cc| Ok(Box::new(TodoApp::new(todo, list, cc.egui_ctx.clone(), prefs)))),
     )
     .expect("Erro ao iniciar a GUI");
 }
 
 impl App for TodoApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_events();
+        self.track_window(ctx);
+        self.apply_font_scale(ctx);
+
+        // --- Barra de Status ---
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if self.worker.is_busy() {
+                    ui.spinner();
+                }
+                if let Some(message) = self.status.latest() {
+                    status::show_message(ui, message);
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    ui.toggle_value(&mut self.show_history, format!("Histórico ({})", self.status.len()));
+                });
+            });
+        });
+
+        if self.show_history {
+            egui::SidePanel::right("status_history").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Histórico");
+                    if ui.button("Limpar").clicked() {
+                        self.status.clear();
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical()
+                    .id_salt("history_scroll")
+                    .show(ui, |ui| {
+                        for message in self.status.iter() {
+                            status::show_message(ui, message);
+                        }
+                    });
+            });
+        }
+
+        egui::SidePanel::left("lists_panel").show(ctx, |ui| self.show_lists(ui));
+
+        if self.detail.is_some() {
+            egui::SidePanel::right("detail_panel")
+                .resizable(true)
+                .default_width(280.0)
+                .show(ctx, |ui| self.show_detail(ui));
+        }
+
+        self.show_confirm(ctx);
+        self.show_unlock(ctx);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             // --- Seção do Tema (sem alterações) ---
             ui.heading("TodoTodo GUI");
@@ -86,6 +676,8 @@ impl App for TodoApp {
                 ui.label("Tema:");
                 ui.radio_value(&mut self.dark_mode, true, "Escuro");
                 ui.radio_value(&mut self.dark_mode, false, "Claro");
+                ui.separator();
+                ui.add(egui::Slider::new(&mut self.font_scale, 0.5..=3.0).text("Fonte"));
             });
 
             if self.dark_mode {
@@ -94,10 +686,17 @@ impl App for TodoApp {
                 ctx.set_visuals(egui::Visuals::light());
             }
 
-            // --- Mensagem de Status (sem alterações) ---
-            if !self.status_messages.is_empty() {
-                let message = self.status_messages.clone(); // Clona para usar no label
-                ui.colored_label(egui::Color32::from_rgb(0, 150, 0), message);
+            // --- Aviso de alteração externa (não bloqueia o resto da interface) ---
+            if self.external_change {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        Severity::Warning.color(),
+                        "⚠ O arquivo TODO foi alterado fora do app. Confira os índices antes de aplicar suas edições.",
+                    );
+                    if ui.button("Ok").clicked() {
+                        self.external_change = false;
+                    }
+                });
             }
             ui.separator();
 
@@ -108,16 +707,21 @@ impl App for TodoApp {
                 .show(ui, |ui| {
                     // --- Linha 1: Adicionar Tarefa ---
                     ui.label("Nova tarefa:");
-                    ui.text_edit_singleline(&mut self.new_task);
-                    if ui.button("Adicionar").clicked() {
-                        if !self.new_task.is_empty() {
-                            if let Ok(todo) = self.todo.lock() {
-                                todo.add(&[self.new_task.clone()]);
-                            }
-                            self.set_status(format!("Tarefa '{}' adicionada!", self.new_task));
-                            self.new_task.clear();
-                            self.refresh_todo();
+                    ui.horizontal(|ui| {
+                        ui.text_edit_singleline(&mut self.new_task);
+                        // Com um índice aqui, a tarefa entra como subtarefa dele
+                        ui.label("Dentro de:");
+                        ui.add(egui::TextEdit::singleline(&mut self.new_parent).desired_width(50.0));
+                    });
+                    if ui.button("Adicionar").clicked() && !self.new_task.is_empty() {
+                        let parent = self.new_parent.trim().to_string();
+                        if parent.is_empty() {
+                            self.worker.send(Command::Add(self.new_task.clone()));
+                        } else {
+                            self.warn_invalid_indices(std::slice::from_ref(&parent));
+                            self.worker.send(Command::AddSub(parent, self.new_task.clone()));
                         }
+                        self.new_task.clear();
                     }
                     ui.end_row();
 
@@ -131,16 +735,10 @@ impl App for TodoApp {
                         // O campo da nova tarefa deve expandir
                         ui.add(egui::TextEdit::singleline(&mut self.edit_task).desired_width(100.0));
                     });
-                    if ui.button("Editar").clicked() {
-                        if !self.edit_index.is_empty() && !self.edit_task.is_empty() {
-                            if let Ok(todo) = self.todo.lock() {
-                                todo.edit(&[self.edit_index.clone(), self.edit_task.clone()]);
-                            }
-                            self.set_status(format!("Tarefa {} editada para '{}'!", self.edit_index, self.edit_task));
-                            self.edit_index.clear();
-                            self.edit_task.clear();
-                            self.refresh_todo();
-                        }
+                    if ui.button("Editar").clicked() && !self.edit_index.is_empty() && !self.edit_task.is_empty() {
+                        self.worker.send(Command::Edit(self.edit_index.clone(), self.edit_task.clone()));
+                        self.edit_index.clear();
+                        self.edit_task.clear();
                     }
                     ui.end_row();
 
@@ -149,30 +747,55 @@ impl App for TodoApp {
                     ui.text_edit_singleline(&mut self.select_index);
                     // Célula com múltiplos botões
                     ui.horizontal(|ui| {
-                        if ui.button("Marcar/Desmarcar").clicked() {
-                            if !self.select_index.is_empty() {
-                                let indices: Vec<String> = self.select_index.split_whitespace().map(String::from).collect();
-                                if let Ok(todo) = self.todo.lock() {
-                                    todo.done(&indices);
-                                }
-                                self.set_status("Status das tarefas alterado!".to_string());
-                                self.select_index.clear(); // Limpa após o uso
-                                self.refresh_todo();
-                            }
+                        if ui.button("Marcar/Desmarcar").clicked() && !self.select_index.is_empty() {
+                            let indices: Vec<String> = self.select_index.split_whitespace().map(String::from).collect();
+                            self.warn_invalid_indices(&indices);
+                            self.worker.send(Command::Done(indices, self.done_children));
+                            self.select_index.clear(); // Limpa após o uso
                         }
-                        if ui.button("Remover").clicked() {
-                            if !self.select_index.is_empty() {
-                                let indices: Vec<String> = self.select_index.split_whitespace().map(String::from).collect();
-                                if let Ok(todo) = self.todo.lock() {
-                                    todo.remove(&indices);
-                                }
-                                self.set_status("Tarefas removidas!".to_string());
-                                self.select_index.clear(); // Limpa após o uso
-                                self.refresh_todo();
+                        if ui.button("Remover").clicked() && !self.select_index.is_empty() {
+                            let indices: Vec<String> = self.select_index.split_whitespace().map(String::from).collect();
+                            self.warn_invalid_indices(&indices);
+                            // Remoção em lote pede confirmação
+                            if indices.len() > 1 {
+                                self.confirm = Some(Confirm::Remove(indices));
+                            } else {
+                                self.worker.send(Command::Remove(indices));
                             }
+                            self.select_index.clear(); // Limpa após o uso
                         }
+                        ui.checkbox(&mut self.done_children, "Incluir subtarefas");
                     });
                     ui.end_row();
+
+                    // --- Linha 4: Mover tarefas para outra lista ---
+                    ui.label("Mover para a lista:");
+                    let targets: Vec<Option<String>> = std::iter::once(None)
+                        .chain(self.list_names.iter().cloned().map(Some))
+                        .filter(|target| *target != self.list)
+                        .collect();
+                    if !targets.contains(&self.move_target) {
+                        self.move_target = targets.first().cloned().flatten();
+                    }
+                    egui::ComboBox::from_id_salt("move_target")
+                        .selected_text(self.move_target.as_deref().unwrap_or(lists::DEFAULT_LIST))
+                        .show_ui(ui, |ui| {
+                            for target in &targets {
+                                let label = target.as_deref().unwrap_or(lists::DEFAULT_LIST);
+                                ui.selectable_value(&mut self.move_target, target.clone(), label);
+                            }
+                        });
+                    if ui
+                        .add_enabled(!targets.is_empty(), egui::Button::new("Mover"))
+                        .clicked()
+                        && !self.select_index.is_empty()
+                    {
+                        let indices: Vec<String> = self.select_index.split_whitespace().map(String::from).collect();
+                        self.warn_invalid_indices(&indices);
+                        self.worker.send(Command::Move(indices, self.move_target.clone()));
+                        self.select_index.clear();
+                    }
+                    ui.end_row();
                 }); // --- Fim do Grid ---
 
             ui.separator();
@@ -180,32 +803,23 @@ impl App for TodoApp {
             // --- Botões de Ação (agora usam a lógica imediata também) ---
             ui.horizontal(|ui| {
                 if ui.button("Ordenar").clicked() {
-                    if let Ok(todo) = self.todo.lock() {
-                        todo.sort();
-                    }
-                    self.set_status("Lista ordenada (pendentes primeiro)!".to_string());
-                    self.refresh_todo();
+                    self.worker.send(Command::Sort);
                 }
 
                 if ui.button("Atualizar Lista").clicked() {
-                    self.refresh_todo();
-                    self.set_status("Lista atualizada!".to_string());
+                    self.worker.send(Command::Reload);
                 }
 
                 if ui.button("Reset").clicked() {
-                    if let Ok(todo) = self.todo.lock() {
-                        todo.reset();
-                    }
-                    self.set_status("Lista resetada (backup criado)!".to_string());
-                    self.refresh_todo();
+                    self.confirm = Some(Confirm::Reset);
                 }
 
                 if ui.button("Restaurar").clicked() {
-                    if let Ok(todo) = self.todo.lock() {
-                        todo.restore();
-                    }
-                    self.set_status("Lista restaurada do backup!".to_string());
-                    self.refresh_todo();
+                    self.confirm = Some(Confirm::Restore);
+                }
+
+                if ui.button("Arquivar concluídas").clicked() {
+                    self.worker.send(Command::Archive);
                 }
             });
 
@@ -221,6 +835,9 @@ impl App for TodoApp {
                     self.show_raw_done = !self.show_raw_done;
                     self.show_raw_todo = false;
                 }
+                if ui.toggle_value(&mut self.show_archive, "Ver Arquivo").clicked() && self.show_archive {
+                    self.worker.send(Command::LoadArchive);
+                }
             });
 
             ui.separator();
@@ -229,62 +846,72 @@ impl App for TodoApp {
             // ... (if self.show_raw_todo, if self.show_raw_done, e a lista principal)
             if self.show_raw_todo {
                 ui.label("📋 Tarefas Pendentes:");
-                if let Ok(todo) = self.todo.lock() {
-                    egui::ScrollArea::vertical()
-                        .id_salt("pending_scroll")
-                        .show(ui, |ui| {
-                        for line in &todo.todo {
-                            let entry = Entry::read_line(line);
-                            if !entry.done {
-                                ui.label(&entry.todo_entry);
-                            }
+                egui::ScrollArea::vertical()
+                    .id_salt("pending_scroll")
+                    .show(ui, |ui| {
+                    for line in &self.todo.todo {
+                        let entry = Entry::read_line(line);
+                        if !entry.done {
+                            ui.label(&entry.todo_entry);
                         }
-                    });
-                }
+                    }
+                });
                 ui.separator();
             }
 
             if self.show_raw_done {
                 ui.label("✅ Tarefas Completas:");
-                if let Ok(todo) = self.todo.lock() {
-                    egui::ScrollArea::vertical()
-                        .id_salt("done_scroll")
-                        .show(ui, |ui| {
-                        for line in &todo.todo {
-                            let entry = Entry::read_line(line);
-                            if entry.done {
-                                ui.label(&entry.todo_entry);
-                            }
+                egui::ScrollArea::vertical()
+                    .id_salt("done_scroll")
+                    .show(ui, |ui| {
+                    for line in &self.todo.todo {
+                        let entry = Entry::read_line(line);
+                        if entry.done {
+                            ui.label(&entry.todo_entry);
                         }
-                    });
-                }
+                    }
+                });
                 ui.separator();
             }
 
-            ui.label("Lista de Tarefas:");
-            
-            egui::ScrollArea::vertical()
-                .id_salt("main_scroll")
-                .show(ui, |ui| {
-                    if let Ok(todo) = self.todo.lock() {
-                        for (index, line) in todo.todo.iter().enumerate() {
-                            let entry = Entry::read_line(line);
-                            let status_icon = if entry.done { "✅" } else { "⭕" };
-                            let task_text = entry.todo_entry.clone();
-                            
-                            ui.horizontal(|ui| {
-                                ui.label(format!("{}", index + 1));
-                                ui.label(status_icon);
-                                
-                                if entry.done {
-                                    ui.colored_label(egui::Color32::GRAY, task_text);
-                                } else {
-                                    ui.label(task_text);
-                                }
-                            });
-                        }
+            if self.show_archive {
+                self.show_archive(ui);
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Visualização:");
+                ui.selectable_value(&mut self.view, View::List, "Lista");
+                ui.selectable_value(&mut self.view, View::Board, "Quadro");
+                ui.selectable_value(&mut self.view, View::Calendar, "Calendário");
+                ui.selectable_value(&mut self.view, View::Stats, "Estatísticas");
+            });
+
+            match self.view {
+                View::List => self.show_list(ui),
+                View::Board => {
+                    if let Some(command) = self.board.show(ui, &self.todo) {
+                        self.worker.send(command);
                     }
-                });
+                }
+                View::Calendar => {
+                    if let Some(command) = self.calendar.show(ui, &self.todo) {
+                        self.worker.send(command);
+                    }
+                    ui.separator();
+                    self.show_list(ui);
+                }
+                View::Stats => {
+                    let stats = Stats::collect(&self.todo.todo, &self.archive_lines, today());
+                    super::stats::show(ui, &stats);
+                }
+            }
         });
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Err(e) = self.prefs().save() {
+            eprintln!("{}", e);
+        }
+    }
 }
@@ -0,0 +1,215 @@
+use todo_todo::*;
+use eframe::egui;
+use super::worker::Command;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Grouping {
+    State,
+    Status,
+}
+
+#[derive(Clone, PartialEq)]
+enum ColumnKey {
+    Pending,
+    Done,
+    Status(Option<String>),
+}
+
+struct Column {
+    title: String,
+    key: ColumnKey,
+    cards: Vec<usize>,
+}
+
+impl ColumnKey {
+    fn accepts(&self, entry: &Entry) -> bool {
+        match self {
+            ColumnKey::Pending => !entry.done,
+            ColumnKey::Done => entry.done,
+            ColumnKey::Status(status) => &entry.status == status,
+        }
+    }
+
+    // Comando que move a tarefa para esta coluna, se ela ainda não estiver nela
+    fn move_command(&self, index: &str, entry: &Entry) -> Option<Command> {
+        if self.accepts(entry) {
+            return None;
+        }
+        let index = index.to_string();
+        match self {
+            ColumnKey::Pending | ColumnKey::Done => Some(Command::Done(vec![index], false)),
+            ColumnKey::Status(status) => Some(Command::SetStatus(index, status.clone())),
+        }
+    }
+}
+
+// Quadro Kanban: as colunas vêm do estado (pendente/concluída) ou do campo status
+pub struct Board {
+    pub grouping: Grouping,
+    extra_columns: Vec<String>,
+    new_column: String,
+}
+
+impl Board {
+    pub fn new(grouping: Grouping) -> Self {
+        Self {
+            grouping,
+            extra_columns: Vec::new(),
+            new_column: String::new(),
+        }
+    }
+
+    fn columns(&self, entries: &[Entry]) -> Vec<Column> {
+        let keys: Vec<(String, ColumnKey)> = match self.grouping {
+            Grouping::State => vec![
+                ("Pendentes".to_string(), ColumnKey::Pending),
+                ("Concluídas".to_string(), ColumnKey::Done),
+            ],
+            Grouping::Status => {
+                let mut statuses: Vec<String> = Vec::new();
+                for status in entries.iter().filter_map(|entry| entry.status.clone()).chain(self.extra_columns.clone()) {
+                    if !statuses.contains(&status) {
+                        statuses.push(status);
+                    }
+                }
+                let mut keys = vec![("Sem status".to_string(), ColumnKey::Status(None))];
+                keys.extend(statuses.into_iter().map(|status| (status.clone(), ColumnKey::Status(Some(status)))));
+                keys
+            }
+        };
+
+        keys.into_iter()
+            .map(|(title, key)| {
+                let cards = entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, entry)| key.accepts(entry))
+                    .map(|(pos, _)| pos)
+                    .collect();
+                Column { title, key, cards }
+            })
+            .collect()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, todo: &Todo) -> Option<Command> {
+        ui.horizontal(|ui| {
+            ui.label("Colunas por:");
+            ui.radio_value(&mut self.grouping, Grouping::State, "Estado");
+            ui.radio_value(&mut self.grouping, Grouping::Status, "Status");
+            if self.grouping == Grouping::Status {
+                ui.separator();
+                ui.add(egui::TextEdit::singleline(&mut self.new_column).desired_width(100.0));
+                let name = self.new_column.trim().to_string();
+                if ui.button("Nova coluna").clicked() && !name.is_empty() && !name.contains(char::is_whitespace) {
+                    self.extra_columns.push(name);
+                    self.new_column.clear();
+                }
+            }
+        });
+        ui.separator();
+
+        let entries: Vec<Entry> = todo.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let labels = todo.index_labels();
+        let columns = self.columns(&entries);
+        let mut command = None;
+
+        egui::ScrollArea::both().id_salt("board_scroll").show(ui, |ui| {
+            ui.columns(columns.len(), |uis| {
+                for (ui, column) in uis.iter_mut().zip(&columns) {
+                    let frame = egui::Frame::group(ui.style()).inner_margin(6.0);
+                    let (_, dropped) = ui.dnd_drop_zone::<usize, ()>(frame, |ui| {
+                        ui.set_min_width(ui.available_width());
+                        ui.strong(format!("{} ({})", column.title, column.cards.len()));
+                        ui.separator();
+                        for pos in &column.cards {
+                            let entry = &entries[*pos];
+                            let id = egui::Id::new(("board_card", *pos));
+                            ui.dnd_drag_source(id, *pos, |ui| {
+                                egui::Frame::group(ui.style()).show(ui, |ui| {
+                                    ui.set_width(ui.available_width());
+                                    let text = format!("{} {}", labels[*pos], entry.todo_entry);
+                                    if entry.done {
+                                        ui.colored_label(egui::Color32::GRAY, text);
+                                    } else {
+                                        ui.label(text);
+                                    }
+                                });
+                            });
+                        }
+                    });
+
+                    if let Some(pos) = dropped
+                        && let Some(entry) = entries.get(*pos)
+                    {
+                        command = column.key.move_command(&labels[*pos], entry);
+                    }
+                }
+            });
+        });
+
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<Entry> {
+        [
+            "[ ] a status:doing",
+            "[*] b",
+            "[ ] c",
+            "[ ] d status:review",
+            "[*] e status:doing",
+        ]
+        .iter()
+        .map(|line| Entry::read_line(line))
+        .collect()
+    }
+
+    fn layout(columns: &[Column]) -> Vec<(&str, Vec<usize>)> {
+        columns.iter().map(|column| (column.title.as_str(), column.cards.clone())).collect()
+    }
+
+    #[test]
+    fn state_columns_split_pending_and_done() {
+        let board = Board::new(Grouping::State);
+        assert_eq!(layout(&board.columns(&entries())), [("Pendentes", vec![0, 2, 3]), ("Concluídas", vec![1, 4])]);
+    }
+
+    #[test]
+    fn status_columns_follow_the_order_of_first_use() {
+        let mut board = Board::new(Grouping::Status);
+        board.extra_columns = vec!["review".to_string(), "bloqueada".to_string()];
+        assert_eq!(layout(&board.columns(&entries())), [
+            ("Sem status", vec![1, 2]),
+            ("doing", vec![0, 4]),
+            ("review", vec![3]),
+            ("bloqueada", vec![]),
+        ]);
+        assert_eq!(layout(&Board::new(Grouping::Status).columns(&[])), [("Sem status", vec![])]);
+    }
+
+    #[test]
+    fn dropping_a_card_moves_it_only_to_another_column() {
+        let entries = entries();
+        let doing = ColumnKey::Status(Some("doing".to_string()));
+        assert!(doing.move_command("1", &entries[0]).is_none());
+        assert!(ColumnKey::Pending.move_command("1", &entries[0]).is_none());
+        assert!(matches!(
+            doing.move_command("3", &entries[2]),
+            Some(Command::SetStatus(index, Some(status))) if index == "3" && status == "doing"
+        ));
+        assert!(matches!(
+            ColumnKey::Status(None).move_command("1", &entries[0]),
+            Some(Command::SetStatus(index, None)) if index == "1"
+        ));
+        assert!(matches!(
+            ColumnKey::Done.move_command("1", &entries[0]),
+            Some(Command::Done(indices, false)) if indices == ["1"]
+        ));
+        assert!(matches!(ColumnKey::Pending.move_command("2", &entries[1]), Some(Command::Done(..))));
+    }
+}
+
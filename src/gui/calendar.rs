@@ -0,0 +1,284 @@
+use todo_todo::*;
+use chrono::{Datelike, Days, Months, NaiveDate};
+use eframe::egui;
+use super::worker::Command;
+
+const MONTHS: [&str; 12] = [
+    "Janeiro", "Fevereiro", "Março", "Abril", "Maio", "Junho",
+    "Julho", "Agosto", "Setembro", "Outubro", "Novembro", "Dezembro",
+];
+const WEEKDAYS: [&str; 7] = ["Seg", "Ter", "Qua", "Qui", "Sex", "Sáb", "Dom"];
+
+// Quantas tarefas cabem numa célula do mês antes de resumir em "+N"
+const MONTH_CELL_TASKS: usize = 3;
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Days::new(day.weekday().num_days_from_monday() as u64)
+}
+
+// Tarefas que vencem no dia, na ordem da lista
+fn tasks_on(entries: &[Entry], day: NaiveDate) -> Vec<(usize, &Entry)> {
+    entries.iter().enumerate().filter(|(_, entry)| entry.due == Some(day)).collect()
+}
+
+// Pendentes sem vencimento, mostradas na faixa "Sem data"
+fn undated(entries: &[Entry]) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| entry.due.is_none() && !entry.done)
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+// Calendário mensal/semanal que posiciona as tarefas pela data de vencimento
+pub struct Calendar {
+    week_mode: bool,
+    cursor: NaiveDate,
+    pub selected_day: Option<NaiveDate>,
+}
+
+impl Calendar {
+    pub fn new() -> Self {
+        Self {
+            week_mode: false,
+            cursor: today(),
+            selected_day: None,
+        }
+    }
+
+    // Dias exibidos: semanas completas (segunda a domingo) cobrindo o período
+    fn days(&self) -> Vec<NaiveDate> {
+        let (first, last) = if self.week_mode {
+            let monday = monday_of(self.cursor);
+            (monday, monday + Days::new(6))
+        } else {
+            let first = self.cursor.with_day(1).unwrap_or(self.cursor);
+            let last = first + Months::new(1) - Days::new(1);
+            (monday_of(first), monday_of(last) + Days::new(6))
+        };
+        first.iter_days().take_while(|day| *day <= last).collect()
+    }
+
+    fn title(&self) -> String {
+        if self.week_mode {
+            let monday = monday_of(self.cursor);
+            format!("Semana de {}", monday.format("%d/%m/%Y"))
+        } else {
+            format!("{} {}", MONTHS[self.cursor.month0() as usize], self.cursor.year())
+        }
+    }
+
+    fn step(&mut self, forward: bool) {
+        self.cursor = match (self.week_mode, forward) {
+            (true, true) => self.cursor + Days::new(7),
+            (true, false) => self.cursor - Days::new(7),
+            (false, true) => self.cursor + Months::new(1),
+            (false, false) => self.cursor - Months::new(1),
+        };
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, todo: &Todo) -> Option<Command> {
+        ui.horizontal(|ui| {
+            if ui.button("◀").clicked() {
+                self.step(false);
+            }
+            if ui.button("Hoje").clicked() {
+                self.cursor = today();
+            }
+            if ui.button("▶").clicked() {
+                self.step(true);
+            }
+            ui.strong(self.title());
+            ui.separator();
+            ui.radio_value(&mut self.week_mode, false, "Mês");
+            ui.radio_value(&mut self.week_mode, true, "Semana");
+        });
+
+        let entries: Vec<Entry> = todo.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let labels = todo.index_labels();
+        let mut command = None;
+
+        // Faixa com as tarefas pendentes sem data: arraste para um dia para agendar
+        let frame = egui::Frame::group(ui.style()).inner_margin(4.0);
+        let (_, dropped) = ui.dnd_drop_zone::<usize, ()>(frame, |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.horizontal_wrapped(|ui| {
+                ui.label("Sem data:");
+                for pos in undated(&entries) {
+                    task_chip(ui, pos, &labels[pos], &entries[pos]);
+                }
+            });
+        });
+        if let Some(pos) = dropped
+            && entries.get(*pos).is_some_and(|entry| entry.due.is_some())
+        {
+            command = Some(Command::SetDue(labels[*pos].clone(), None));
+        }
+
+        let days = self.days();
+        let spacing = ui.spacing().item_spacing.x;
+        let cell_width = ((ui.available_width() - spacing * 6.0) / 7.0).max(40.0);
+        let cell_height = if self.week_mode { 200.0 } else { 70.0 };
+
+        egui::Grid::new("calendar_grid")
+            .num_columns(7)
+            .min_col_width(cell_width)
+            .max_col_width(cell_width)
+            .show(ui, |ui| {
+                for weekday in WEEKDAYS {
+                    ui.strong(weekday);
+                }
+                ui.end_row();
+
+                for (i, day) in days.iter().enumerate() {
+                    if let Some(cmd) = self.day_cell(ui, *day, &entries, &labels, [cell_width, cell_height]) {
+                        command = Some(cmd);
+                    }
+                    if i % 7 == 6 {
+                        ui.end_row();
+                    }
+                }
+            });
+
+        command
+    }
+
+    fn day_cell(
+        &mut self,
+        ui: &mut egui::Ui,
+        day: NaiveDate,
+        entries: &[Entry],
+        labels: &[String],
+        size: [f32; 2],
+    ) -> Option<Command> {
+        let in_period = self.week_mode || day.month() == self.cursor.month();
+        let mut frame = egui::Frame::group(ui.style()).inner_margin(3.0);
+        if day == today() {
+            frame = frame.stroke(egui::Stroke::new(2.0, ui.visuals().selection.bg_fill));
+        }
+
+        let (_, dropped) = ui.dnd_drop_zone::<usize, ()>(frame, |ui| {
+            ui.set_min_size(egui::vec2(size[0] - 8.0, size[1]));
+            ui.set_max_width(size[0] - 8.0);
+
+            let label = if in_period {
+                egui::RichText::new(day.day().to_string())
+            } else {
+                egui::RichText::new(day.day().to_string()).weak()
+            };
+            // Clicar no dia filtra a lista; clicar de novo remove o filtro
+            if ui.selectable_label(self.selected_day == Some(day), label).clicked() {
+                self.selected_day = if self.selected_day == Some(day) { None } else { Some(day) };
+            }
+
+            let tasks = tasks_on(entries, day);
+            let limit = if self.week_mode { tasks.len() } else { MONTH_CELL_TASKS };
+            for (pos, entry) in tasks.iter().take(limit) {
+                task_chip(ui, *pos, &labels[*pos], entry);
+            }
+            if tasks.len() > limit {
+                ui.weak(format!("+{}", tasks.len() - limit));
+            }
+        });
+
+        let pos = *dropped?;
+        let entry = entries.get(pos)?;
+        if entry.due == Some(day) {
+            return None;
+        }
+        Some(Command::SetDue(labels[pos].clone(), Some(day)))
+    }
+}
+
+fn task_chip(ui: &mut egui::Ui, pos: usize, index: &str, entry: &Entry) {
+    let id = egui::Id::new(("calendar_task", pos));
+    ui.dnd_drag_source(id, pos, |ui| {
+        let text = egui::RichText::new(format!("{} {}", index, entry.todo_entry)).small();
+        let text = if entry.done {
+            text.color(egui::Color32::GRAY).strikethrough()
+        } else if entry.is_overdue() {
+            text.color(egui::Color32::from_rgb(200, 40, 40)).strong()
+        } else {
+            text
+        };
+        ui.add(egui::Label::new(text).truncate());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, DATE_FORMAT).unwrap()
+    }
+
+    fn calendar(cursor: &str, week_mode: bool) -> Calendar {
+        Calendar {
+            week_mode,
+            cursor: date(cursor),
+            selected_day: None,
+        }
+    }
+
+    fn span(calendar: &Calendar) -> (usize, NaiveDate, NaiveDate) {
+        let days = calendar.days();
+        (days.len(), days[0], days[days.len() - 1])
+    }
+
+    #[test]
+    fn the_month_is_shown_in_whole_weeks() {
+        // Fevereiro de 2026 começa num domingo e termina num sábado
+        assert_eq!(span(&calendar("2026-02-14", false)), (35, date("2026-01-26"), date("2026-03-01")));
+        // Fevereiro de 2027 começa numa segunda e termina num domingo: quatro semanas exatas
+        assert_eq!(span(&calendar("2027-02-01", false)), (28, date("2027-02-01"), date("2027-02-28")));
+        // Agosto de 2026 ocupa seis linhas
+        assert_eq!(span(&calendar("2026-08-31", false)), (42, date("2026-07-27"), date("2026-09-06")));
+        assert_eq!(span(&calendar("2026-02-14", true)), (7, date("2026-02-09"), date("2026-02-15")));
+        assert_eq!(span(&calendar("2026-02-09", true)), (7, date("2026-02-09"), date("2026-02-15")));
+    }
+
+    #[test]
+    fn stepping_moves_by_month_or_week() {
+        let mut month = calendar("2026-01-31", false);
+        month.step(true);
+        assert_eq!(month.cursor, date("2026-02-28"));
+        assert_eq!(month.title(), "Fevereiro 2026");
+        month.step(false);
+        month.step(false);
+        assert_eq!(month.title(), "Dezembro 2025");
+
+        let mut week = calendar("2026-12-30", true);
+        week.step(true);
+        assert_eq!(week.cursor, date("2027-01-06"));
+        assert_eq!(week.title(), "Semana de 04/01/2027");
+    }
+
+    #[test]
+    fn tasks_are_placed_on_their_due_date() {
+        let entries: Vec<Entry> = [
+            "[ ] a due:2026-02-10",
+            "[ ] b",
+            "[*] c due:2026-02-10",
+            "[*] d",
+            "[ ] e due:2026-03-01",
+        ]
+        .iter()
+        .map(|line| Entry::read_line(line))
+        .collect();
+
+        let positions = |day| tasks_on(&entries, date(day)).iter().map(|(pos, _)| *pos).collect::<Vec<_>>();
+        assert_eq!(positions("2026-02-10"), [0, 2]);
+        assert_eq!(positions("2026-03-01"), [4]);
+        assert!(positions("2026-02-11").is_empty());
+        // Concluídas sem data não vão para a faixa "Sem data"
+        assert_eq!(undated(&entries), [1]);
+
+        // O dia 1º de março aparece no fim da grade de fevereiro
+        let days = calendar("2026-02-14", false).days();
+        let placed: usize = days.iter().map(|day| tasks_on(&entries, *day).len()).sum();
+        assert_eq!(placed, 3);
+    }
+}
+
@@ -1,2 +1,7 @@
 pub mod app;
-
+mod board;
+mod calendar;
+mod prefs;
+mod stats;
+mod status;
+mod worker;
@@ -0,0 +1,218 @@
+use todo_todo::lists;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum View {
+    List,
+    Board,
+    Calendar,
+    Stats,
+}
+
+impl View {
+    fn as_str(self) -> &'static str {
+        match self {
+            View::List => "list",
+            View::Board => "board",
+            View::Calendar => "calendar",
+            View::Stats => "stats",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "board" => View::Board,
+            "calendar" => View::Calendar,
+            "stats" => View::Stats,
+            _ => View::List,
+        }
+    }
+}
+
+// Preferências da GUI salvas entre sessões, num arquivo simples "chave=valor"
+pub struct Prefs {
+    pub dark_mode: bool,
+    pub window_size: Option<[f32; 2]>,
+    pub window_pos: Option<[f32; 2]>,
+    pub show_raw_todo: bool,
+    pub show_raw_done: bool,
+    pub font_scale: f32,
+    pub filter: String,
+    pub view: View,
+    // Última lista aberta (None = principal)
+    pub list: Option<String>,
+}
+
+impl Default for Prefs {
+    fn default() -> Self {
+        Self {
+            dark_mode: true,
+            window_size: None,
+            window_pos: None,
+            show_raw_todo: false,
+            show_raw_done: false,
+            font_scale: 1.0,
+            filter: String::new(),
+            view: View::List,
+            list: None,
+        }
+    }
+}
+
+fn prefs_path() -> Option<PathBuf> {
+    match env::var("TODO_GUI_CONFIG") {
+        Ok(path) => Some(PathBuf::from(path)),
+        Err(_) => Some(dirs::config_dir()?.join("todo-todo").join("gui.conf")),
+    }
+}
+
+fn parse_pair(value: &str) -> Option<[f32; 2]> {
+    let (x, y) = value.split_once(',')?;
+    Some([x.trim().parse().ok()?, y.trim().parse().ok()?])
+}
+
+impl Prefs {
+    pub fn load() -> Self {
+        prefs_path().map_or_else(Self::default, |path| Self::load_from(&path))
+    }
+
+    // Valores ausentes ou inválidos ficam com o padrão
+    fn load_from(path: &Path) -> Self {
+        let mut prefs = Self::default();
+        let Ok(contents) = fs::read_to_string(path) else {
+            return prefs;
+        };
+
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            match key.trim() {
+                "dark_mode" => prefs.dark_mode = value.trim() == "true",
+                "window_size" => prefs.window_size = parse_pair(value),
+                "window_pos" => prefs.window_pos = parse_pair(value),
+                "show_raw_todo" => prefs.show_raw_todo = value.trim() == "true",
+                "show_raw_done" => prefs.show_raw_done = value.trim() == "true",
+                "font_scale" => {
+                    if let Ok(scale) = value.trim().parse::<f32>() {
+                        prefs.font_scale = scale.clamp(0.5, 3.0);
+                    }
+                }
+                "filter" => prefs.filter = value.to_string(),
+                "view" => prefs.view = View::parse(value.trim()),
+                "list" => prefs.list = lists::list_name(value.trim()).map(String::from),
+                _ => {}
+            }
+        }
+        prefs
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = prefs_path().ok_or("Não foi possível determinar o diretório de configuração")?;
+        self.save_to(&path)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Não foi possível criar {}: {}", dir.display(), e))?;
+        }
+
+        let mut data = String::new();
+        data.push_str(&format!("dark_mode={}\n", self.dark_mode));
+        if let Some([w, h]) = self.window_size {
+            data.push_str(&format!("window_size={},{}\n", w, h));
+        }
+        if let Some([x, y]) = self.window_pos {
+            data.push_str(&format!("window_pos={},{}\n", x, y));
+        }
+        data.push_str(&format!("show_raw_todo={}\n", self.show_raw_todo));
+        data.push_str(&format!("show_raw_done={}\n", self.show_raw_done));
+        data.push_str(&format!("font_scale={}\n", self.font_scale));
+        // O filtro é texto livre: quebras de linha quebrariam o formato
+        data.push_str(&format!("filter={}\n", self.filter.replace(['\n', '\r'], " ")));
+
+        data.push_str(&format!("view={}\n", self.view.as_str()));
+        data.push_str(&format!("list={}\n", self.list.as_deref().unwrap_or(lists::DEFAULT_LIST)));
+
+        fs::write(path, data).map_err(|e| format!("Não foi possível salvar as preferências: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_default(prefs: &Prefs) {
+        assert!(prefs.dark_mode && !prefs.show_raw_todo && !prefs.show_raw_done);
+        assert_eq!((prefs.window_size, prefs.window_pos), (None, None));
+        assert_eq!(prefs.font_scale, 1.0);
+        assert_eq!(prefs.filter, "");
+        assert!(prefs.view == View::List);
+        assert_eq!(prefs.list, None);
+    }
+
+    #[test]
+    fn saved_prefs_load_back() {
+        let dir = tempfile::tempdir().unwrap();
+        // O diretório de configuração é criado ao salvar
+        let path = dir.path().join("todo-todo").join("gui.conf");
+        let prefs = Prefs {
+            dark_mode: false,
+            window_size: Some([800.0, 600.5]),
+            window_pos: Some([-10.0, 20.0]),
+            show_raw_todo: true,
+            show_raw_done: true,
+            font_scale: 1.25,
+            filter: "#casa\nurgente=sim".to_string(),
+            view: View::Calendar,
+            list: Some("trabalho".to_string()),
+        };
+        prefs.save_to(&path).unwrap();
+
+        let loaded = Prefs::load_from(&path);
+        assert!(!loaded.dark_mode && loaded.show_raw_todo && loaded.show_raw_done);
+        assert_eq!(loaded.window_size, Some([800.0, 600.5]));
+        assert_eq!(loaded.window_pos, Some([-10.0, 20.0]));
+        assert_eq!(loaded.font_scale, 1.25);
+        // A quebra de linha do filtro vira espaço; o '=' do valor é preservado
+        assert_eq!(loaded.filter, "#casa urgente=sim");
+        assert!(loaded.view == View::Calendar);
+        assert_eq!(loaded.list.as_deref(), Some("trabalho"));
+
+        // A lista principal é salva pelo nome padrão e volta como None
+        Prefs::default().save_to(&path).unwrap();
+        assert_default(&Prefs::load_from(&path));
+    }
+
+    #[test]
+    fn a_missing_or_corrupt_file_falls_back_to_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_default(&Prefs::load_from(&dir.path().join("nao-existe.conf")));
+
+        let path = dir.path().join("gui.conf");
+        let corrupt = [
+            "lixo sem igual",
+            "window_size=800",
+            "window_pos=x,y",
+            "font_scale=muito",
+            "view=kanban",
+            "chave_nova=1",
+            "=",
+        ];
+        fs::write(&path, corrupt.join("\n")).unwrap();
+        assert_default(&Prefs::load_from(&path));
+
+        // Arquivo que nem é texto
+        fs::write(&path, [0xff, 0xfe, 0x00, b'=', 0x80]).unwrap();
+        assert_default(&Prefs::load_from(&path));
+
+        // Valores fora da faixa são limitados; o resto do arquivo continua valendo
+        fs::write(&path, "font_scale=10\nwindow_size=1,2,3\ndark_mode=false\n").unwrap();
+        let prefs = Prefs::load_from(&path);
+        assert_eq!(prefs.font_scale, 3.0);
+        assert_eq!(prefs.window_size, None);
+        assert!(!prefs.dark_mode);
+    }
+}
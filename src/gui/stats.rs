@@ -0,0 +1,110 @@
+use todo_todo::stats::{Breakdown, Period, Stats};
+use eframe::egui;
+use egui_plot::{Bar, BarChart, Legend, Plot};
+
+const ADDED_COLOR: egui::Color32 = egui::Color32::from_rgb(70, 130, 220);
+const COMPLETED_COLOR: egui::Color32 = egui::Color32::from_rgb(60, 170, 90);
+const PENDING_COLOR: egui::Color32 = egui::Color32::from_rgb(220, 170, 40);
+
+// Aba de estatísticas: resumo, gráficos por dia/semana e divisão por tag e prioridade
+pub fn show(ui: &mut egui::Ui, stats: &Stats) {
+    egui::ScrollArea::vertical().id_salt("stats_scroll").show(ui, |ui| {
+        egui::Grid::new("stats_summary").num_columns(2).show(ui, |ui| {
+            ui.label("Pendentes:");
+            ui.strong(stats.pending.to_string());
+            ui.end_row();
+            ui.label("Concluídas:");
+            ui.strong(stats.done.to_string());
+            ui.end_row();
+            ui.label("Arquivadas:");
+            ui.strong(stats.archived.to_string());
+            ui.end_row();
+            ui.label("Conclusão:");
+            ui.add(
+                egui::ProgressBar::new(stats.completion_rate as f32)
+                    .desired_width(200.0)
+                    .show_percentage(),
+            );
+            ui.end_row();
+            ui.label("Tempo médio até concluir:");
+            match stats.average_days_to_complete {
+                Some(days) => ui.strong(format!("{:.1} dia(s)", days)),
+                None => ui.weak("sem dados"),
+            };
+            ui.end_row();
+        });
+
+        ui.separator();
+        ui.strong("Criadas x concluídas por dia");
+        period_plot(ui, "stats_days", &stats.days, |start| start[5..].to_string());
+        ui.strong("Criadas x concluídas por semana");
+        period_plot(ui, "stats_weeks", &stats.weeks, |start| format!("sem. {}", &start[5..]));
+
+        ui.separator();
+        ui.columns(2, |columns| {
+            breakdown_table(&mut columns[0], "Por tag", &stats.by_tag);
+            breakdown_table(&mut columns[1], "Por prioridade", &stats.by_priority);
+        });
+    });
+}
+
+// Barras lado a lado para cada período; o eixo X mostra o início do período
+fn period_plot(ui: &mut egui::Ui, id: &str, periods: &[Period], label: impl Fn(&str) -> String) {
+    let bars = |offset: f64, value: fn(&Period) -> usize| -> Vec<Bar> {
+        periods
+            .iter()
+            .enumerate()
+            .map(|(i, period)| Bar::new(i as f64 + offset, value(period) as f64).width(0.35))
+            .collect()
+    };
+    let added = BarChart::new("Criadas", bars(-0.2, |period| period.added)).color(ADDED_COLOR);
+    let completed = BarChart::new("Concluídas", bars(0.2, |period| period.completed)).color(COMPLETED_COLOR);
+    let labels: Vec<String> = periods.iter().map(|period| label(&period.start)).collect();
+
+    Plot::new(id)
+        .height(160.0)
+        .legend(Legend::default())
+        .allow_zoom(false)
+        .allow_drag(false)
+        .allow_scroll(false)
+        .include_y(0.0)
+        .x_axis_formatter(move |mark, _| {
+            let i = mark.value.round();
+            if (mark.value - i).abs() > f64::EPSILON || i < 0.0 {
+                return String::new();
+            }
+            labels.get(i as usize).cloned().unwrap_or_default()
+        })
+        .show(ui, |plot| {
+            plot.bar_chart(added);
+            plot.bar_chart(completed);
+        });
+}
+
+fn breakdown_table(ui: &mut egui::Ui, title: &str, groups: &[Breakdown]) {
+    ui.strong(title);
+    if groups.is_empty() {
+        ui.weak("nenhuma");
+        return;
+    }
+    let max = groups.iter().map(|group| group.pending + group.done).max().unwrap_or(1).max(1);
+    egui::Grid::new(title).num_columns(3).show(ui, |ui| {
+        for group in groups {
+            ui.label(&group.name);
+            let total = group.pending + group.done;
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(150.0, 12.0), egui::Sense::hover());
+            let done_width = rect.width() * group.done as f32 / max as f32;
+            let pending_width = rect.width() * group.pending as f32 / max as f32;
+            let painter = ui.painter();
+            let done_rect = egui::Rect::from_min_size(rect.min, egui::vec2(done_width, rect.height()));
+            let pending_rect = egui::Rect::from_min_size(
+                rect.min + egui::vec2(done_width, 0.0),
+                egui::vec2(pending_width, rect.height()),
+            );
+            painter.rect_filled(done_rect, 0.0, COMPLETED_COLOR);
+            painter.rect_filled(pending_rect, 0.0, PENDING_COLOR);
+            ui.weak(format!("{} de {} concluída(s)", group.done, total));
+            ui.end_row();
+        }
+    });
+}
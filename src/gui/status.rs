@@ -0,0 +1,85 @@
+use eframe::egui;
+use std::time::Instant;
+
+// Quantidade máxima de mensagens guardadas no histórico
+const MAX_HISTORY: usize = 200;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn color(self) -> egui::Color32 {
+        match self {
+            Severity::Info => egui::Color32::from_rgb(0, 150, 0),
+            Severity::Warning => egui::Color32::from_rgb(200, 140, 0),
+            Severity::Error => egui::Color32::from_rgb(200, 40, 40),
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            Severity::Info => "ℹ",
+            Severity::Warning => "⚠",
+            Severity::Error => "❌",
+        }
+    }
+}
+
+pub struct StatusMessage {
+    pub severity: Severity,
+    pub text: String,
+    pub at: Instant,
+}
+
+#[derive(Default)]
+pub struct StatusLog {
+    messages: Vec<StatusMessage>,
+}
+
+impl StatusLog {
+    pub fn push(&mut self, severity: Severity, text: String) {
+        if self.messages.len() == MAX_HISTORY {
+            self.messages.remove(0);
+        }
+        self.messages.push(StatusMessage {
+            severity,
+            text,
+            at: Instant::now(),
+        });
+    }
+
+    pub fn latest(&self) -> Option<&StatusMessage> {
+        self.messages.last()
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    // Mensagens da mais recente para a mais antiga
+    pub fn iter(&self) -> impl Iterator<Item = &StatusMessage> {
+        self.messages.iter().rev()
+    }
+}
+
+pub fn show_message(ui: &mut egui::Ui, message: &StatusMessage) {
+    let seconds = message.at.elapsed().as_secs();
+    let age = if seconds < 60 {
+        format!("há {}s", seconds)
+    } else {
+        format!("há {}min", seconds / 60)
+    };
+    ui.horizontal(|ui| {
+        ui.colored_label(message.severity.color(), message.severity.icon());
+        ui.colored_label(message.severity.color(), &message.text);
+        ui.weak(age);
+    });
+}
@@ -0,0 +1,407 @@
+use todo_todo::*;
+use chrono::NaiveDate;
+use eframe::egui;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// Intervalo entre verificações do arquivo TODO no disco
+const DISK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// Identifica uma versão do arquivo no disco (data de modificação + tamanho)
+type DiskStamp = Option<(SystemTime, u64)>;
+
+fn disk_stamp(path: &Path) -> DiskStamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+// Operações que a interface pede para a thread de persistência
+#[derive(Clone)]
+pub enum Command {
+    Add(String),
+    AddSub(String, String),
+    Edit(String, String),
+    Done(Vec<String>, bool),
+    Remove(Vec<String>),
+    SetStatus(String, Option<String>),
+    SetDue(String, Option<NaiveDate>),
+    SetNote(String, String),
+    // Abre o arquivo e a linha de onde a tarefa foi importada pelo `scan`
+    OpenSource(String),
+    Sort,
+    Reset,
+    Restore,
+    Reload,
+    // Troca a lista aberta (None = principal)
+    SwitchList(Option<String>),
+    CreateList(String),
+    // Move as tarefas para a lista de destino (None = principal)
+    Move(Vec<String>, Option<String>),
+    // Move as tarefas concluídas para o arquivo morto
+    Archive,
+    // Só relê o arquivo morto
+    LoadArchive,
+    // Confere a senha de uma lista criptografada (None = principal) e abre a lista
+    Unlock(Option<String>, String),
+}
+
+pub enum Event {
+    // Resultado de um comando enviado pela interface, com a lista relida do disco
+    Finished {
+        command: Command,
+        result: Result<(), String>,
+        todo: Option<Todo>,
+    },
+    // O arquivo foi alterado por outro processo (ex.: `todo-todo mk` no terminal)
+    ExternalChange(Todo),
+    // Listas nomeadas existentes, enviadas no início e quando uma lista é criada
+    Lists(Vec<String>),
+    // Linhas do arquivo morto da lista aberta
+    Archive(Vec<String>),
+}
+
+// O que chega na thread de persistência: pedidos da interface ou avisos do daemon
+enum Message {
+    Command(Command),
+    Changed,
+}
+
+pub struct Worker {
+    commands: Sender<Message>,
+    events: Receiver<Event>,
+    pending: usize,
+}
+
+impl Worker {
+    // A thread recebe a lista já localizada e só resolve caminhos de novo ao trocar de lista
+    pub fn spawn(todo: &Todo, ctx: egui::Context) -> Self {
+        let (commands, command_rx) = mpsc::channel();
+        let (event_tx, events) = mpsc::channel();
+        let location = todo.clone();
+
+        // Com o daemon rodando, os avisos dele disparam a releitura sem esperar a próxima verificação
+        let changes = commands.clone();
+        thread::spawn(move || daemon::Client::subscribe(|_| changes.send(Message::Changed).is_ok()));
+        thread::spawn(move || run(location, command_rx, event_tx, ctx));
+
+        Self {
+            commands,
+            events,
+            pending: 0,
+        }
+    }
+
+    pub fn send(&mut self, command: Command) {
+        if self.commands.send(Message::Command(command)).is_ok() {
+            self.pending += 1;
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.pending > 0
+    }
+
+    // Eventos prontos, sem bloquear o frame
+    pub fn poll(&mut self) -> Vec<Event> {
+        let events: Vec<Event> = self.events.try_iter().collect();
+        for event in &events {
+            if let Event::Finished { .. } = event {
+                self.pending = self.pending.saturating_sub(1);
+            }
+        }
+        events
+    }
+}
+
+// Comando equivalente da CLI para as alterações que `Todo::apply` sabe fazer
+fn list_command(command: &Command) -> Option<(&'static str, Vec<String>)> {
+    let command = match command {
+        Command::Add(task) => ("mk", vec![task.clone()]),
+        Command::AddSub(parent, task) => ("sub", vec![parent.clone(), task.clone()]),
+        Command::Edit(index, task) => ("edit", vec![index.clone(), task.clone()]),
+        Command::Done(indices, recursive) => {
+            let mut args = indices.clone();
+            if *recursive {
+                args.push("-r".to_string());
+            }
+            ("done", args)
+        }
+        Command::Remove(indices) => ("rm", indices.clone()),
+        Command::SetStatus(index, status) => {
+            let mut args = vec![index.clone()];
+            args.extend(status.clone());
+            ("status", args)
+        }
+        Command::SetDue(index, due) => {
+            let mut args = vec![index.clone()];
+            args.extend(due.map(|due| due.format(DATE_FORMAT).to_string()));
+            ("due", args)
+        }
+        Command::SetNote(index, note) => ("note", vec![index.clone(), note.clone()]),
+        Command::Sort => ("sort", Vec::new()),
+        Command::Reset => ("reset", Vec::new()),
+        Command::Restore => ("restore", Vec::new()),
+        _ => return None,
+    };
+    Some(command)
+}
+
+// Mensagem do commit do sync, descrita antes de o comando alterar a lista
+fn describe(client: &mut Option<daemon::Client>, location: &Todo, command: &Command) -> Option<String> {
+    let (name, args) = match command {
+        Command::Move(indices, _) => ("mv", indices.clone()),
+        Command::Archive => ("archive", Vec::new()),
+        _ => list_command(command)?,
+    };
+    let todo = daemon::load(client, location.clone()).ok()?;
+    Some(sync::describe(&todo, name, &args))
+}
+
+fn apply(client: &mut Option<daemon::Client>, location: &mut Todo, command: &Command) -> Result<(), String> {
+    match command {
+        Command::SwitchList(name) => {
+            // Abrir a lista valida que ela existe antes de trocar
+            *location = daemon::open_list(client, name.as_deref())?;
+            return Ok(());
+        }
+        Command::CreateList(name) => return lists::create(name),
+        Command::Unlock(name, passphrase) => {
+            let todo = Todo::locate(name.as_deref())?;
+            crypto::unlock(&todo.todo_path, passphrase)?;
+            *location = todo;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let todo = daemon::load(client, location.clone())?;
+    // Alterações na lista aberta passam pelo daemon, se ele estiver rodando
+    if let Some((name, args)) = list_command(command) {
+        return daemon::apply(client, &todo, name, &args);
+    }
+
+    match command {
+        Command::OpenSource(index) => {
+            let source = todo
+                .source(index)?
+                .ok_or(format!("A tarefa {} não veio de um comentário de código", index))?;
+            editor::open_location(&todo.source_path(&source), source.line)
+        }
+        Command::Move(indices, target) => todo.move_to(indices, &Todo::open_list(target.as_deref())?),
+        Command::Archive => todo.archive(None).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+// Executa um pedido da interface; None quando a interface já foi fechada
+fn execute(
+    client: &mut Option<daemon::Client>,
+    location: &mut Todo,
+    command: Command,
+    events: &Sender<Event>,
+) -> Option<Event> {
+    let message = describe(client, location, &command);
+    let before = daemon::load(client, location.clone()).map(|todo| todo.todo).ok();
+    let result = apply(client, location, &command);
+    // Arquivamento automático (TODO_AUTO_ARCHIVE) depois de cada alteração
+    let auto_archived = match location.reload() {
+        Ok(todo) if result.is_ok() => todo.auto_archive().unwrap_or(0),
+        _ => 0,
+    };
+    // Mover, arquivar e o arquivamento automático gravam direto no arquivo
+    if matches!(command, Command::Move(..) | Command::Archive) || auto_archived > 0 {
+        daemon::touched(client, location);
+    }
+    // Com o sync ligado, cada alteração vira um commit
+    let result = match (result, message) {
+        (Ok(()), Some(message)) => location.reload().and_then(|todo| {
+            if Some(&todo.todo) == before.as_ref() {
+                return Ok(());
+            }
+            sync::record(&todo, &message)
+        }),
+        (result, _) => result,
+    };
+    if matches!(command, Command::CreateList(_))
+        && let Ok(names) = lists::names()
+    {
+        let _ = events.send(Event::Lists(names));
+    }
+    let todo = daemon::load(client, location.clone()).ok();
+    if let Some(todo) = &todo {
+        let archive_changed = matches!(
+            command,
+            Command::Archive | Command::LoadArchive | Command::SwitchList(_) | Command::Unlock(..)
+        );
+        if (archive_changed || auto_archived > 0) && events.send(Event::Archive(todo.archived_lines())).is_err() {
+            return None;
+        }
+    }
+    Some(Event::Finished {
+        command,
+        result,
+        todo,
+    })
+}
+
+// Um panic vira o erro do comando: sem o Finished, a interface ficaria ocupada para sempre
+fn catch_panic(command: &Command, execute: impl FnOnce() -> Option<Event>) -> Option<Event> {
+    panic::catch_unwind(AssertUnwindSafe(execute)).unwrap_or_else(|panic| {
+        let reason = match (panic.downcast_ref::<&str>(), panic.downcast_ref::<String>()) {
+            (Some(reason), _) => reason.to_string(),
+            (_, Some(reason)) => reason.clone(),
+            _ => "causa desconhecida".to_string(),
+        };
+        Some(Event::Finished {
+            command: command.clone(),
+            result: Err(format!("Erro interno ao executar o comando: {}", reason)),
+            todo: None,
+        })
+    })
+}
+
+fn run(mut location: Todo, commands: Receiver<Message>, events: Sender<Event>, ctx: egui::Context) {
+    let mut client = daemon::Client::connect();
+    let mut stamp = disk_stamp(&location.todo_path);
+    if let Ok(names) = lists::names()
+        && events.send(Event::Lists(names)).is_err()
+    {
+        return;
+    }
+    if let Ok(todo) = daemon::load(&mut client, location.clone())
+        && events.send(Event::Archive(todo.archived_lines())).is_err()
+    {
+        return;
+    }
+    loop {
+        let event = match commands.recv_timeout(DISK_POLL_INTERVAL) {
+            Ok(Message::Command(command)) => {
+                let event = catch_panic(&command, || execute(&mut client, &mut location, command.clone(), &events));
+                let Some(event) = event else {
+                    return;
+                };
+                // Todo::new recria o arquivo se ele foi apagado, então o carimbo é lido depois
+                stamp = disk_stamp(&location.todo_path);
+                event
+            }
+            Ok(Message::Changed) | Err(RecvTimeoutError::Timeout) => {
+                let current = disk_stamp(&location.todo_path);
+                if current == stamp {
+                    continue;
+                }
+                let Ok(todo) = daemon::load(&mut client, location.clone()) else {
+                    continue;
+                };
+                stamp = disk_stamp(&location.todo_path);
+                // `todo-todo archive` no terminal também muda o arquivo morto
+                if events.send(Event::Archive(todo.archived_lines())).is_err() {
+                    return;
+                }
+                Event::ExternalChange(todo)
+            }
+            Err(RecvTimeoutError::Disconnected) => return,
+        };
+
+        if events.send(event).is_err() {
+            return;
+        }
+        ctx.request_repaint();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_command_maps_list_changes_to_cli_commands() {
+        let due = NaiveDate::from_ymd_opt(2025, 3, 1);
+        let cases = [
+            (Command::Add("comprar leite".into()), "mk", vec!["comprar leite"]),
+            (Command::AddSub("1".into(), "pão".into()), "sub", vec!["1", "pão"]),
+            (Command::Done(vec!["1".into(), "2".into()], false), "done", vec!["1", "2"]),
+            (Command::Done(vec!["1".into()], true), "done", vec!["1", "-r"]),
+            (Command::SetStatus("1".into(), None), "status", vec!["1"]),
+            (Command::SetStatus("1".into(), Some("doing".into())), "status", vec!["1", "doing"]),
+            (Command::SetDue("2".into(), due), "due", vec!["2", "2025-03-01"]),
+            (Command::SetDue("2".into(), None), "due", vec!["2"]),
+            (Command::Sort, "sort", vec![]),
+        ];
+        for (command, name, args) in cases {
+            let (mapped, mapped_args) = list_command(&command).unwrap();
+            assert_eq!(mapped, name);
+            assert_eq!(mapped_args, args);
+        }
+    }
+
+    #[test]
+    fn list_command_ignores_commands_outside_the_list() {
+        for command in [
+            Command::Reload,
+            Command::Archive,
+            Command::LoadArchive,
+            Command::SwitchList(None),
+            Command::Move(vec!["1".into()], None),
+            Command::OpenSource("1".into()),
+        ] {
+            assert!(list_command(&command).is_none());
+        }
+    }
+
+    #[test]
+    fn describe_uses_the_task_text_before_the_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO");
+        fs::write(&path, "[ ] comprar leite\n[*] pagar conta\n").unwrap();
+        let location = Todo {
+            todo: Vec::new(),
+            todo_bak: dir.path().join("TODO.bak"),
+            todo_path: path,
+            no_backup: true,
+            project: false,
+        };
+
+        let mut client = None;
+        let mut describe = |command| describe(&mut client, &location, &command);
+        assert_eq!(describe(Command::Done(vec!["1".into()], false)).as_deref(), Some("done: comprar leite"));
+        assert_eq!(describe(Command::Remove(vec!["2".into()])).as_deref(), Some("rm: pagar conta"));
+        assert_eq!(describe(Command::Move(vec!["1".into()], None)).as_deref(), Some("mv: comprar leite"));
+        assert_eq!(describe(Command::Archive).as_deref(), Some("archive"));
+        assert_eq!(describe(Command::Reload), None);
+    }
+
+    #[test]
+    fn a_panic_becomes_the_error_of_the_command() {
+        let event = catch_panic(&Command::Sort, || panic!("índice fora da lista"));
+        let Some(Event::Finished { command, result, todo }) = event else {
+            panic!("esperava Event::Finished");
+        };
+        assert!(matches!(command, Command::Sort));
+        assert_eq!(result, Err("Erro interno ao executar o comando: índice fora da lista".to_string()));
+        assert!(todo.is_none());
+
+        let event = catch_panic(&Command::Reload, || panic!("linha {}", 3));
+        assert!(matches!(event, Some(Event::Finished { result: Err(e), .. }) if e.ends_with("linha 3")));
+        assert!(catch_panic(&Command::Reload, || None).is_none());
+    }
+
+    #[test]
+    fn the_worker_is_not_left_busy_after_a_panic() {
+        let (commands, _command_rx) = mpsc::channel();
+        let (event_tx, events) = mpsc::channel();
+        let mut worker = Worker {
+            commands,
+            events,
+            pending: 0,
+        };
+        worker.send(Command::Sort);
+        assert!(worker.is_busy());
+        event_tx.send(catch_panic(&Command::Sort, || panic!("falhou")).unwrap()).unwrap();
+        assert_eq!(worker.poll().len(), 1);
+        assert!(!worker.is_busy());
+    }
+}
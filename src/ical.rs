@@ -0,0 +1,521 @@
+use crate::{Entry, Todo, today};
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
+
+// Exportação e importação de tarefas como componentes VTODO do iCalendar (RFC 5545)
+
+const PRODID: &str = "-//todo-todo//todo-todo//PT";
+
+// Linhas mais longas que isso são dobradas (continuação começa com um espaço)
+const MAX_LINE: usize = 75;
+
+// Resultado de `Todo::import_ics`
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub skipped: usize,
+}
+
+// FNV-1a: o UID precisa ser o mesmo entre execuções e versões do Rust
+fn stable_hash(text: &str) -> u64 {
+    text.bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+// Tarefas com id têm UID fixo; as outras dependem do texto e da data de criação, sem precisar
+// gravar nada na lista (a exportação e o CalDAV só leem). Editar o texto muda o UID delas.
+fn uid(entry: &Entry) -> String {
+    match entry.id {
+        Some(id) => format!("todo-todo-{}@todo-todo", id),
+        None => {
+            let created = entry.created.map(|date| date.to_string()).unwrap_or_default();
+            format!("{:016x}@todo-todo", stable_hash(&format!("{}\n{}", entry.todo_entry, created)))
+        }
+    }
+}
+
+// UID de cada tarefa, na ordem da lista: tarefas iguais (mesmo texto e criação) são diferenciadas
+// pela ordem em que aparecem
+fn uids(entries: &[Entry]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    entries
+        .iter()
+        .map(|entry| {
+            let uid = uid(entry);
+            let count = seen.entry(uid.clone()).or_insert(0);
+            *count += 1;
+            if *count == 1 { uid } else { uid.replacen('@', &format!("-{}@", count), 1) }
+        })
+        .collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// Separa por vírgulas não escapadas (ex.: CATEGORIES:casa,mercado)
+fn split_list(text: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in text.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            items.push(unescape(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    items.push(unescape(&current));
+    items.into_iter().filter(|item| !item.trim().is_empty()).collect()
+}
+
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        if let Some(rest) = line.strip_prefix([' ', '\t'])
+            && let Some(last) = lines.last_mut()
+        {
+            last.push_str(rest);
+        } else if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+fn date_value(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+// CREATED e COMPLETED precisam de data e hora em UTC; o arquivo só guarda o dia
+fn datetime_value(date: NaiveDate) -> String {
+    format!("{}T000000Z", date_value(date))
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..8)?, "%Y%m%d").ok()
+}
+
+// A (mais alta) vira 1 e I em diante vira 9, a mais baixa do iCalendar
+fn ical_priority(priority: char) -> u8 {
+    (priority as u8).saturating_sub(b'A').min(8) + 1
+}
+
+fn entry_priority(value: u8) -> Option<char> {
+    match value {
+        1..=9 => Some((b'A' + value - 1) as char),
+        _ => None,
+    }
+}
+
+// As #tags saem do resumo e viram CATEGORIES
+fn summary_and_categories(entry: &Entry) -> (String, Vec<String>) {
+    let summary: Vec<&str> = entry
+        .todo_entry
+        .split_whitespace()
+        .filter(|word| !(word.len() > 1 && word.starts_with('#')))
+        .collect();
+    let categories = entry.tags().iter().map(|tag| tag[1..].to_string()).collect();
+    (summary.join(" "), categories)
+}
+
+pub fn vtodo(entry: &Entry, parent: Option<&str>, stamp: &str) -> String {
+    let (summary, categories) = summary_and_categories(entry);
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", uid(entry)),
+        format!("DTSTAMP:{}", stamp),
+        format!("SUMMARY:{}", escape(&summary)),
+        format!("STATUS:{}", if entry.done { "COMPLETED" } else { "NEEDS-ACTION" }),
+    ];
+    if !categories.is_empty() {
+        let categories: Vec<String> = categories.iter().map(|category| escape(category)).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    if let Some(due) = entry.due {
+        lines.push(format!("DUE;VALUE=DATE:{}", date_value(due)));
+    }
+    if let Some(priority) = entry.priority {
+        lines.push(format!("PRIORITY:{}", ical_priority(priority)));
+    }
+    if let Some(created) = entry.created {
+        lines.push(format!("CREATED:{}", datetime_value(created)));
+    }
+    if entry.done {
+        lines.push("PERCENT-COMPLETE:100".to_string());
+        if let Some(completed) = entry.completed {
+            lines.push(format!("COMPLETED:{}", datetime_value(completed)));
+        }
+    }
+    if let Some(note) = &entry.note {
+        lines.push(format!("DESCRIPTION:{}", escape(note)));
+    }
+    if let Some(parent) = parent {
+        lines.push(format!("RELATED-TO;RELTYPE=PARENT:{}", parent));
+    }
+    lines.push("END:VTODO".to_string());
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+pub fn calendar(components: &[String]) -> String {
+    let mut out = String::new();
+    out.push_str(&fold("BEGIN:VCALENDAR"));
+    out.push_str(&fold("VERSION:2.0"));
+    out.push_str(&fold(&format!("PRODID:{}", PRODID)));
+    for component in components {
+        out.push_str(component);
+    }
+    out.push_str(&fold("END:VCALENDAR"));
+    out
+}
+
+// Tarefa lida de um VTODO
+struct Imported {
+    uid: Option<String>,
+    parent: Option<String>,
+    entry: Entry,
+}
+
+// Nome da propriedade, parâmetros (ex.: ";VALUE=DATE") e valor; o ':' dentro de aspas não conta
+fn split_property(line: &str) -> Option<(String, String, &str)> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let head = line[..i].to_ascii_uppercase();
+                let (name, params) = head.split_once(';').unwrap_or((&head, ""));
+                return Some((name.to_string(), params.to_string(), &line[i + 1..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_vtodos(text: &str) -> Result<Vec<Imported>, String> {
+    let lines = unfold(text);
+    if !lines.first().is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("O arquivo não é um calendário iCalendar (BEGIN:VCALENDAR)".to_string());
+    }
+
+    let mut imported = Vec::new();
+    let mut current: Option<(Imported, Vec<String>, String)> = None;
+    // Componentes dentro do VTODO (ex.: VALARM) são ignorados
+    let mut nested = 0;
+    for line in &lines {
+        let Some((name, params, value)) = split_property(line) else {
+            continue;
+        };
+        let Some((item, categories, summary)) = &mut current else {
+            if name == "BEGIN" && value.eq_ignore_ascii_case("VTODO") {
+                current = Some((
+                    Imported {
+                        uid: None,
+                        parent: None,
+                        entry: Entry::new(String::new(), false),
+                    },
+                    Vec::new(),
+                    String::new(),
+                ));
+            }
+            continue;
+        };
+        match name.as_str() {
+            "BEGIN" => nested += 1,
+            "END" if nested > 0 => nested -= 1,
+            "END" => {
+                if let Some((mut item, categories, summary)) = current.take() {
+                    // As categorias voltam para o texto como #tags
+                    let mut text = summary.split_whitespace().collect::<Vec<_>>().join(" ");
+                    for category in categories {
+                        let tag = format!("#{}", category.split_whitespace().collect::<Vec<_>>().join("-"));
+                        if !text.split_whitespace().any(|word| word == tag) {
+                            text.push(' ');
+                            text.push_str(&tag);
+                        }
+                    }
+                    item.entry.todo_entry = text.trim().to_string();
+                    if !item.entry.todo_entry.is_empty() {
+                        imported.push(item);
+                    }
+                }
+            }
+            _ if nested > 0 => {}
+            "UID" => item.uid = Some(value.to_string()),
+            "SUMMARY" => *summary = unescape(value),
+            "CATEGORIES" => categories.extend(split_list(value)),
+            // Cancelada conta como concluída: não há outro estado final na lista
+            "STATUS" => {
+                item.entry.done = value.eq_ignore_ascii_case("COMPLETED") || value.eq_ignore_ascii_case("CANCELLED");
+            }
+            "DUE" => item.entry.due = parse_date(value),
+            "PRIORITY" => item.entry.priority = value.trim().parse().ok().and_then(entry_priority),
+            "CREATED" => item.entry.created = parse_date(value),
+            "COMPLETED" => item.entry.completed = parse_date(value),
+            "DESCRIPTION" => item.entry.note = Some(unescape(value)).filter(|note| !note.trim().is_empty()),
+            "RELATED-TO" if !params.contains("RELTYPE=") || params.contains("RELTYPE=PARENT") => {
+                item.parent = Some(value.to_string());
+            }
+            _ => {}
+        }
+    }
+    Ok(imported)
+}
+
+impl Todo {
+    // Calendário com todas as tarefas; subtarefas apontam para a tarefa pai com RELATED-TO
+    pub fn to_ics(&self) -> String {
+        let components: Vec<String> = self.vtodos().into_iter().map(|(_, component)| component).collect();
+        calendar(&components)
+    }
+
+    // UID e componente VTODO de cada tarefa, na ordem da lista
+    pub fn vtodos(&self) -> Vec<(String, String)> {
+        let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut parents: Vec<String> = Vec::new();
+        let mut components = Vec::new();
+        for (entry, uid) in entries.iter().zip(uids(&entries)) {
+            parents.truncate(entry.depth);
+            components.push((uid.clone(), vtodo(entry, parents.last().map(String::as_str), &stamp)));
+            parents.push(uid);
+        }
+        components
+    }
+
+    // Tarefas com o mesmo UID são atualizadas; as com o mesmo texto de uma existente são ignoradas
+    pub fn import_ics(&self, text: &str) -> Result<ImportSummary, String> {
+        let imported = parse_vtodos(text)?;
+        let mut entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut changed = vec![false; entries.len()];
+        let by_uid: HashMap<String, usize> =
+            uids(&entries).into_iter().enumerate().map(|(pos, uid)| (uid, pos)).collect();
+        let mut summary = ImportSummary {
+            added: 0,
+            updated: 0,
+            skipped: 0,
+        };
+
+        let mut added: Vec<Imported> = Vec::new();
+        for mut item in imported {
+            if let Some(&pos) = item.uid.as_ref().and_then(|uid| by_uid.get(uid)) {
+                let entry = &mut entries[pos];
+                let before = entry.file_line();
+                entry.todo_entry = item.entry.todo_entry;
+                entry.due = item.entry.due;
+                entry.priority = item.entry.priority;
+                entry.note = item.entry.note;
+                entry.set_done(item.entry.done);
+                if item.entry.done && item.entry.completed.is_some() {
+                    entry.completed = item.entry.completed;
+                }
+                if entry.file_line() != before {
+                    changed[pos] = true;
+                    summary.updated += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+                continue;
+            }
+            let duplicate = entries
+                .iter()
+                .chain(added.iter().map(|item| &item.entry))
+                .any(|entry| entry.todo_entry == item.entry.todo_entry);
+            if duplicate {
+                summary.skipped += 1;
+                continue;
+            }
+            let entry = &mut item.entry;
+            entry.created = entry.created.or(Some(today()));
+            if entry.done {
+                entry.completed = entry.completed.or(Some(today()));
+            } else {
+                entry.completed = None;
+            }
+            summary.added += 1;
+            added.push(item);
+        }
+
+        let mut data = String::new();
+        for ((line, entry), changed) in self.todo.iter().zip(&entries).zip(changed) {
+            if changed {
+                data.push_str(&entry.file_line());
+            } else {
+                data.push_str(&format!("{}\n", line));
+            }
+        }
+        // As novas vão para o fim, cada subtarefa logo abaixo da tarefa pai importada junto
+        let uids: Vec<Option<String>> = added.iter().map(|item| item.uid.clone()).collect();
+        let parent_of = |item: &Imported| {
+            item.parent
+                .as_ref()
+                .and_then(|parent| uids.iter().position(|uid| uid.as_ref() == Some(parent)))
+        };
+        let parents: Vec<Option<usize>> = added.iter().map(parent_of).collect();
+        let mut stack: Vec<(usize, usize)> = (0..added.len())
+            .rev()
+            .filter(|&i| parents[i].is_none_or(|parent| parent == i))
+            .map(|i| (i, 0))
+            .collect();
+        let mut written = vec![false; added.len()];
+        while let Some((i, depth)) = stack.pop() {
+            if written[i] {
+                continue;
+            }
+            written[i] = true;
+            let mut entry = Entry::new(String::new(), false);
+            std::mem::swap(&mut entry, &mut added[i].entry);
+            entry.depth = depth;
+            data.push_str(&entry.file_line());
+            for child in (0..added.len()).rev() {
+                if parents[child] == Some(i) && child != i {
+                    stack.push((child, depth + 1));
+                }
+            }
+        }
+        // Tarefas presas num ciclo de RELATED-TO entram sem tarefa pai
+        for (i, item) in added.iter_mut().enumerate() {
+            if !written[i] {
+                item.entry.depth = 0;
+                data.push_str(&item.entry.file_line());
+            }
+        }
+
+        self.write_file(&data)?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{reload, temp_list, texts};
+
+    const LIST: &[&str] = &[
+        "[ ] comprar pão #casa due:2026-05-01 pri:A added:2026-04-01",
+        "  [*] ver preço added:2026-04-01 done:2026-04-02",
+        "[ ] ligar, depois; anotar note:falar%20com%0Aela,%20tudo added:2026-04-03",
+    ];
+
+    fn uids_of(todo: &Todo) -> Vec<String> {
+        todo.vtodos().into_iter().map(|(uid, _)| uid).collect()
+    }
+
+    #[test]
+    fn export_derives_stable_uids_without_writing() {
+        let (_dir, todo) = temp_list(&["[ ] a id:4", "[ ] b added:2026-04-01", "[ ] b added:2026-04-01", "[ ] c"]);
+        let original = std::fs::read(&todo.todo_path).unwrap();
+        let uids = uids_of(&todo);
+        assert_eq!(uids[0], "todo-todo-4@todo-todo");
+        // Tarefas iguais ganham UIDs diferentes
+        assert_ne!(uids[1], uids[2]);
+        assert!(uids[2].ends_with("-2@todo-todo"));
+        todo.to_ics();
+        assert_eq!(std::fs::read(&todo.todo_path).unwrap(), original);
+
+        // O UID não depende da posição, só do texto e da criação
+        todo.apply("rm", &["1".to_string()]).unwrap();
+        let todo = reload(&todo);
+        assert_eq!(uids_of(&todo), uids[1..]);
+        todo.apply("edit", &["3".to_string(), "c editada".to_string()]).unwrap();
+        assert_ne!(uids_of(&reload(&todo))[2], uids[3]);
+        assert_eq!(texts(&reload(&todo)), ["b", "b", "c editada"]);
+    }
+
+    #[test]
+    fn export_and_import_round_trip() {
+        let (_dir, todo) = temp_list(LIST);
+        let text = todo.to_ics();
+        let parent = &uids_of(&todo)[0];
+        assert!(text.contains("CATEGORIES:casa\r\n"));
+        assert!(text.contains("SUMMARY:ligar\\, depois\\; anotar\r\n"));
+        assert!(text.contains(&format!("RELATED-TO;RELTYPE=PARENT:{}\r\n", parent)));
+
+        let (_other, empty) = temp_list(&[]);
+        let summary = empty.import_ics(&text).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.skipped), (3, 0, 0));
+        let imported = reload(&empty);
+        let exported = reload(&todo);
+        assert_eq!(texts(&imported), texts(&exported));
+        for (imported, exported) in imported.todo.iter().zip(&exported.todo) {
+            let (imported, exported) = (Entry::read_line(imported), Entry::read_line(exported));
+            assert_eq!(imported.depth, exported.depth);
+            assert_eq!(imported.done, exported.done);
+            assert_eq!(imported.due, exported.due);
+            assert_eq!(imported.priority, exported.priority);
+            assert_eq!(imported.created, exported.created);
+            assert_eq!(imported.completed, exported.completed);
+            assert_eq!(imported.note, exported.note);
+        }
+
+        // Importar de volta na lista de origem não muda nada
+        let summary = exported.import_ics(&text).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.skipped), (0, 0, 3));
+        assert_eq!(reload(&exported).todo, exported.todo);
+    }
+
+    #[test]
+    fn import_updates_by_uid_and_maps_cancelled_to_done() {
+        let (_dir, todo) = temp_list(&["[ ] a id:1", "[ ] b id:2"]);
+        let text = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:todo-todo-1@todo-todo\r\nSUMMARY:a\r\n\
+                    STATUS:CANCELLED\r\nEND:VTODO\r\nBEGIN:VTODO\r\nUID:outro\r\nSUMMARY:c com \r\n  dobra\r\n\
+                    STATUS:CANCELLED\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let summary = todo.import_ics(text).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.skipped), (1, 1, 0));
+        let entries: Vec<Entry> = reload(&todo).todo.iter().map(|line| Entry::read_line(line)).collect();
+        let done: Vec<(&str, bool)> = entries.iter().map(|entry| (entry.todo_entry.as_str(), entry.done)).collect();
+        assert_eq!(done, [("a", true), ("b", false), ("c com dobra", true)]);
+        assert!(entries[2].completed.is_some());
+    }
+
+    #[test]
+    fn import_rejects_non_calendars() {
+        let (_dir, todo) = temp_list(&["[ ] a"]);
+        assert!(todo.import_ics("[ ] a\n").is_err());
+        assert_eq!(reload(&todo).todo, todo.todo);
+    }
+}
+
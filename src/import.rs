@@ -0,0 +1,723 @@
+use crate::{Entry, Recurrence, Todo, today};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
+use serde_json::Value;
+use std::collections::HashMap;
+
+// Importação dos arquivos exportados por outros gerenciadores de tarefas (sem acesso à rede).
+// Os projetos e etiquetas de lá viram #tags no texto da tarefa.
+
+#[derive(Clone, Copy)]
+pub enum Format {
+    Taskwarrior,
+    Todoist,
+    GitHub,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name.to_lowercase().as_str() {
+            "taskwarrior" | "task" => Ok(Self::Taskwarrior),
+            "todoist" => Ok(Self::Todoist),
+            "github" | "gh" => Ok(Self::GitHub),
+            _ => Err(format!("Formato desconhecido '{}', use taskwarrior, todoist ou github", name)),
+        }
+    }
+}
+
+// Tarefa pronta para entrar na lista, na ordem em que será gravada
+pub struct Imported {
+    pub entry: Entry,
+    // Posição da tarefa pai nesta mesma importação
+    pub parent: Option<usize>,
+    // Já está na lista (ou apareceu antes no mesmo arquivo) e não será gravada
+    pub duplicate: bool,
+}
+
+pub struct Preview {
+    pub tasks: Vec<Imported>,
+    // Itens do arquivo que não viram tarefas (excluídas, modelos de recorrência, seções...)
+    pub ignored: usize,
+}
+
+impl Preview {
+    pub fn new_tasks(&self) -> usize {
+        self.tasks.iter().filter(|task| !task.duplicate).count()
+    }
+}
+
+// Tarefa lida do arquivo, antes de montar a árvore
+struct Task {
+    entry: Entry,
+    tags: Vec<String>,
+    // Identificador no formato de origem e o da tarefa pai, para as subtarefas
+    key: Option<String>,
+    parent_key: Option<String>,
+}
+
+impl Task {
+    fn new(text: &str, done: bool) -> Self {
+        Self {
+            entry: Entry::new(text.split_whitespace().collect::<Vec<_>>().join(" "), done),
+            tags: Vec::new(),
+            key: None,
+            parent_key: None,
+        }
+    }
+}
+
+fn field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a Value> {
+    keys.iter().find_map(|key| value.get(key).filter(|value| !value.is_null()))
+}
+
+fn str_field<'a>(value: &'a Value, keys: &[&str]) -> Option<&'a str> {
+    field(value, keys).and_then(Value::as_str)
+}
+
+// Ids aparecem como número ou texto, conforme a versão da exportação
+fn key_field(value: &Value, keys: &[&str]) -> Option<String> {
+    match field(value, keys)? {
+        Value::String(key) => Some(key.clone()),
+        Value::Number(key) => Some(key.to_string()),
+        _ => None,
+    }
+}
+
+// Datas com horário são convertidas para o dia no fuso local
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(text) {
+        return Some(datetime.with_timezone(&Local).date_naive());
+    }
+    // Formato do Taskwarrior: 20250301T120000Z
+    if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ") {
+        return Some(datetime.and_utc().with_timezone(&Local).date_naive());
+    }
+    NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+}
+
+fn date_field(value: &Value, keys: &[&str]) -> Option<NaiveDate> {
+    str_field(value, keys).and_then(parse_date)
+}
+
+fn tag(name: &str) -> Option<String> {
+    let name = name.trim().trim_start_matches(['#', '@']);
+    let words: Vec<&str> = name.split_whitespace().collect();
+    if words.is_empty() { None } else { Some(format!("#{}", words.join("-"))) }
+}
+
+// Acrescenta ao texto as #tags que ele ainda não tem
+fn with_tags(text: &str, tags: &[String]) -> String {
+    let mut text = text.to_string();
+    for tag in tags {
+        let present = text.split_whitespace().any(|word| word.eq_ignore_ascii_case(tag));
+        if !present {
+            text.push(' ');
+            text.push_str(tag);
+        }
+    }
+    text.trim().to_string()
+}
+
+fn parse_json(text: &str, format: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("JSON do {} inválido: {}", format, e))
+}
+
+fn taskwarrior(text: &str) -> Result<(Vec<Task>, usize), String> {
+    let values = match serde_json::from_str::<Value>(text) {
+        Ok(Value::Array(values)) => values,
+        // Versões antigas do `task export` escrevem um objeto por linha
+        _ => text
+            .lines()
+            .map(|line| line.trim().trim_end_matches(','))
+            .filter(|line| !line.is_empty() && *line != "[" && *line != "]")
+            .map(|line| parse_json(line, "Taskwarrior"))
+            .collect::<Result<Vec<_>, _>>()?,
+    };
+
+    let mut tasks = Vec::new();
+    let mut ignored = 0;
+    for value in values {
+        let status = str_field(&value, &["status"]).unwrap_or("pending");
+        let description = str_field(&value, &["description"]).unwrap_or_default();
+        // Modelos de recorrência: as ocorrências pendentes vêm como tarefas próprias
+        if matches!(status, "deleted" | "recurring") || description.trim().is_empty() {
+            ignored += 1;
+            continue;
+        }
+        let done = status == "completed";
+        let mut task = Task::new(description, done);
+        let entry = &mut task.entry;
+        entry.created = date_field(&value, &["entry"]);
+        entry.completed = if done { date_field(&value, &["end"]) } else { None };
+        entry.due = date_field(&value, &["due"]);
+        entry.priority = match str_field(&value, &["priority"]) {
+            Some("H") => Some('A'),
+            Some("M") => Some('B'),
+            Some("L") => Some('C'),
+            _ => None,
+        };
+        entry.status = if status == "waiting" {
+            Some("aguardando".to_string())
+        } else if !done && field(&value, &["start"]).is_some() {
+            Some("fazendo".to_string())
+        } else {
+            None
+        };
+        entry.recur = str_field(&value, &["recur"]).and_then(|rule| Recurrence::parse(rule).ok());
+        let annotations: Vec<&str> = field(&value, &["annotations"])
+            .and_then(Value::as_array)
+            .map(|annotations| annotations.iter().filter_map(|a| str_field(a, &["description"])).collect())
+            .unwrap_or_default();
+        if !annotations.is_empty() {
+            entry.note = Some(annotations.join("\n"));
+        }
+        task.tags.extend(str_field(&value, &["project"]).and_then(tag));
+        if let Some(tags) = field(&value, &["tags"]).and_then(Value::as_array) {
+            task.tags.extend(tags.iter().filter_map(Value::as_str).filter_map(tag));
+        }
+        tasks.push(task);
+    }
+    Ok((tasks, ignored))
+}
+
+// Backup em JSON: o da API de sincronização ({"items": [...], "projects": [...]}),
+// o da API REST ({"tasks": [...]}) ou uma lista de tarefas
+fn todoist_json(text: &str) -> Result<(Vec<Task>, usize), String> {
+    let value = parse_json(text, "Todoist")?;
+    let items = match &value {
+        Value::Array(items) => items.clone(),
+        _ => field(&value, &["items", "tasks"])
+            .and_then(Value::as_array)
+            .cloned()
+            .ok_or("Backup do Todoist sem a lista de tarefas (items)")?,
+    };
+    let projects: HashMap<String, String> = field(&value, &["projects"])
+        .and_then(Value::as_array)
+        .map(|projects| {
+            projects
+                .iter()
+                .filter_map(|project| Some((key_field(project, &["id"])?, str_field(project, &["name"])?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default();
+    // Comentários das tarefas viram a anotação
+    let mut notes: HashMap<String, Vec<String>> = HashMap::new();
+    for note in field(&value, &["notes"]).and_then(Value::as_array).into_iter().flatten() {
+        if let (Some(item), Some(content)) = (key_field(note, &["item_id", "task_id"]), str_field(note, &["content"])) {
+            notes.entry(item).or_default().push(content.to_string());
+        }
+    }
+
+    let mut tasks = Vec::new();
+    let mut ignored = 0;
+    for item in &items {
+        let content = str_field(item, &["content"]).unwrap_or_default();
+        let deleted = field(item, &["is_deleted"]).and_then(Value::as_bool).unwrap_or(false);
+        if deleted || content.trim().is_empty() {
+            ignored += 1;
+            continue;
+        }
+        let done = field(item, &["checked", "is_completed"]).and_then(Value::as_bool).unwrap_or(false)
+            || field(item, &["completed_at"]).is_some();
+        let mut task = Task::new(content, done);
+        task.key = key_field(item, &["id"]);
+        task.parent_key = key_field(item, &["parent_id"]);
+        let entry = &mut task.entry;
+        entry.created = date_field(item, &["added_at", "created_at", "date_added"]);
+        entry.completed = if done { date_field(item, &["completed_at", "date_completed"]) } else { None };
+        entry.due = field(item, &["due"]).and_then(|due| date_field(due, &["date"]));
+        // Na API, 4 é a prioridade mais alta (p1) e 1 é sem prioridade
+        entry.priority = match field(item, &["priority"]).and_then(Value::as_u64) {
+            Some(4) => Some('A'),
+            Some(3) => Some('B'),
+            Some(2) => Some('C'),
+            _ => None,
+        };
+        let mut note: Vec<String> = str_field(item, &["description"])
+            .filter(|description| !description.trim().is_empty())
+            .map(String::from)
+            .into_iter()
+            .collect();
+        if let Some(comments) = task.key.as_ref().and_then(|key| notes.get(key)) {
+            note.extend(comments.iter().cloned());
+        }
+        if !note.is_empty() {
+            entry.note = Some(note.join("\n\n"));
+        }
+        let project = key_field(item, &["project_id"]).and_then(|id| projects.get(&id));
+        task.tags.extend(project.and_then(|name| tag(name)));
+        if let Some(labels) = field(item, &["labels"]).and_then(Value::as_array) {
+            task.tags.extend(labels.iter().filter_map(Value::as_str).filter_map(tag));
+        }
+        tasks.push(task);
+    }
+    Ok((tasks, ignored))
+}
+
+// Campos de um CSV, com aspas e quebras de linha dentro das aspas
+fn csv_records(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    records
+}
+
+// Modelo CSV do Todoist (um arquivo por projeto): TYPE, CONTENT, DESCRIPTION, PRIORITY, INDENT, DATE...
+// O nome do arquivo é o nome do projeto.
+fn todoist_csv(text: &str, project: &str) -> Result<(Vec<Task>, usize), String> {
+    let mut records = csv_records(text).into_iter();
+    let header: Vec<String> = records
+        .next()
+        .ok_or("O arquivo CSV está vazio")?
+        .iter()
+        .map(|name| name.trim().to_uppercase())
+        .collect();
+    let column = |name: &str| header.iter().position(|column| column == name);
+    let (Some(kind), Some(content)) = (column("TYPE"), column("CONTENT")) else {
+        return Err("CSV do Todoist sem as colunas TYPE e CONTENT".to_string());
+    };
+    let (description, priority, indent, date) =
+        (column("DESCRIPTION"), column("PRIORITY"), column("INDENT"), column("DATE"));
+
+    let mut tasks: Vec<Task> = Vec::new();
+    let mut ignored = 0;
+    // Última tarefa de cada nível de recuo, para achar a tarefa pai
+    let mut levels: Vec<String> = Vec::new();
+    for record in records {
+        let get = |pos: Option<usize>| pos.and_then(|pos| record.get(pos)).map(|field| field.trim()).unwrap_or_default();
+        match get(Some(kind)) {
+            "task" => {}
+            // Comentários entram na anotação da tarefa anterior
+            "note" if !tasks.is_empty() => {
+                let comment = get(Some(content));
+                let last = tasks.len() - 1;
+                let entry = &mut tasks[last].entry;
+                entry.note = Some(match entry.note.take() {
+                    Some(note) => format!("{}\n\n{}", note, comment),
+                    None => comment.to_string(),
+                });
+                continue;
+            }
+            _ => {
+                ignored += 1;
+                continue;
+            }
+        }
+        // Etiquetas ficam no texto como @etiqueta
+        let words: Vec<&str> = get(Some(content)).split_whitespace().collect();
+        let (labels, text): (Vec<&str>, Vec<&str>) = words.iter().partition(|word| word.len() > 1 && word.starts_with('@'));
+        if text.is_empty() {
+            ignored += 1;
+            continue;
+        }
+        let mut task = Task::new(&text.join(" "), false);
+        let depth = get(indent).parse::<usize>().unwrap_or(1).max(1) - 1;
+        levels.truncate(depth);
+        task.key = Some(tasks.len().to_string());
+        task.parent_key = levels.last().cloned();
+        levels.push(tasks.len().to_string());
+        let entry = &mut task.entry;
+        // Aqui 1 é a prioridade mais alta (p1) e 4 é sem prioridade
+        entry.priority = match get(priority) {
+            "1" => Some('A'),
+            "2" => Some('B'),
+            "3" => Some('C'),
+            _ => None,
+        };
+        // Datas em linguagem natural ("every monday") não são reconhecidas
+        entry.due = parse_date(get(date));
+        let description = get(description);
+        if !description.is_empty() {
+            entry.note = Some(description.to_string());
+        }
+        task.tags.extend(tag(project));
+        task.tags.extend(labels.into_iter().filter_map(tag));
+        tasks.push(task);
+    }
+    Ok((tasks, ignored))
+}
+
+// Etiquetas como "priority: high", "P1" ou "urgent" viram a prioridade
+fn label_priority(label: &str) -> Option<char> {
+    let label = label.to_lowercase();
+    let level = label
+        .strip_prefix("priority")
+        .map(|rest| rest.trim_start_matches([':', '/', '-', ' ']))
+        .unwrap_or(&label);
+    match level {
+        "critical" | "urgent" | "high" | "alta" => Some('A'),
+        "medium" | "média" | "media" => Some('B'),
+        "low" | "baixa" => Some('C'),
+        _ => {
+            let n: u8 = level.strip_prefix('p')?.parse().ok()?;
+            (n <= 8).then_some((b'A' + n) as char)
+        }
+    }
+}
+
+// Lista salva da API REST (`/repos/DONO/REPO/issues`, ou a busca com "items")
+// ou de `gh issue list --json ...`
+fn github(text: &str) -> Result<(Vec<Task>, usize), String> {
+    let value = parse_json(text, "GitHub")?;
+    let issues = match &value {
+        Value::Array(issues) => issues.clone(),
+        _ => field(&value, &["items"])
+            .and_then(Value::as_array)
+            .cloned()
+            .ok_or("JSON do GitHub sem a lista de issues")?,
+    };
+
+    let mut tasks = Vec::new();
+    let mut ignored = 0;
+    for issue in &issues {
+        let title = str_field(issue, &["title"]).unwrap_or_default();
+        if title.trim().is_empty() {
+            ignored += 1;
+            continue;
+        }
+        let text = match key_field(issue, &["number"]) {
+            Some(number) => format!("{} (#{})", title, number),
+            None => title.to_string(),
+        };
+        let done = str_field(issue, &["state"]).is_some_and(|state| state.eq_ignore_ascii_case("closed"));
+        let mut task = Task::new(&text, done);
+        let entry = &mut task.entry;
+        entry.created = date_field(issue, &["created_at", "createdAt"]);
+        entry.completed = if done { date_field(issue, &["closed_at", "closedAt"]) } else { None };
+        entry.due = field(issue, &["milestone"]).and_then(|milestone| date_field(milestone, &["due_on", "dueOn"]));
+        let url = str_field(issue, &["html_url", "url"]);
+        let body = str_field(issue, &["body"]).map(str::trim).filter(|body| !body.is_empty());
+        let note: Vec<&str> = url.into_iter().chain(body).collect();
+        if !note.is_empty() {
+            entry.note = Some(note.join("\n\n"));
+        }
+
+        // O repositório é o projeto: "repository_url" da API REST ou "repository" do gh
+        let repository = str_field(issue, &["repository_url"])
+            .or_else(|| field(issue, &["repository"]).and_then(|repo| str_field(repo, &["name"])))
+            .and_then(|repo| repo.rsplit('/').next());
+        task.tags.extend(repository.and_then(tag));
+        let labels = field(issue, &["labels"]).and_then(Value::as_array).cloned().unwrap_or_default();
+        for label in &labels {
+            let Some(name) = label.as_str().or_else(|| str_field(label, &["name"])) else {
+                continue;
+            };
+            match label_priority(name) {
+                Some(priority) => {
+                    task.entry.priority = Some(task.entry.priority.map_or(priority, |current| current.min(priority)))
+                }
+                None => task.tags.extend(tag(name)),
+            }
+        }
+        tasks.push(task);
+    }
+    Ok((tasks, ignored))
+}
+
+pub fn parse(format: Format, text: &str, name: &str) -> Result<Preview, String> {
+    let (tasks, ignored) = match format {
+        Format::Taskwarrior => taskwarrior(text)?,
+        Format::Todoist if text.trim_start().starts_with(['{', '[']) => todoist_json(text)?,
+        Format::Todoist => todoist_csv(text, name)?,
+        Format::GitHub => github(text)?,
+    };
+    Ok(Preview {
+        tasks: arrange(tasks),
+        ignored,
+    })
+}
+
+// Coloca cada subtarefa logo abaixo da tarefa pai, com o recuo certo
+fn arrange(tasks: Vec<Task>) -> Vec<Imported> {
+    let positions: HashMap<&str, usize> = tasks
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, task)| Some((task.key.as_deref()?, pos)))
+        .collect();
+    let parents: Vec<Option<usize>> = tasks
+        .iter()
+        .enumerate()
+        .map(|(pos, task)| {
+            let parent = *positions.get(task.parent_key.as_deref()?)?;
+            (parent != pos).then_some(parent)
+        })
+        .collect();
+
+    let mut order: Vec<(usize, Option<usize>)> = Vec::new();
+    let mut stack: Vec<(usize, Option<usize>)> = (0..tasks.len()).rev().filter(|&i| parents[i].is_none()).map(|i| (i, None)).collect();
+    let mut placed = vec![false; tasks.len()];
+    while let Some((i, parent)) = stack.pop() {
+        if placed[i] {
+            continue;
+        }
+        placed[i] = true;
+        let at = order.len();
+        order.push((i, parent));
+        for child in (0..tasks.len()).rev().filter(|&child| parents[child] == Some(i)) {
+            stack.push((child, Some(at)));
+        }
+    }
+    // Tarefas presas num ciclo de tarefas pai entram sem tarefa pai
+    order.extend((0..tasks.len()).filter(|&i| !placed[i]).map(|i| (i, None)));
+
+    let mut tasks: Vec<Option<Task>> = tasks.into_iter().map(Some).collect();
+    let mut arranged: Vec<Imported> = Vec::new();
+    for (i, parent) in order {
+        let Some(task) = tasks[i].take() else {
+            continue;
+        };
+        let mut entry = task.entry;
+        entry.todo_entry = with_tags(&entry.todo_entry, &task.tags);
+        entry.depth = parent.map_or(0, |parent| arranged[parent].entry.depth + 1);
+        arranged.push(Imported {
+            entry,
+            parent,
+            duplicate: false,
+        });
+    }
+    arranged
+}
+
+// Textos comparados sem diferença de maiúsculas e espaços
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+impl Todo {
+    // Marca as tarefas que já estão na lista ou que se repetem no próprio arquivo
+    pub fn check_duplicates(&self, preview: &mut Preview) {
+        let mut seen: Vec<String> = self.todo.iter().map(|line| normalize(&Entry::read_line(line).todo_entry)).collect();
+        for task in &mut preview.tasks {
+            let text = normalize(&task.entry.todo_entry);
+            task.duplicate = seen.contains(&text);
+            if !task.duplicate {
+                seen.push(text);
+            }
+        }
+    }
+
+    // Grava no fim da lista as tarefas que não são duplicadas. Uma subtarefa cuja tarefa pai
+    // ficou de fora entra no primeiro nível. Devolve quantas foram gravadas.
+    pub fn import(&self, preview: &Preview) -> Result<usize, String> {
+        let mut depths: Vec<Option<usize>> = Vec::new();
+        let mut lines = Vec::new();
+        for task in &preview.tasks {
+            if task.duplicate {
+                depths.push(None);
+                continue;
+            }
+            let depth = task.parent.and_then(|parent| depths[parent]).map_or(0, |depth| depth + 1);
+            depths.push(Some(depth));
+            let mut entry = task.entry.clone();
+            entry.depth = depth;
+            entry.created = entry.created.or(Some(today()));
+            if entry.done {
+                entry.completed = entry.completed.or(Some(today()));
+            }
+            lines.push(entry.file_line().trim_end_matches('\n').to_string());
+        }
+        if lines.is_empty() {
+            return Ok(0);
+        }
+        self.storage().append(&lines)?;
+        Ok(lines.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{reload, temp_list, texts};
+
+    fn date(text: &str) -> Option<NaiveDate> {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+    }
+
+    fn preview_texts(preview: &Preview) -> Vec<(usize, &str)> {
+        preview.tasks.iter().map(|task| (task.entry.depth, task.entry.todo_entry.as_str())).collect()
+    }
+
+    #[test]
+    fn taskwarrior_export() {
+        let text = r#"[
+            {"uuid": "1", "description": "Revisar  contrato", "status": "pending", "project": "Trabalho",
+             "tags": ["urgente"], "priority": "H", "due": "20260501T120000Z", "entry": "20260401T120000Z",
+             "annotations": [{"description": "ver cláusula 3"}, {"description": "ligar pro João"}]},
+            {"uuid": "2", "description": "Pagar luz", "status": "completed", "end": "20260402T120000Z", "priority": "L"},
+            {"uuid": "3", "description": "Esperando peça", "status": "waiting"},
+            {"uuid": "4", "description": "Escrevendo", "status": "pending", "start": "20260403T120000Z"},
+            {"uuid": "5", "description": "Apagada", "status": "deleted"},
+            {"uuid": "6", "description": "Modelo", "status": "recurring", "recur": "weekly"},
+            {"uuid": "7", "status": "pending"}
+        ]"#;
+        let preview = parse(Format::Taskwarrior, text, "").unwrap();
+        assert_eq!(preview.ignored, 3);
+        assert_eq!(
+            preview_texts(&preview),
+            [(0, "Revisar contrato #Trabalho #urgente"), (0, "Pagar luz"), (0, "Esperando peça"), (0, "Escrevendo")]
+        );
+        let entries: Vec<&Entry> = preview.tasks.iter().map(|task| &task.entry).collect();
+        assert_eq!(entries[0].priority, Some('A'));
+        assert_eq!(entries[0].due, date("2026-05-01"));
+        assert_eq!(entries[0].created, date("2026-04-01"));
+        assert_eq!(entries[0].note.as_deref(), Some("ver cláusula 3\nligar pro João"));
+        assert!(entries[1].done);
+        assert_eq!(entries[1].completed, date("2026-04-02"));
+        assert_eq!(entries[1].priority, Some('C'));
+        assert_eq!(entries[2].status.as_deref(), Some("aguardando"));
+        assert_eq!(entries[3].status.as_deref(), Some("fazendo"));
+    }
+
+    #[test]
+    fn taskwarrior_line_per_object_and_malformed_lines() {
+        let text = "{\"description\": \"a\", \"status\": \"pending\"}\n{\"description\": \"b\", \"status\": \"completed\"}\n";
+        let preview = parse(Format::Taskwarrior, text, "").unwrap();
+        assert_eq!(preview_texts(&preview), [(0, "a"), (0, "b")]);
+
+        let text = "{\"description\": \"a\", \"status\": \"pending\"}\n{\"description\": \"b\", \n";
+        let error = parse(Format::Taskwarrior, text, "").err().unwrap();
+        assert!(error.starts_with("JSON do Taskwarrior inválido"), "{}", error);
+    }
+
+    #[test]
+    fn todoist_json_backup() {
+        let text = r#"{
+            "projects": [{"id": "p1", "name": "Casa nova"}],
+            "items": [
+                {"id": "10", "content": "Mudança", "project_id": "p1", "priority": 4, "labels": ["fim de semana"],
+                 "due": {"date": "2026-05-01"}, "description": "alugar caminhão"},
+                {"id": 11, "content": "Caixas", "parent_id": "10", "checked": true, "completed_at": "2026-04-02T12:00:00Z"},
+                {"id": "12", "content": "Órfã", "parent_id": "99", "priority": 1},
+                {"id": "13", "content": "Apagada", "is_deleted": true},
+                {"id": "14", "project_id": "p1"}
+            ],
+            "notes": [{"item_id": "10", "content": "confirmar data"}]
+        }"#;
+        let preview = parse(Format::Todoist, text, "").unwrap();
+        assert_eq!(preview.ignored, 2);
+        assert_eq!(
+            preview_texts(&preview),
+            [(0, "Mudança #Casa-nova #fim-de-semana"), (1, "Caixas"), (0, "Órfã")]
+        );
+        assert_eq!(preview.tasks[1].parent, Some(0));
+        let entry = &preview.tasks[0].entry;
+        assert_eq!(entry.priority, Some('A'));
+        assert_eq!(entry.due, date("2026-05-01"));
+        assert_eq!(entry.note.as_deref(), Some("alugar caminhão\n\nconfirmar data"));
+        assert!(preview.tasks[1].entry.done);
+        assert_eq!(preview.tasks[1].entry.completed, date("2026-04-02"));
+        assert_eq!(preview.tasks[2].entry.priority, None);
+
+        assert!(parse(Format::Todoist, r#"{"projects": []}"#, "").is_err());
+        assert!(parse(Format::Todoist, r#"{"items": [}"#, "").is_err());
+    }
+
+    #[test]
+    fn todoist_csv_template() {
+        let text = "\u{feff}TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,DATE\r\n\
+                    section,Semana,,,,,\r\n\
+                    task,Comprar tinta @loja,\"branca, fosca\",1,1,,2026-05-01\r\n\
+                    task,Pincel,,4,2,,\r\n\
+                    note,\"levar a amostra\nda parede\",,,,,\r\n\
+                    task,@só-etiqueta,,,1,,\r\n\
+                    task,Pintar,,,1,,every monday\r\n";
+        let preview = parse(Format::Todoist, text, "Reforma").unwrap();
+        assert_eq!(preview.ignored, 2);
+        assert_eq!(
+            preview_texts(&preview),
+            [(0, "Comprar tinta #Reforma #loja"), (1, "Pincel #Reforma"), (0, "Pintar #Reforma")]
+        );
+        let entry = &preview.tasks[0].entry;
+        assert_eq!(entry.priority, Some('A'));
+        assert_eq!(entry.due, date("2026-05-01"));
+        assert_eq!(entry.note.as_deref(), Some("branca, fosca"));
+        assert_eq!(preview.tasks[1].entry.note.as_deref(), Some("levar a amostra\nda parede"));
+        assert_eq!(preview.tasks[2].entry.due, None);
+
+        let error = parse(Format::Todoist, "CONTENT,PRIORITY\nComprar,1\n", "x").err().unwrap();
+        assert_eq!(error, "CSV do Todoist sem as colunas TYPE e CONTENT");
+        assert!(parse(Format::Todoist, "", "x").is_err());
+    }
+
+    #[test]
+    fn github_issues() {
+        let text = r#"[
+            {"number": 12, "title": "Corrigir login", "state": "open", "html_url": "https://github.com/dono/app/issues/12",
+             "body": "  quebra no Safari  ", "created_at": "2026-04-01T12:00:00Z",
+             "repository_url": "https://api.github.com/repos/dono/app",
+             "labels": [{"name": "bug"}, {"name": "priority: medium"}, {"name": "P0"}],
+             "milestone": {"due_on": "2026-05-01T12:00:00Z"}},
+            {"number": 13, "title": "Docs", "state": "CLOSED", "closedAt": "2026-04-02T12:00:00Z",
+             "repository": {"name": "app"}, "labels": ["good first issue"]},
+            {"number": 14, "state": "open"}
+        ]"#;
+        let preview = parse(Format::GitHub, text, "").unwrap();
+        assert_eq!(preview.ignored, 1);
+        assert_eq!(
+            preview_texts(&preview),
+            [(0, "Corrigir login (#12) #app #bug"), (0, "Docs (#13) #app #good-first-issue")]
+        );
+        let entry = &preview.tasks[0].entry;
+        assert_eq!(entry.priority, Some('A'));
+        assert_eq!(entry.due, date("2026-05-01"));
+        assert_eq!(entry.created, date("2026-04-01"));
+        assert_eq!(entry.note.as_deref(), Some("https://github.com/dono/app/issues/12\n\nquebra no Safari"));
+        assert!(preview.tasks[1].entry.done);
+        assert_eq!(preview.tasks[1].entry.completed, date("2026-04-02"));
+
+        assert_eq!(label_priority("priority: high"), Some('A'));
+        assert_eq!(label_priority("p2"), Some('C'));
+        assert_eq!(label_priority("p9"), None);
+        assert!(parse(Format::GitHub, r#"{"total_count": 0}"#, "").is_err());
+        assert!(parse(Format::GitHub, "[{\"title\": ", "").is_err());
+    }
+
+    #[test]
+    fn duplicates_are_marked_and_skipped() {
+        let (_dir, todo) = temp_list(&["[ ] Comprar pão #casa"]);
+        let text = r#"{"items": [
+            {"id": "1", "content": "comprar   PÃO #casa"},
+            {"id": "2", "content": "Ir à feira"},
+            {"id": "3", "content": "Levar sacola", "parent_id": "1"},
+            {"id": "4", "content": "ir à feira"}
+        ]}"#;
+        let mut preview = parse(Format::Todoist, text, "").unwrap();
+        todo.check_duplicates(&mut preview);
+        let duplicates: Vec<bool> = preview.tasks.iter().map(|task| task.duplicate).collect();
+        assert_eq!(
+            preview_texts(&preview),
+            [(0, "comprar PÃO #casa"), (1, "Levar sacola"), (0, "Ir à feira"), (0, "ir à feira")]
+        );
+        assert_eq!(duplicates, [true, false, false, true]);
+        assert_eq!(preview.new_tasks(), 2);
+
+        // A subtarefa de uma duplicada entra no primeiro nível
+        assert_eq!(todo.import(&preview).unwrap(), 2);
+        let todo = reload(&todo);
+        assert_eq!(texts(&todo), ["Comprar pão #casa", "Levar sacola", "Ir à feira"]);
+        assert_eq!(todo.index_labels(), ["1", "2", "3"]);
+    }
+}
+
@@ -1,42 +1,358 @@
+use chrono::{Local, NaiveDate};
 use colored::*;
-use std::fs;
-use std::fs::OpenOptions;
-use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
 use std::path::PathBuf;
-use std::{env, process};
+use std::env;
 
+mod archive;
+pub mod crypto;
+pub mod daemon;
+pub mod diff;
+pub mod editor;
+pub mod ical;
+pub mod import;
+pub mod lists;
+pub mod merge;
+pub mod project;
+mod recur;
+pub mod remind;
+pub mod scan;
+pub mod server;
+pub mod stats;
+pub mod storage;
+pub mod sync;
+
+pub use archive::{ArchivedTask, read_archive};
+pub use recur::Recurrence;
+pub use scan::SourceRef;
+
+// Cada nível de subtarefa é indentado com dois espaços no arquivo
+const INDENT: &str = "  ";
+
+// Chaves dos metadados "chave:valor" gravados no fim da linha
+const METADATA_KEYS: &[&str] = &["status", "due", "added", "done", "pri", "rec", "id", "next", "dep", "src", "note"];
+
+// Formato das datas gravadas no arquivo (ex.: due:2025-03-14)
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+// Diretório que só o usuário acessa, para os arquivos temporários do editor e o socket do daemon:
+// o de execução ($XDG_RUNTIME_DIR), que já é 0700, ou um subdiretório 0700 do diretório temporário
+#[cfg(unix)]
+pub fn private_dir() -> Result<PathBuf, String> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir);
+    }
+    let uid = unsafe { libc::getuid() };
+    let dir = env::temp_dir().join(format!("todo-todo-{}", uid));
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(e) => return Err(format!("Não foi possível criar o diretório {}: {}", dir.display(), e)),
+    }
+    // Outro usuário pode ter criado o diretório antes: só serve se for nosso e fechado
+    let meta = std::fs::symlink_metadata(&dir).map_err(|e| format!("Erro ao ler {}: {}", dir.display(), e))?;
+    if !meta.is_dir() || meta.uid() != uid || meta.mode() & 0o077 != 0 {
+        return Err(format!("O diretório {} não é privado (dono ou permissões inesperados)", dir.display()));
+    }
+    Ok(dir)
+}
+
+// No Windows, o diretório temporário já é por usuário
+#[cfg(not(unix))]
+pub fn private_dir() -> Result<PathBuf, String> {
+    Ok(env::temp_dir())
+}
+
+// Prioridades vão de A (mais alta) a Z
+pub fn parse_priority(text: &str) -> Result<char, String> {
+    let mut chars = text.trim().chars();
+    match (chars.next(), chars.next()) {
+        (Some(priority), None) if priority.is_ascii_alphabetic() => Ok(priority.to_ascii_uppercase()),
+        _ => Err(format!("Prioridade inválida '{}', use uma letra de A a Z", text)),
+    }
+}
+
+// Aceita AAAA-MM-DD, "hoje" e "amanhã"
+pub fn parse_date(text: &str) -> Result<NaiveDate, String> {
+    match text.trim() {
+        "hoje" => Ok(today()),
+        "amanhã" | "amanha" => Ok(today() + chrono::Days::new(1)),
+        text => NaiveDate::parse_from_str(text, DATE_FORMAT)
+            .map_err(|_| format!("Data inválida '{}', use AAAA-MM-DD", text)),
+    }
+}
+
+// A anotação vira um só token: espaços, quebras de linha e '%' são escritos como %XX
+fn encode_note(note: &str) -> String {
+    let mut encoded = String::new();
+    for c in note.chars() {
+        match c {
+            '%' => encoded.push_str("%25"),
+            ' ' => encoded.push_str("%20"),
+            '\t' => encoded.push_str("%09"),
+            '\n' => encoded.push_str("%0A"),
+            '\r' => {}
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+fn decode_note(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[derive(Clone, PartialEq)]
 pub struct Entry {
     pub todo_entry: String,
     pub done: bool,
+    pub status: Option<String>,
+    pub due: Option<NaiveDate>,
+    // Dia em que a tarefa foi criada e dia em que foi concluída
+    pub created: Option<NaiveDate>,
+    pub completed: Option<NaiveDate>,
+    pub depth: usize,
+    pub id: Option<u32>,
+    pub deps: Vec<u32>,
+    pub priority: Option<char>,
+    pub recur: Option<Recurrence>,
+    // ID da ocorrência criada quando esta tarefa recorrente foi concluída
+    pub next: Option<u32>,
+    // Anotação de várias linhas, gravada codificada num único token "note:"
+    pub note: Option<String>,
+    // Comentário de código de onde a tarefa foi importada (veja `scan`)
+    pub source: Option<SourceRef>,
 }
 
 impl Entry {
     pub fn new(todo_entry: String, done: bool) -> Self {
-        Self { todo_entry, done }
+        Self {
+            todo_entry,
+            done,
+            status: None,
+            due: None,
+            created: None,
+            completed: None,
+            depth: 0,
+            id: None,
+            deps: Vec::new(),
+            priority: None,
+            recur: None,
+            next: None,
+            note: None,
+            source: None,
+        }
     }
 
     pub fn file_line(&self) -> String {
         let symbol = if self.done { "[*] " } else { "[ ] " };
-        format!("{}{}\n", symbol, self.todo_entry)
+        let text = escape_text(&self.todo_entry);
+        format!("{}{}{}{}\n", INDENT.repeat(self.depth), symbol, text, self.metadata())
     }
 
-    pub fn list_line(&self, number: usize) -> String {
+    // Metadados gravados no fim da linha como "chave:valor"
+    fn metadata(&self) -> String {
+        let mut meta = String::new();
+        if let Some(status) = &self.status {
+            meta.push_str(&format!(" status:{}", status));
+        }
+        if let Some(due) = &self.due {
+            meta.push_str(&format!(" due:{}", due.format(DATE_FORMAT)));
+        }
+        if let Some(created) = &self.created {
+            meta.push_str(&format!(" added:{}", created.format(DATE_FORMAT)));
+        }
+        if let Some(completed) = &self.completed {
+            meta.push_str(&format!(" done:{}", completed.format(DATE_FORMAT)));
+        }
+        if let Some(priority) = self.priority {
+            meta.push_str(&format!(" pri:{}", priority));
+        }
+        if let Some(recur) = &self.recur {
+            meta.push_str(&format!(" rec:{}", recur));
+        }
+        if let Some(id) = self.id {
+            meta.push_str(&format!(" id:{}", id));
+        }
+        if let Some(next) = self.next {
+            meta.push_str(&format!(" next:{}", next));
+        }
+        if !self.deps.is_empty() {
+            let deps: Vec<String> = self.deps.iter().map(u32::to_string).collect();
+            meta.push_str(&format!(" dep:{}", deps.join(",")));
+        }
+        if let Some(source) = &self.source {
+            meta.push_str(&format!(" src:{}", source.token()));
+        }
+        if let Some(note) = &self.note {
+            meta.push_str(&format!(" note:{}", encode_note(note)));
+        }
+        meta
+    }
+
+    // Interpreta um token "chave:valor"; devolve false se não for um metadado conhecido
+    fn read_metadata(&mut self, word: &str) -> bool {
+        let Some((key, value)) = word.split_once(':') else {
+            return false;
+        };
+        if value.is_empty() {
+            return false;
+        }
+        match key {
+            "status" => self.status = Some(value.to_string()),
+            "due" => match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                Ok(date) => self.due = Some(date),
+                Err(_) => return false,
+            },
+            "added" => match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                Ok(date) => self.created = Some(date),
+                Err(_) => return false,
+            },
+            "done" => match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                Ok(date) => self.completed = Some(date),
+                Err(_) => return false,
+            },
+            "pri" => match parse_priority(value) {
+                Ok(priority) => self.priority = Some(priority),
+                Err(_) => return false,
+            },
+            "rec" => match Recurrence::parse(value) {
+                Ok(recur) => self.recur = Some(recur),
+                Err(_) => return false,
+            },
+            "id" => match value.parse() {
+                Ok(id) => self.id = Some(id),
+                Err(_) => return false,
+            },
+            "next" => match value.parse() {
+                Ok(next) => self.next = Some(next),
+                Err(_) => return false,
+            },
+            "dep" => match value.split(',').map(str::parse).collect() {
+                Ok(deps) => self.deps = deps,
+                Err(_) => return false,
+            },
+            "src" => match SourceRef::parse(value) {
+                Some(source) => self.source = Some(source),
+                None => return false,
+            },
+            "note" => match decode_note(value) {
+                Some(note) => self.note = Some(note),
+                None => return false,
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    // Marca ou desmarca, guardando o dia da conclusão
+    pub fn set_done(&mut self, done: bool) {
+        if self.done != done {
+            self.done = done;
+            self.completed = if done { Some(today()) } else { None };
+        }
+    }
+
+    // Palavras do texto começando com '#' (ex.: #casa)
+    pub fn tags(&self) -> Vec<&str> {
+        self.todo_entry
+            .split_whitespace()
+            .filter(|word| word.len() > 1 && word.starts_with('#'))
+            .collect()
+    }
+
+    pub fn is_overdue(&self) -> bool {
+        !self.done && self.due.is_some_and(|due| due < today())
+    }
+
+    // `blocked_by` traz os índices das tarefas pendentes que bloqueiam esta
+    pub fn list_line(&self, index: &str, blocked_by: &[String]) -> String {
         let todo_entry = if self.done {
             self.todo_entry.strikethrough().to_string()
+        } else if !blocked_by.is_empty() {
+            self.todo_entry.dimmed().to_string()
         } else {
             self.todo_entry.clone()
         };
-        format!("{number} {todo_entry}\n")
+        let priority = match self.priority {
+            Some(priority) => format!("{} ", format!("({})", priority).magenta()),
+            None => String::new(),
+        };
+        let status = match &self.status {
+            Some(status) => format!(" {}", format!("({})", status).cyan()),
+            None => String::new(),
+        };
+        let due = match &self.due {
+            Some(due) if self.is_overdue() => format!(" {}", format!("[vence {}]", due).red().bold()),
+            Some(due) => format!(" {}", format!("[vence {}]", due).yellow()),
+            None => String::new(),
+        };
+        let recur = match &self.recur {
+            Some(recur) => format!(" {}", format!("↻ {}", recur).blue()),
+            None => String::new(),
+        };
+        let note = if self.note.is_some() { format!(" {}", "✎".dimmed()) } else { String::new() };
+        let source = match &self.source {
+            Some(source) => format!(" {}", source.to_string().dimmed()),
+            None => String::new(),
+        };
+        let blocked = if blocked_by.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", format!("[bloqueada por {}]", blocked_by.join(", ")).dimmed())
+        };
+        let indent = INDENT.repeat(self.depth);
+        format!("{indent}{index} {priority}{todo_entry}{status}{due}{recur}{note}{source}{blocked}\n")
     }
 
-    pub fn read_line(line: &String) -> Self {
+    pub fn read_line(line: &str) -> Self {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let depth = indent / INDENT.len();
+        let line = &line[indent..];
         let done = line.len() >= 4 && &line[..4] == "[*] ";
-        let todo_entry = if line.len() > 4 {
-            line[4..].to_string()
+        let text = if line.len() > 4 {
+            &line[4..]
         } else {
-            line.clone()
+            line
         };
-        Self { todo_entry, done }
+
+        let mut entry = Self::new(String::new(), done);
+        entry.depth = depth;
+        // Os metadados ficam no fim da linha: a leitura para na última palavra que não é um deles
+        let mut words: Vec<&str> = text.split(' ').collect();
+        let mut has_metadata = false;
+        while let Some(word) = words.last() {
+            if !word.is_empty() {
+                if !entry.read_metadata(word) {
+                    break;
+                }
+                has_metadata = true;
+            }
+            words.pop();
+        }
+        // Sem metadados o texto fica intacto
+        let text = if has_metadata { words.join(" ").trim_end().to_string() } else { text.to_string() };
+        entry.todo_entry = unescape_text(&text);
+        entry
     }
 
     pub fn raw_line(&self) -> String {
@@ -44,22 +360,51 @@ impl Entry {
     }
 }
 
+#[derive(Clone)]
 pub struct Todo {
     pub todo: Vec<String>,
     pub todo_path: PathBuf,
     pub todo_bak: PathBuf,
     pub no_backup: bool,
+    // Lista de um projeto (um `.todo` achado a partir do diretório atual, veja `project`)
+    pub project: bool,
 }
 
 impl Todo {
     pub fn new() -> Result<Self, String> {
+        Self::open_list(None)
+    }
+
+    // Abre uma lista nomeada (veja `lists`) ou, sem nome, o arquivo TODO principal
+    pub fn open_list(list: Option<&str>) -> Result<Self, String> {
+        Self::locate(list)?.load()
+    }
+
+    // Resolve os caminhos da lista e do backup, sem ler o arquivo (as linhas ficam vazias)
+    pub fn locate(list: Option<&str>) -> Result<Self, String> {
         // Determina o diretório home cross-platform
         let home_dir = dirs::home_dir().ok_or("Não foi possível determinar o diretório home")?;
 
+        // Um `.todo` de projeto tem precedência sobre o TODO principal, mas não sobre TODO_PATH
+        let project_path = match (list, env::var("TODO_PATH")) {
+            (None, Err(_)) => project::discover(),
+            _ => None,
+        };
+
         // Caminho do TODO principal - with better error handling
-        let todo_path = match env::var("TODO_PATH") {
-            Ok(path) => PathBuf::from(path),
-            Err(_) => {
+        let todo_path = match (list, env::var("TODO_PATH")) {
+            (None, _) if let Some(path) = &project_path => path.clone(),
+            (Some(name), _) => {
+                if !lists::exists(name)? {
+                    return Err(format!(
+                        "A lista '{}' não existe (crie com: todo lists mk {})",
+                        name, name
+                    ));
+                }
+                lists::list_path(name)?
+            }
+            (None, Ok(path)) => PathBuf::from(path),
+            (None, Err(_)) => {
                 let legacy = home_dir.join("TODO");
                 if legacy.exists() {
                     legacy
@@ -70,7 +415,7 @@ impl Todo {
         };
 
         // Caminho do backup - with better error handling
-        let todo_bak = match env::var("TODO_BAK_DIR") {
+        let mut todo_bak = match env::var("TODO_BAK_DIR") {
             Ok(path) => PathBuf::from(path),
             Err(_) => {
                 if cfg!(windows) {
@@ -81,45 +426,57 @@ impl Todo {
             }
         };
 
-        // Check if backup is disabled
-        let no_backup = match env::var("TODO_NOBACKUP") {
-            Ok(_) => true,
-            Err(_) => false,
-        };
-
-        // Cria o arquivo se não existir
-        let todofile = OpenOptions::new()
-            .write(true)
-            .read(true)
-            .create(true)
-            .open(&todo_path)
-            .map_err(|e| format!("Não foi possível abrir o arquivo TODO: {}", e))?;
+        // A lista de um projeto guarda o backup ao lado dela, para não misturar projetos
+        if let Some(path) = &project_path {
+            todo_bak = path.with_file_name(format!("{}.bak", project::PROJECT_FILE));
+        }
 
-        let mut buf_reader = BufReader::new(&todofile);
-        let mut contents = String::new();
-        
-        // Replace unwrap() with proper error handling
-        buf_reader.read_to_string(&mut contents)
-            .map_err(|e| format!("Erro ao ler o arquivo TODO: {}", e))?;
+        // Cada lista nomeada tem o seu próprio backup (ex.: /tmp/todo.bak.trabalho)
+        if let Some(name) = list {
+            let mut name_bak = todo_bak.into_os_string();
+            name_bak.push(format!(".{}", name));
+            todo_bak = PathBuf::from(name_bak);
+        }
 
-        let todo = contents.lines().map(str::to_string).collect();
+        // Check if backup is disabled
+        let no_backup = env::var("TODO_NOBACKUP").is_ok();
 
         Ok(Self {
-            todo,
+            todo: Vec::new(),
             todo_path,
             todo_bak,
             no_backup,
+            project: project_path.is_some(),
         })
     }
 
+    // Lê as linhas do arquivo da lista
+    pub fn load(mut self) -> Result<Self, String> {
+        self.todo = self.storage().load()?;
+        Ok(self)
+    }
+
+    // Lê de novo a mesma lista (ex.: depois de uma alteração)
+    pub fn reload(&self) -> Result<Self, String> {
+        let todo = Self {
+            todo: Vec::new(),
+            todo_path: self.todo_path.clone(),
+            todo_bak: self.todo_bak.clone(),
+            no_backup: self.no_backup,
+            project: self.project,
+        };
+        todo.load()
+    }
+
     pub fn list(&self) {
         let stdout = io::stdout();
         let mut writer = BufWriter::new(stdout);
         let mut data = String::new();
 
-        for (number, task) in self.todo.iter().enumerate() {
+        let blockers = self.blockers();
+        for ((task, index), blocked_by) in self.todo.iter().zip(self.index_labels()).zip(blockers) {
             let entry = Entry::read_line(task);
-            let line = entry.list_line(number + 1);
+            let line = entry.list_line(&index, &blocked_by);
             data.push_str(&line);
         }
 
@@ -140,222 +497,987 @@ impl Todo {
 
         for task in self.todo.iter() {
             let entry = Entry::read_line(task);
-            if (entry.done && arg == "done") || (!entry.done && arg == "todo") {
-                if let Err(e) = writer.write_all(entry.raw_line().as_bytes()) {
-                    eprintln!("Falha na gravação: {}", e);
-                    break;
-                }
+            if ((entry.done && arg == "done") || (!entry.done && arg == "todo"))
+                && let Err(e) = writer.write_all(entry.raw_line().as_bytes())
+            {
+                eprintln!("Falha na gravação: {}", e);
+                break;
             }
         }
     }
 
-    pub fn add(&self, args: &[String]) {
+    pub fn add(&self, args: &[String]) -> Result<(), String> {
         if args.is_empty() {
-            eprintln!("Precisa colocar alguma tarefa");
-            process::exit(1);
-        }
-
-        let todofile = match OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.todo_path) {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("Não foi possível abrir o arquivo: {}", e);
-                process::exit(1);
-            }
-        };
-
-        let mut buffer = BufWriter::new(todofile);
+            return Err("Precisa colocar alguma tarefa".to_string());
+        }
 
+        let mut lines = Vec::new();
         for arg in args {
             if arg.trim().is_empty() {
                 continue;
             }
-            let entry = Entry::new(arg.to_string(), false);
-            if let Err(e) = buffer.write_all(entry.file_line().as_bytes()) {
-                eprintln!("Falha na gravação: {}", e);
-                process::exit(1);
-            }
+            let mut entry = Entry::new(arg.to_string(), false);
+            entry.created = Some(today());
+            lines.push(entry.file_line().trim_end_matches('\n').to_string());
         }
+        self.storage().append(&lines)
     }
 
-    pub fn remove(&self, args: &[String]) {
+    pub fn remove(&self, args: &[String]) -> Result<(), String> {
         if args.is_empty() {
-            eprintln!("Precisa remover alguma tarefa");
-            process::exit(1);
-        }
-
-        let todofile = match OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.todo_path) {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("Não foi possível abrir o arquivo: {}", e);
-                process::exit(1);
+            return Err("Precisa remover alguma tarefa".to_string());
+        }
+
+        let removed = self.subtree_mask(args);
+        let mut data = String::new();
+        for (pos, line) in self.todo.iter().enumerate() {
+            if removed[pos] {
+                continue;
             }
-        };
+            data.push_str(&format!("{}\n", line));
+        }
 
-        let mut buffer = BufWriter::new(todofile);
+        self.write_file(&data)
+    }
+
+    // Linhas das tarefas informadas e de todas as suas subtarefas
+    fn subtree_mask(&self, args: &[String]) -> Vec<bool> {
+        let depths = self.depths();
+        let mut mask = vec![false; self.todo.len()];
+        for pos in self.positions(args) {
+            for flag in &mut mask[pos..subtree_end(&depths, pos)] {
+                *flag = true;
+            }
+        }
+        mask
+    }
 
+    // Move as tarefas (com suas subtarefas) para o fim de outra lista.
+    // IDs e dependências não valem na outra lista e são descartados.
+    pub fn move_to(&self, args: &[String], target: &Todo) -> Result<(), String> {
+        if args.is_empty() {
+            return Err("Precisa mover alguma tarefa".to_string());
+        }
+        if self.todo_path == target.todo_path {
+            return Err("A lista de destino é a mesma de origem".to_string());
+        }
+
+        let moved = self.subtree_mask(args);
+        let depths = self.depths();
+        let mut data: String = target.todo.iter().map(|line| format!("{}\n", line)).collect();
+        let mut base_depth = 0;
         for (pos, line) in self.todo.iter().enumerate() {
-            if args.contains(&(pos + 1).to_string()) {
+            if !moved[pos] {
                 continue;
             }
-            if let Err(e) = buffer.write_all(format!("{}\n", line).as_bytes()) {
-                eprintln!("Falha na gravação: {}", e);
-                process::exit(1);
+            if pos == 0 || !moved[pos - 1] || depths[pos] <= base_depth {
+                base_depth = depths[pos];
             }
+            let mut entry = Entry::read_line(line);
+            entry.depth = depths[pos] - base_depth;
+            entry.id = None;
+            entry.next = None;
+            entry.deps.clear();
+            data.push_str(&entry.file_line());
         }
+
+        // Grava primeiro o destino: se a remoção falhar, a tarefa fica duplicada, não perdida
+        target.write_file(&data)?;
+        let remaining: String = self
+            .todo
+            .iter()
+            .zip(moved)
+            .filter(|(_, moved)| !moved)
+            .map(|(line, _)| format!("{}\n", line))
+            .collect();
+        self.write_file(&remaining)
     }
 
-    fn remove_file(&self) {
-        if let Err(e) = fs::remove_file(&self.todo_path) {
-            eprintln!("Erro ao apagar arquivo: {}", e)
+    // Insere subtarefas logo após as subtarefas já existentes do pai
+    pub fn add_sub(&self, args: &[String]) -> Result<(), String> {
+        if args.len() < 2 {
+            return Err("Uso: todo sub [ÍNDICE] [SUBTAREFA/S]".to_string());
+        }
+        let parent = self.position(&args[0])?;
+        let depths = self.depths();
+        let end = subtree_end(&depths, parent);
+
+        let mut data = String::new();
+        for line in &self.todo[..end] {
+            data.push_str(&format!("{}\n", line));
+        }
+        for arg in &args[1..] {
+            if arg.trim().is_empty() {
+                continue;
+            }
+            let mut entry = Entry::new(arg.to_string(), false);
+            entry.created = Some(today());
+            entry.depth = depths[parent] + 1;
+            data.push_str(&entry.file_line());
         }
+        for line in &self.todo[end..] {
+            data.push_str(&format!("{}\n", line));
+        }
+
+        self.write_file(&data)
     }
 
-    pub fn reset(&self) {
-        if !self.no_backup {
-            if fs::copy(&self.todo_path, &self.todo_bak).is_ok() {
-                self.remove_file()
-            } else {
-                eprintln!("Não foi possível fazer backup do arquivo");
+    // Rótulos pontilhados de cada linha, na ordem do arquivo (1, 2, 2.1, 2.1.1...)
+    pub fn index_labels(&self) -> Vec<String> {
+        let mut labels = Vec::with_capacity(self.todo.len());
+        let mut counters: Vec<usize> = Vec::new();
+        for line in &self.todo {
+            // Uma indentação que pula níveis vira filha direta da tarefa anterior
+            let depth = Entry::read_line(line).depth.min(counters.len());
+            counters.truncate(depth + 1);
+            if counters.len() == depth {
+                counters.push(0);
             }
-        } else {
-            self.remove_file();
+            counters[depth] += 1;
+            let label: Vec<String> = counters.iter().map(usize::to_string).collect();
+            labels.push(label.join("."));
         }
+        labels
     }
 
-    pub fn restore(&self) {
-        if let Err(e) = fs::copy(&self.todo_bak, &self.todo_path) {
-            eprintln!("Não foi possível restaurar o arquivo de backup: {}", e);
-            process::exit(1);
+    // Profundidade efetiva de cada linha na árvore
+    pub fn depths(&self) -> Vec<usize> {
+        self.index_labels().iter().map(|label| label.matches('.').count()).collect()
+    }
+
+    // Posições (no vetor de linhas) dos índices informados; índices inexistentes são ignorados
+    pub fn positions(&self, args: &[String]) -> Vec<usize> {
+        self.index_labels()
+            .iter()
+            .enumerate()
+            .filter(|(_, label)| args.contains(label))
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    // Regrava a lista inteira com o conteúdo informado (uma tarefa por linha)
+    fn write_file(&self, data: &str) -> Result<(), String> {
+        let lines: Vec<String> = data.lines().map(str::to_string).collect();
+        self.storage().save(&lines)
+    }
+
+    pub fn reset(&self) -> Result<(), String> {
+        let storage = self.storage();
+        if !self.no_backup {
+            storage.backup()?;
         }
+        storage.clear()
     }
 
-    pub fn sort(&self) {
-        let mut todo = String::new();
-        let mut done = String::new();
+    pub fn restore(&self) -> Result<(), String> {
+        self.storage().restore()
+    }
 
-        for line in self.todo.iter() {
-            let entry = Entry::read_line(line);
-            if entry.done {
-                done.push_str(&format!("{}\n", line));
-            } else {
-                todo.push_str(&format!("{}\n", line));
-            }
+    pub fn sort(&self) -> Result<(), String> {
+        let depths = self.depths();
+        let mut data = String::new();
+        for pos in sorted_siblings(&self.todo, &depths, 0, self.todo.len()) {
+            data.push_str(&format!("{}\n", self.todo[pos]));
         }
 
-        let newtodo = format!("{}{}", todo, done);
+        self.write_file(&data)
+    }
+
+    // Com "-r", as subtarefas acompanham o novo estado da tarefa
+    pub fn done(&self, args: &[String]) -> Result<(), String> {
+        let recursive = args.iter().any(|arg| arg == "-r");
+        let args: Vec<String> = args.iter().filter(|arg| *arg != "-r").cloned().collect();
+        if args.is_empty() {
+            return Err("Precisa de pelo menos 1 argumento".to_string());
+        }
 
-        let mut todofile = match OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.todo_path) {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("Não foi possível abrir o arquivo: {}", e);
-                process::exit(1);
+        let depths = self.depths();
+        let entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut next_id = entries.iter().filter_map(|entry| entry.id).max().unwrap_or(0) + 1;
+        let mut changed: Vec<Option<Entry>> = self.todo.iter().map(|_| None).collect();
+        // Próximas ocorrências de tarefas recorrentes, inseridas depois da linha indicada
+        let mut inserted: Vec<Vec<Entry>> = self.todo.iter().map(|_| Vec::new()).collect();
+        let mut removed = vec![false; self.todo.len()];
+        for pos in self.positions(&args) {
+            let mut entry = entries[pos].clone();
+            entry.set_done(!entry.done);
+            let end = subtree_end(&depths, pos);
+            if entry.done
+                && let Some(recur) = entry.recur.take()
+            {
+                inserted[end - 1] = self.next_occurrence(pos, end, recur, next_id)?;
+                entry.next = Some(next_id);
+                next_id += 1;
+            } else if !entry.done
+                && let Some(next) = entry.next.take()
+                && let Some(start) = entries.iter().position(|entry| entry.id == Some(next) && !entry.done)
+            {
+                // Desfazer a conclusão tira a ocorrência gerada (se ainda pendente) e devolve a regra
+                entry.recur = entries[start].recur.clone();
+                for flag in &mut removed[start..subtree_end(&depths, start)] {
+                    *flag = true;
+                }
             }
-        };
+            if recursive {
+                for (line, slot) in self.todo[pos + 1..end].iter().zip(&mut changed[pos + 1..end]) {
+                    let mut child = Entry::read_line(line);
+                    child.set_done(entry.done);
+                    *slot = Some(child);
+                }
+            }
+            changed[pos] = Some(entry);
+        }
 
-        if let Err(e) = todofile.write_all(newtodo.as_bytes()) {
-            eprintln!("Falha ao salvar o arquivo: {}", e);
-            process::exit(1);
+        let mut data = String::new();
+        for (((line, entry), next), removed) in self.todo.iter().zip(changed).zip(inserted).zip(removed) {
+            match entry {
+                _ if removed => {}
+                Some(entry) => data.push_str(&entry.file_line()),
+                None => data.push_str(&format!("{}\n", line)),
+            }
+            for entry in next {
+                data.push_str(&entry.file_line());
+            }
         }
+
+        self.write_file(&data)
     }
 
-    pub fn done(&self, args: &[String]) {
-        if args.is_empty() {
-            eprintln!("Precisa de pelo menos 1 argumento");
-            process::exit(1);
-        }
-
-        let todofile = match OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.todo_path) {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("Não foi possível abrir o arquivo: {}", e);
-                process::exit(1);
+    // Cópia pendente da tarefa recorrente em [pos, end) e suas subtarefas, com o próximo vencimento.
+    // A regra passa para a cópia, que recebe o ID `id`; a tarefa concluída fica só como histórico.
+    fn next_occurrence(&self, pos: usize, end: usize, recur: Recurrence, id: u32) -> Result<Vec<Entry>, String> {
+        let due = recur.next_due(Entry::read_line(&self.todo[pos]).due, today())?;
+        let occurrence = self.todo[pos..end]
+            .iter()
+            .enumerate()
+            .map(|(offset, line)| {
+                let mut entry = Entry::read_line(line);
+                entry.set_done(false);
+                entry.created = Some(today());
+                entry.id = None;
+                entry.next = None;
+                if offset == 0 {
+                    entry.due = Some(due);
+                    entry.recur = Some(recur.clone());
+                    entry.id = Some(id);
+                }
+                entry
+            })
+            .collect();
+        Ok(occurrence)
+    }
+
+    // Regrava o arquivo trocando só as linhas com uma nova versão da tarefa
+    fn write_changed(&self, changed: Vec<Option<Entry>>) -> Result<(), String> {
+        let mut data = String::new();
+        for (line, entry) in self.todo.iter().zip(changed) {
+            match entry {
+                Some(entry) => data.push_str(&entry.file_line()),
+                None => data.push_str(&format!("{}\n", line)),
             }
-        };
+        }
+
+        self.write_file(&data)
+    }
 
-        let mut buffer = BufWriter::new(todofile);
+    // Regrava o arquivo alterando apenas a tarefa do índice informado
+    fn update_entry(&self, index: &str, update: impl FnOnce(&mut Entry)) -> Result<(), String> {
+        let mut update = Some(update);
         let mut data = String::new();
 
-        for (pos, line) in self.todo.iter().enumerate() {
-            let mut entry = Entry::read_line(line);
-            let line = if args.contains(&(pos + 1).to_string()) {
-                entry.done = !entry.done;
+        for (line, label) in self.todo.iter().zip(self.index_labels()) {
+            let line = if index == label
+                && let Some(update) = update.take()
+            {
+                let mut entry = Entry::read_line(line);
+                update(&mut entry);
                 entry.file_line()
             } else {
                 format!("{}\n", line)
             };
             data.push_str(&line);
         }
-        
-        if let Err(e) = buffer.write_all(data.as_bytes()) {
-            eprintln!("Falha na gravação: {}", e);
-            process::exit(1);
-        }
+
+        self.write_file(&data)
     }
 
-    pub fn edit(&self, args: &[String]) {
+    pub fn edit(&self, args: &[String]) -> Result<(), String> {
         if args.len() != 2 {
-            eprintln!("A edição precisa de exatamente 2 argumentos");
-            process::exit(1);
-        }
-
-        let todofile = match OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .open(&self.todo_path) {
-            Ok(file) => file,
-            Err(e) => {
-                eprintln!("Não foi possível abrir o arquivo: {}", e);
-                process::exit(1);
+            return Err("A edição precisa de exatamente 2 argumentos".to_string());
+        }
+
+        self.update_entry(&args[0], |entry| entry.todo_entry = args[1].clone())
+    }
+
+    // Comandos que alteram a lista, pelo nome usado na linha de comando.
+    // É o ponto de entrada comum da CLI, da GUI e do daemon.
+    pub fn apply(&self, command: &str, args: &[String]) -> Result<(), String> {
+        match command {
+            "mk" => self.add(args),
+            "sub" => self.add_sub(args),
+            "edit" => self.edit(args),
+            "rm" => self.remove(args),
+            "done" => self.done(args),
+            "status" => self.status(args),
+            "due" => self.due(args),
+            "pri" => self.priority(args),
+            "rec" => self.recur(args),
+            "block" => self.block(args),
+            "unblock" => self.unblock(args),
+            "note" => match args {
+                [index, note @ ..] => self.set_note(index, &note.join(" ")),
+                [] => Err("Uso: todo note [ÍNDICE] [ANOTAÇÃO]".to_string()),
+            },
+            "sort" => self.sort(),
+            "reset" => self.reset(),
+            "restore" => self.restore(),
+            _ => Err(format!("Comando desconhecido: {}", command)),
+        }
+    }
+
+    // Texto da tarefa, sem os metadados
+    pub fn entry_text(&self, index: &str) -> Result<String, String> {
+        let pos = self.position(index)?;
+        Ok(Entry::read_line(&self.todo[pos]).todo_entry)
+    }
+
+    // Confere o texto de uma lista inteira (ex.: editada no $EDITOR) e devolve as linhas.
+    // Linhas em branco são descartadas; os erros trazem o número da linha.
+    pub fn parse_lines(text: &str) -> Result<Vec<String>, Vec<String>> {
+        let mut lines = Vec::new();
+        let mut errors = Vec::new();
+        let mut previous_depth: Option<usize> = None;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                continue;
             }
-        };
+            let number = number + 1;
+            let indent = line.len() - line.trim_start_matches(' ').len();
+            let rest = &line[indent..];
+            if rest.starts_with('\t') {
+                errors.push(format!("Linha {}: use espaços, não tabulações, para indentar", number));
+                continue;
+            }
+            if indent % INDENT.len() != 0 {
+                errors.push(format!("Linha {}: a indentação deve ter múltiplos de {} espaços", number, INDENT.len()));
+                continue;
+            }
+            let depth = indent / INDENT.len();
+            if depth > previous_depth.map_or(0, |previous| previous + 1) {
+                errors.push(format!("Linha {}: a subtarefa pula um nível de indentação", number));
+                continue;
+            }
+            let text = rest.strip_prefix("[ ] ").or_else(|| rest.strip_prefix("[*] "));
+            if text.is_none_or(|text| text.trim().is_empty()) {
+                errors.push(format!("Linha {}: esperado \"[ ] tarefa\" ou \"[*] tarefa\"", number));
+                continue;
+            }
+            previous_depth = Some(depth);
+            lines.push(line.to_string());
+        }
+        if errors.is_empty() { Ok(lines) } else { Err(errors) }
+    }
 
-        let mut buffer = BufWriter::new(todofile);
+    // Substitui a lista inteira pelas linhas informadas (já conferidas por `parse_lines`)
+    pub fn replace_lines(&self, lines: &[String]) -> Result<(), String> {
+        let data: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        self.write_file(&data)
+    }
 
-        for (pos, line) in self.todo.iter().enumerate() {
-            let line = if args[0] == (pos + 1).to_string() {
-                let mut entry = Entry::read_line(line);
-                entry.todo_entry = args[1].clone();
-                entry.file_line()
-            } else {
-                format!("{}\n", line)
+    // Comentário de código de onde a tarefa veio, se houver
+    pub fn source(&self, index: &str) -> Result<Option<SourceRef>, String> {
+        let pos = self.position(index)?;
+        Ok(Entry::read_line(&self.todo[pos]).source)
+    }
+
+    // Anotação atual da tarefa, se houver
+    pub fn note(&self, index: &str) -> Result<Option<String>, String> {
+        let pos = self.position(index)?;
+        Ok(Entry::read_line(&self.todo[pos]).note)
+    }
+
+    // Define a anotação; texto vazio remove
+    pub fn set_note(&self, index: &str, note: &str) -> Result<(), String> {
+        self.position(index)?;
+        let note = note.trim_end();
+        self.update_entry(index, |entry| {
+            entry.note = if note.is_empty() { None } else { Some(note.to_string()) }
+        })
+    }
+
+    // Define (ou remove, sem o segundo argumento) o status usado nas colunas do quadro
+    pub fn status(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() || args.len() > 2 {
+            return Err("Uso: todo status [ÍNDICE] [STATUS]".to_string());
+        }
+        let status = args.get(1).map(|status| status.trim()).filter(|status| !status.is_empty());
+        if status.is_some_and(|status| status.contains(char::is_whitespace)) {
+            return Err("O status não pode conter espaços".to_string());
+        }
+
+        self.update_entry(&args[0], |entry| entry.status = status.map(str::to_string))
+    }
+
+    // Define (ou remove, sem o segundo argumento) a data de vencimento
+    pub fn due(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() || args.len() > 2 {
+            return Err("Uso: todo due [ÍNDICE] [AAAA-MM-DD]".to_string());
+        }
+        let due = args.get(1).map(|date| parse_date(date)).transpose()?;
+
+        self.update_entry(&args[0], |entry| entry.due = due)
+    }
+
+    // Define (ou remove, sem o segundo argumento) a prioridade, de A a Z
+    pub fn priority(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() || args.len() > 2 {
+            return Err("Uso: todo pri [ÍNDICE] [A-Z]".to_string());
+        }
+        let priority = args.get(1).map(|priority| parse_priority(priority)).transpose()?;
+
+        self.update_entry(&args[0], |entry| entry.priority = priority)
+    }
+
+    // Define (ou remove, sem o segundo argumento) a regra de repetição
+    pub fn recur(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() || args.len() > 2 {
+            return Err("Uso: todo rec [ÍNDICE] [daily/weekly:mon,thu/monthly:15/after:3]".to_string());
+        }
+        let recur = args.get(1).map(|rule| Recurrence::parse(rule)).transpose()?;
+
+        self.update_entry(&args[0], |entry| entry.recur = recur)
+    }
+
+    // Para cada linha, os índices das tarefas pendentes que a bloqueiam
+    pub fn blockers(&self) -> Vec<Vec<String>> {
+        let entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let labels = self.index_labels();
+        let pending: HashMap<u32, &str> = entries
+            .iter()
+            .zip(&labels)
+            .filter(|(entry, _)| !entry.done)
+            .filter_map(|(entry, label)| Some((entry.id?, label.as_str())))
+            .collect();
+
+        entries
+            .iter()
+            .map(|entry| {
+                if entry.done {
+                    return Vec::new();
+                }
+                entry
+                    .deps
+                    .iter()
+                    .filter_map(|dep| pending.get(dep).map(|label| label.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Marca a primeira tarefa como bloqueada pelas demais, atribuindo IDs quando preciso
+    pub fn block(&self, args: &[String]) -> Result<(), String> {
+        if args.len() < 2 {
+            return Err("Uso: todo block [ÍNDICE] [ÍNDICE/S QUE BLOQUEIAM]".to_string());
+        }
+        let target = self.position(&args[0])?;
+        let mut entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut changed = vec![false; entries.len()];
+        let mut next_id = entries.iter().filter_map(|entry| entry.id).max().unwrap_or(0) + 1;
+
+        for index in &args[1..] {
+            let blocker = self.position(index)?;
+            if blocker == target {
+                return Err(format!("A tarefa {} não pode bloquear a si mesma", index));
+            }
+            if depends_on(&entries, blocker, target) {
+                return Err(format!("Dependência circular: {} já depende de {}", index, args[0]));
+            }
+
+            let id = match entries[blocker].id {
+                Some(id) => id,
+                None => {
+                    let id = next_id;
+                    next_id += 1;
+                    entries[blocker].id = Some(id);
+                    changed[blocker] = true;
+                    id
+                }
             };
-            if let Err(e) = buffer.write_all(line.as_bytes()) {
-                eprintln!("Falha na gravação: {}", e);
-                process::exit(1);
+            if !entries[target].deps.contains(&id) {
+                entries[target].deps.push(id);
+                changed[target] = true;
+            }
+        }
+
+        let changed = entries
+            .into_iter()
+            .zip(changed)
+            .map(|(entry, changed)| changed.then_some(entry))
+            .collect();
+        self.write_changed(changed)
+    }
+
+    // Remove os bloqueios informados, ou todos se só o índice for passado
+    pub fn unblock(&self, args: &[String]) -> Result<(), String> {
+        if args.is_empty() {
+            return Err("Uso: todo unblock [ÍNDICE] [ÍNDICE/S QUE BLOQUEIAM]".to_string());
+        }
+        let mut ids = Vec::new();
+        for index in &args[1..] {
+            let blocker = Entry::read_line(&self.todo[self.position(index)?]);
+            ids.extend(blocker.id);
+        }
+
+        self.update_entry(&args[0], |entry| {
+            if args.len() == 1 {
+                entry.deps.clear();
+            } else {
+                entry.deps.retain(|dep| !ids.contains(dep));
             }
+        })
+    }
+
+    // Tarefas pendentes e desbloqueadas, por prioridade e vencimento
+    pub fn next(&self) {
+        let labels = self.index_labels();
+        let mut data = String::new();
+        for (pos, mut entry) in self.actionable() {
+            entry.depth = 0;
+            data.push_str(&entry.list_line(&labels[pos], &[]));
         }
+
+        if let Err(e) = io::stdout().write_all(data.as_bytes()) {
+            eprintln!("Falha na gravação: {}", e);
+        }
+    }
+
+    // Posição e entrada das tarefas que `next` mostra, já na ordem
+    fn actionable(&self) -> Vec<(usize, Entry)> {
+        let blockers = self.blockers();
+        let mut actionable: Vec<(usize, Entry)> = self
+            .todo
+            .iter()
+            .map(|line| Entry::read_line(line))
+            .enumerate()
+            .filter(|(pos, entry)| !entry.done && blockers[*pos].is_empty())
+            .collect();
+        // Sem prioridade ou sem data vai para o fim; empates mantêm a ordem do arquivo
+        actionable.sort_by_key(|(pos, entry)| {
+            (entry.priority.is_none(), entry.priority, entry.due.is_none(), entry.due, *pos)
+        });
+        actionable
     }
+
+    fn position(&self, index: &str) -> Result<usize, String> {
+        self.positions(&[index.to_string()])
+            .first()
+            .copied()
+            .ok_or(format!("Tarefa {} não encontrada", index))
+    }
+}
+
+// Palavra do texto que seria lida como metadado (ex.: "status:ok"), com ou sem barras na frente
+fn looks_like_metadata(word: &str) -> bool {
+    word.trim_start_matches('\\')
+        .split_once(':')
+        .is_some_and(|(key, value)| !value.is_empty() && METADATA_KEYS.contains(&key))
+}
+
+// Ao gravar, uma barra invertida na frente impede que a palavra vire metadado
+fn escape_text(text: &str) -> String {
+    let words: Vec<String> = text
+        .split(' ')
+        .map(|word| if looks_like_metadata(word) { format!("\\{}", word) } else { word.to_string() })
+        .collect();
+    words.join(" ")
 }
 
-const TODO_HELP: &str = "Uso: todo [COMANDO] [ARGUMENTOS]
+fn unescape_text(text: &str) -> String {
+    let words: Vec<&str> = text
+        .split(' ')
+        .map(|word| match word.strip_prefix('\\') {
+            Some(rest) if looks_like_metadata(word) => rest,
+            _ => word,
+        })
+        .collect();
+    words.join(" ")
+}
+
+// Verifica se a tarefa em `from` depende (direta ou indiretamente) da tarefa em `to`
+fn depends_on(entries: &[Entry], from: usize, to: usize) -> bool {
+    let Some(target_id) = entries[to].id else {
+        return false;
+    };
+    let by_id: HashMap<u32, usize> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(pos, entry)| Some((entry.id?, pos)))
+        .collect();
+
+    let mut stack = vec![from];
+    let mut seen = vec![false; entries.len()];
+    while let Some(pos) = stack.pop() {
+        if std::mem::replace(&mut seen[pos], true) {
+            continue;
+        }
+        for dep in &entries[pos].deps {
+            if *dep == target_id {
+                return true;
+            }
+            if let Some(next) = by_id.get(dep) {
+                stack.push(*next);
+            }
+        }
+    }
+    false
+}
+
+// Fim (exclusivo) do bloco formado pela tarefa em `pos` e suas subtarefas
+pub fn subtree_end(depths: &[usize], pos: usize) -> usize {
+    depths[pos + 1..]
+        .iter()
+        .position(|depth| *depth <= depths[pos])
+        .map_or(depths.len(), |offset| pos + 1 + offset)
+}
+
+// Ordena as tarefas irmãs em [start, end) deixando as concluídas no fim, recursivamente,
+// sem separar nenhuma tarefa das suas subtarefas
+fn sorted_siblings(lines: &[String], depths: &[usize], start: usize, end: usize) -> Vec<usize> {
+    let mut todo = Vec::new();
+    let mut done = Vec::new();
+
+    let mut pos = start;
+    while pos < end {
+        let block_end = subtree_end(depths, pos).min(end);
+        let mut block = vec![pos];
+        block.extend(sorted_siblings(lines, depths, pos + 1, block_end));
+        if Entry::read_line(&lines[pos]).done {
+            done.extend(block);
+        } else {
+            todo.extend(block);
+        }
+        pos = block_end;
+    }
+
+    todo.extend(done);
+    todo
+}
+
+const TODO_HELP: &str = "Uso: todo [--list LISTA] [--global] [COMANDO] [ARGUMENTOS]
 Todo é um organizador de tarefas super rápido e simples escrito em Rust
 
 Exemplo: todo list
+Exemplo: todo --list trabalho mk \"Enviar relatório\"
+
+Um arquivo .todo no diretório atual (ou num diretório pai) é usado no lugar
+do TODO principal; --global força o TODO principal.
 
 Comandos disponíveis:
     - mk [TAREFA/S]
-    - edit [ÍNDICE] [TAREFA/S EDITADA/S]
+    - edit [ÍNDICE] [TAREFA/S EDITADA/S] (sem o texto, abre o $EDITOR)
+    - edit --all
     - list
-    - done [ÍNDICE]
+    - sub [ÍNDICE] [SUBTAREFA/S]
+    - done [-r] [ÍNDICE]
     - rm [ÍNDICE]
     - reset
     - restore
     - sort
+    - next
     - raw [todo/done]
+    - status [ÍNDICE] [STATUS]
+    - due [ÍNDICE] [AAAA-MM-DD/hoje/amanhã]
+    - pri [ÍNDICE] [A-Z]
+    - rec [ÍNDICE] [daily/weekly:mon,thu/monthly:15/after:3]
+    - block [ÍNDICE] [ÍNDICE/S QUE BLOQUEIAM]
+    - unblock [ÍNDICE] [ÍNDICE/S QUE BLOQUEIAM]
+    - mv [ÍNDICE/S] [LISTA]
+    - lists [mk NOME | mv ANTIGO NOVO | rm NOME]
+    - where
+    - archive [DIAS]
+    - log [--from AAAA-MM-DD] [--to AAAA-MM-DD] [TEXTO]
+    - stats [--json]
+    - note [ÍNDICE] [ANOTAÇÃO] (sem anotação, abre o $EDITOR)
+    - scan [CAMINHO]
+    - serve [--port PORTA] [--host ENDEREÇO] [--token TOKEN]
+    - ical export [ARQUIVO.ics]
+    - ical import [ARQUIVO.ics]
+    - import [taskwarrior/todoist/github] [ARQUIVO] [--yes]
+    - sync [init [REMOTO]]
+    - merge-driver %O %A %B
+    - migrate [text/sqlite]
+    - encrypt
+    - decrypt
+    - unlock
+    - lock
+    - history [N]
+    - remind [--all] [--lead 1d,1h,0] [--at HH:MM]
+    - daemon
 ";
 
 pub fn help() {
     println!("{}", TODO_HELP);
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    // Lista num diretório temporário, sem backup (o diretório some junto com o TempDir)
+    pub(crate) fn temp_list(lines: &[&str]) -> (TempDir, Todo) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("TODO");
+        let data: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        fs::write(&path, data).unwrap();
+        let todo = Todo {
+            todo: Vec::new(),
+            todo_bak: dir.path().join("TODO.bak"),
+            todo_path: path,
+            no_backup: true,
+            project: false,
+        };
+        (dir, todo.load().unwrap())
+    }
+
+    pub(crate) fn reload(todo: &Todo) -> Todo {
+        todo.reload().unwrap()
+    }
+
+    pub(crate) fn texts(todo: &Todo) -> Vec<String> {
+        todo.todo.iter().map(|line| Entry::read_line(line).todo_entry).collect()
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    const TREE: &[&str] = &[
+        "[ ] casa",
+        "  [ ] cozinha",
+        "    [ ] pia",
+        "  [ ] sala",
+        "[ ] trabalho",
+        "  [ ] relatório",
+    ];
+
+    #[test]
+    fn index_labels_number_nested_tasks() {
+        let (_dir, todo) = temp_list(TREE);
+        assert_eq!(todo.index_labels(), ["1", "1.1", "1.1.1", "1.2", "2", "2.1"]);
+        assert_eq!(todo.depths(), [0, 1, 2, 1, 0, 1]);
+    }
+
+    #[test]
+    fn index_labels_attach_skipped_levels_to_the_previous_task() {
+        let (_dir, todo) = temp_list(&["[ ] a", "      [ ] fundo", "  [ ] b"]);
+        assert_eq!(todo.index_labels(), ["1", "1.1", "1.2"]);
+    }
+
+    #[test]
+    fn positions_ignore_invalid_indices() {
+        let (_dir, todo) = temp_list(TREE);
+        assert_eq!(todo.positions(&args(&["1.2", "2.1"])), [3, 5]);
+        assert!(todo.positions(&args(&["3", "1.3", "0", "x", "1."])).is_empty());
+        assert!(todo.entry_text("1.1.2").is_err());
+        assert_eq!(todo.entry_text("1.1.1").unwrap(), "pia");
+    }
+
+    #[test]
+    fn subtree_end_covers_all_descendants() {
+        let depths = [0, 1, 2, 1, 0, 1];
+        assert_eq!(subtree_end(&depths, 0), 4);
+        assert_eq!(subtree_end(&depths, 1), 3);
+        assert_eq!(subtree_end(&depths, 2), 3);
+        assert_eq!(subtree_end(&depths, 4), 6);
+        assert_eq!(subtree_end(&depths, 5), 6);
+    }
+
+    #[test]
+    fn remove_takes_the_children_along() {
+        let (_dir, todo) = temp_list(TREE);
+        todo.remove(&args(&["1.1"])).unwrap();
+        let todo = reload(&todo);
+        assert_eq!(texts(&todo), ["casa", "sala", "trabalho", "relatório"]);
+        assert_eq!(todo.index_labels(), ["1", "1.1", "2", "2.1"]);
+
+        todo.remove(&args(&["2", "9"])).unwrap();
+        assert_eq!(texts(&reload(&todo)), ["casa", "sala"]);
+    }
+
+    #[test]
+    fn add_sub_appends_after_existing_children() {
+        let (_dir, todo) = temp_list(TREE);
+        todo.add_sub(&args(&["1", "quintal"])).unwrap();
+        let todo = reload(&todo);
+        assert_eq!(texts(&todo)[4], "quintal");
+        assert_eq!(todo.index_labels()[4], "1.3");
+        assert!(todo.add_sub(&args(&["7", "nada"])).is_err());
+    }
+
+    #[test]
+    fn metadata_is_read_only_from_the_end_of_the_line() {
+        let entry = Entry::read_line("[ ] ver status:ok do deploy due:2025-03-01 id:4 dep:1,2");
+        assert_eq!(entry.todo_entry, "ver status:ok do deploy");
+        assert_eq!(entry.status, None);
+        assert_eq!(entry.due, NaiveDate::from_ymd_opt(2025, 3, 1));
+        assert_eq!(entry.id, Some(4));
+        assert_eq!(entry.deps, [1, 2]);
+
+        let entry = Entry::read_line("  [*] texto  com  espaços  ");
+        assert!(entry.done);
+        assert_eq!(entry.depth, 1);
+        assert_eq!(entry.todo_entry, "texto  com  espaços  ");
+    }
+
+    #[test]
+    fn reserved_words_in_the_text_survive_a_round_trip() {
+        for text in ["conferir status:ok", "id:7", "due:2025-01-01", "já \\note:x escapado", "hora 10:30 e url:x"] {
+            let mut entry = Entry::new(text.to_string(), false);
+            entry.status = Some("doing".to_string());
+            let read = Entry::read_line(entry.file_line().trim_end());
+            assert_eq!(read.todo_entry, text);
+            assert_eq!(read.status.as_deref(), Some("doing"));
+            assert_eq!(read.id, None);
+        }
+        assert_eq!(Entry::new("ver status:ok".to_string(), false).file_line(), "[ ] ver \\status:ok\n");
+    }
+
+    #[test]
+    fn notes_survive_a_round_trip() {
+        let notes = [
+            "linha um\nlinha dois\n\nlinha quatro",
+            "C:\\pasta\\arquivo e \\note:x",
+            "status:doing due:2025-01-01 id:3 dep:1,2 note:outra",
+            "100% feito, %20 literal e %ZZ",
+            "\ttabulação  e  espaços ",
+            "acentuação: ação, café ✎",
+        ];
+        for note in notes {
+            let encoded = encode_note(note);
+            assert!(!encoded.contains([' ', '\n', '\t']), "{:?}", encoded);
+            assert_eq!(decode_note(&encoded).as_deref(), Some(note));
+
+            let mut entry = Entry::new("tarefa".to_string(), false);
+            entry.note = Some(note.to_string());
+            let read = Entry::read_line(entry.file_line().trim_end());
+            assert_eq!(read.note.as_deref(), Some(note));
+            assert_eq!(read.todo_entry, "tarefa");
+            assert_eq!((read.status, read.due, read.id), (None, None, None));
+            assert!(read.deps.is_empty());
+        }
+        // Quebras de linha do Windows viram '\n'; sequências inválidas não são anotação
+        assert_eq!(decode_note(&encode_note("a\r\nb")).as_deref(), Some("a\nb"));
+        for value in ["%", "%4", "%GG", "%FF"] {
+            assert_eq!(decode_note(value), None);
+        }
+    }
+
+    #[test]
+    fn set_note_is_saved_in_the_list() {
+        let (_dir, todo) = temp_list(&["[ ] a"]);
+        todo.set_note("1", "primeira linha\nsegunda: \\x\n").unwrap();
+        let todo = reload(&todo);
+        assert_eq!(todo.todo.len(), 1);
+        assert_eq!(todo.note("1").unwrap().as_deref(), Some("primeira linha\nsegunda: \\x"));
+        todo.set_note("1", "  ").unwrap();
+        assert_eq!(reload(&todo).note("1").unwrap(), None);
+    }
+
+    #[test]
+    fn block_refuses_cycles() {
+        let (_dir, todo) = temp_list(&["[ ] a", "[ ] b", "[ ] c"]);
+        todo.block(&args(&["1", "2"])).unwrap();
+        let todo = reload(&todo);
+        todo.block(&args(&["2", "3"])).unwrap();
+        let todo = reload(&todo);
+        assert!(todo.block(&args(&["3", "1"])).unwrap_err().contains("circular"));
+        assert!(todo.block(&args(&["2", "2"])).is_err());
+        assert!(todo.block(&args(&["1", "9"])).is_err());
+    }
+
+    #[test]
+    fn blockers_list_only_pending_dependencies() {
+        let (_dir, todo) = temp_list(&["[ ] a dep:1,2", "[ ] b id:1", "[*] c id:2", "[*] d dep:1", "  [ ] e dep:1"]);
+        assert_eq!(
+            todo.blockers(),
+            [vec!["2".to_string()], vec![], vec![], vec![], vec!["2".to_string()]]
+        );
+    }
+
+    #[test]
+    fn next_orders_by_priority_then_due_date() {
+        let (_dir, todo) = temp_list(&[
+            "[ ] sem nada",
+            "[ ] depois due:2025-05-01",
+            "[ ] urgente pri:A",
+            "[ ] antes due:2025-04-01",
+            "[ ] bloqueada pri:A dep:9",
+            "[*] feita pri:A",
+            "[ ] b pri:B due:2025-01-01",
+            "[ ] bloqueadora id:9",
+        ]);
+        let order: Vec<String> = todo.actionable().into_iter().map(|(_, entry)| entry.todo_entry).collect();
+        assert_eq!(order, ["urgente", "b", "antes", "depois", "sem nada", "bloqueadora"]);
+    }
+
+    #[test]
+    fn done_on_a_recurring_task_creates_the_next_occurrence() {
+        let (_dir, todo) = temp_list(&["[ ] regar due:2025-03-01 rec:daily", "  [ ] samambaia", "[ ] outra id:4"]);
+        todo.done(&args(&["1"])).unwrap();
+        let todo = reload(&todo);
+        let entries: Vec<Entry> = todo.todo.iter().map(|line| Entry::read_line(line)).collect();
+        assert_eq!(texts(&todo), ["regar", "samambaia", "regar", "samambaia", "outra"]);
+        assert!(entries[0].done && entries[0].recur.is_none());
+        assert_eq!(entries[0].next, Some(5));
+        assert!(!entries[2].done && entries[2].recur.is_some());
+        assert_eq!(entries[2].id, Some(5));
+        assert_eq!(entries[2].due, Recurrence::Daily.next_due(NaiveDate::from_ymd_opt(2025, 3, 1), today()).ok());
+        assert_eq!(entries[3].depth, 1);
+    }
+
+    #[test]
+    fn undoing_a_recurring_task_removes_the_pending_occurrence() {
+        let (_dir, todo) = temp_list(&["[ ] regar due:2025-03-01 rec:daily", "  [ ] samambaia", "[ ] outra"]);
+        let original = todo.todo.clone();
+        todo.done(&args(&["1"])).unwrap();
+        let todo = reload(&todo);
+        todo.done(&args(&["1"])).unwrap();
+        let todo = reload(&todo);
+        assert_eq!(texts(&todo), ["regar", "samambaia", "outra"]);
+        assert_eq!(todo.todo, original);
+    }
+
+    #[test]
+    fn undoing_keeps_an_occurrence_that_was_already_completed() {
+        let (_dir, todo) = temp_list(&["[ ] regar due:2025-03-01 rec:daily"]);
+        todo.done(&args(&["1"])).unwrap();
+        let todo = reload(&todo);
+        todo.done(&args(&["2"])).unwrap();
+        let todo = reload(&todo);
+        todo.done(&args(&["1"])).unwrap();
+        let todo = reload(&todo);
+        let entries: Vec<Entry> = todo.todo.iter().map(|line| Entry::read_line(line)).collect();
+        assert_eq!(texts(&todo), ["regar", "regar", "regar"]);
+        assert!(!entries[0].done && entries[0].next.is_none() && entries[0].recur.is_none());
+        assert!(entries[1].done);
+    }
+
+    #[test]
+    fn parse_lines_accepts_a_valid_tree() {
+        let text = "[ ] a\n  [*] b\n\n    [ ] c\n[ ] d   \n";
+        assert_eq!(Todo::parse_lines(text).unwrap(), ["[ ] a", "  [*] b", "    [ ] c", "[ ] d"]);
+    }
+
+    #[test]
+    fn parse_lines_reports_every_bad_line() {
+        let text = "  [ ] sem pai\n[ ] a\n   [ ] ímpar\n\t[ ] tab\n    [ ] pulo\nsem marcador\n[ ]  \n";
+        let errors = Todo::parse_lines(text).unwrap_err();
+        assert_eq!(errors.len(), 6);
+        for (error, line) in errors.iter().zip([1, 3, 4, 5, 6, 7]) {
+            assert!(error.starts_with(&format!("Linha {}:", line)), "{}", error);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Todo;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+// Nome reservado para o arquivo TODO principal (TODO_PATH, ~/TODO ou ~/.todo)
+pub const DEFAULT_LIST: &str = "principal";
+
+const LIST_EXTENSION: &str = "todo";
+
+// Diretório das listas nomeadas
+pub fn data_dir() -> Result<PathBuf, String> {
+    match env::var("TODO_DATA_DIR") {
+        Ok(path) => Ok(PathBuf::from(path)),
+        Err(_) => dirs::data_dir()
+            .map(|dir| dir.join("todo-todo").join("lists"))
+            .ok_or("Não foi possível determinar o diretório de dados".to_string()),
+    }
+}
+
+// "principal" é a lista padrão; qualquer outro nome é uma lista nomeada
+pub fn list_name(name: &str) -> Option<&str> {
+    if name == DEFAULT_LIST { None } else { Some(name) }
+}
+
+fn validate(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Nome de lista inválido '{}': use letras, números, '-' ou '_'", name));
+    }
+    if name == DEFAULT_LIST {
+        return Err(format!("O nome '{}' é reservado para a lista padrão", DEFAULT_LIST));
+    }
+    Ok(())
+}
+
+pub fn list_path(name: &str) -> Result<PathBuf, String> {
+    validate(name)?;
+    Ok(data_dir()?.join(format!("{}.{}", name, LIST_EXTENSION)))
+}
+
+pub fn exists(name: &str) -> Result<bool, String> {
+    Ok(list_path(name)?.exists())
+}
+
+// Listas nomeadas existentes, em ordem alfabética
+pub fn names() -> Result<Vec<String>, String> {
+    let dir = data_dir()?;
+    let Ok(read_dir) = fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+
+    let mut names: Vec<String> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == LIST_EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| validate(name).is_ok())
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+pub fn create(name: &str) -> Result<(), String> {
+    let path = list_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Não foi possível criar {}: {}", dir.display(), e))?;
+    }
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .map(|_| ())
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::AlreadyExists => format!("A lista '{}' já existe", name),
+            _ => format!("Não foi possível criar a lista '{}': {}", name, e),
+        })
+}
+
+pub fn rename(old: &str, new: &str) -> Result<(), String> {
+    let from = list_path(old)?;
+    let to = list_path(new)?;
+    if !from.exists() {
+        return Err(format!("A lista '{}' não existe", old));
+    }
+    if to.exists() {
+        return Err(format!("A lista '{}' já existe", new));
+    }
+    fs::rename(&from, &to).map_err(|e| format!("Não foi possível renomear a lista: {}", e))
+}
+
+// Apaga a lista guardando um backup, como o reset
+pub fn delete(name: &str) -> Result<(), String> {
+    if !exists(name)? {
+        return Err(format!("A lista '{}' não existe", name));
+    }
+    Todo::open_list(Some(name))?.reset()
+}
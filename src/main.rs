@@ -1,15 +1,20 @@
 use std::env;
 mod cli;
 mod gui;
-mod libs;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let list = cli::take_list(&mut args);
+    todo_todo::project::set_global(cli::take_global(&mut args));
 
-    if args.len() == 1 || args.contains(&"--gui".to_string()) {
-        let todo = libs::Todo::new().expect("Não foi possível criar o TODO :(");
-        gui::app::run(todo);
+    if args.len() == 1 || args[1] == "--gui" {
+        // Uma lista criptografada sem chave abre a GUI pedindo a senha
+        let todo = match todo_todo::Todo::locate(list.as_deref().and_then(todo_todo::lists::list_name)) {
+            Ok(todo) if todo.is_locked() => todo,
+            _ => cli::open(list.as_deref()),
+        };
+        gui::app::run(todo, list);
     } else {
-        cli::run(args);
+        cli::run(args, list);
     }
 }
@@ -0,0 +1,401 @@
+use crate::Entry;
+use std::collections::{HashMap, HashSet};
+
+// Mescla em três vias de duas versões da lista que partiram de uma base comum.
+// Cada tarefa é comparada campo a campo: um lado marcar como concluída e o outro
+// editar o texto resulta numa tarefa com as duas alterações.
+
+// Textos com pelo menos essa semelhança (0 a 1) são considerados a mesma tarefa editada
+const SIMILARITY_THRESHOLD: f64 = 0.5;
+
+// Tamanho dos marcadores de conflito, como os do git
+const MARKER_SIZE: usize = 7;
+
+pub enum Item {
+    Entry(Entry),
+    // Os dois lados mudaram o mesmo campo de formas diferentes, ou um lado alterou uma
+    // tarefa que o outro removeu (None)
+    Conflict { ours: Option<Entry>, theirs: Option<Entry> },
+}
+
+pub struct Merge {
+    pub items: Vec<Item>,
+    pub conflicts: usize,
+}
+
+fn file_line(entry: &Entry) -> String {
+    entry.file_line().trim_end_matches('\n').to_string()
+}
+
+impl Merge {
+    // Conflitos resolvidos com a versão de `ours`
+    pub fn lines(&self) -> Vec<String> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Entry(entry) => Some(file_line(entry)),
+                Item::Conflict { ours, .. } => ours.as_ref().map(file_line),
+            })
+            .collect()
+    }
+
+    // Conflitos entre marcadores <<<<<<< / ======= / >>>>>>>, como o git faz
+    pub fn marked_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for item in &self.items {
+            match item {
+                Item::Entry(entry) => lines.push(file_line(entry)),
+                Item::Conflict { ours, theirs } => {
+                    lines.push(format!("{} ours", "<".repeat(MARKER_SIZE)));
+                    lines.extend(ours.as_ref().map(file_line));
+                    lines.push("=".repeat(MARKER_SIZE));
+                    lines.extend(theirs.as_ref().map(file_line));
+                    lines.push(format!("{} theirs", ">".repeat(MARKER_SIZE)));
+                }
+            }
+        }
+        lines
+    }
+}
+
+fn parse(lines: &[String]) -> Vec<Entry> {
+    lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Entry::read_line(line))
+        .collect()
+}
+
+// Coeficiente de Dice dos pares de caracteres: 1 para textos iguais, 0 sem nada em comum
+fn similarity(a: &str, b: &str) -> f64 {
+    let bigrams = |text: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = text.to_lowercase().chars().filter(|c| !c.is_whitespace()).collect();
+        chars.windows(2).map(|pair| (pair[0], pair[1])).collect()
+    };
+    let (a, mut b) = (bigrams(a), bigrams(b));
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 0.0;
+    }
+    let mut common = 0;
+    for bigram in &a {
+        if let Some(pos) = b.iter().position(|other| other == bigram) {
+            b.swap_remove(pos);
+            common += 1;
+        }
+    }
+    2.0 * common as f64 / total as f64
+}
+
+// Para cada tarefa da base, a posição correspondente em `other` (None = removida)
+fn pair_entries(base: &[Entry], other: &[Entry]) -> Vec<Option<usize>> {
+    let mut pairs: Vec<Option<usize>> = vec![None; base.len()];
+    let mut used = vec![false; other.len()];
+
+    // Primeiro pelo id (tarefas usadas em `block`), depois pelo texto igual
+    for (i, entry) in base.iter().enumerate() {
+        if let Some(id) = entry.id
+            && let Some(j) = other.iter().position(|candidate| candidate.id == Some(id))
+        {
+            pairs[i] = Some(j);
+            used[j] = true;
+        }
+    }
+    for (i, entry) in base.iter().enumerate() {
+        if pairs[i].is_some() {
+            continue;
+        }
+        let j = (0..other.len()).find(|&j| !used[j] && other[j].todo_entry == entry.todo_entry);
+        if let Some(j) = j {
+            pairs[i] = Some(j);
+            used[j] = true;
+        }
+    }
+
+    // O que sobrou é casado pelo texto mais parecido: a tarefa foi editada
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (i, entry) in base.iter().enumerate().filter(|(i, _)| pairs[*i].is_none()) {
+        for (j, candidate) in other.iter().enumerate().filter(|(j, _)| !used[*j]) {
+            let score = similarity(&entry.todo_entry, &candidate.todo_entry);
+            if score >= SIMILARITY_THRESHOLD {
+                candidates.push((score, i, j));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    for (_, i, j) in candidates {
+        if pairs[i].is_none() && !used[j] {
+            pairs[i] = Some(j);
+            used[j] = true;
+        }
+    }
+    pairs
+}
+
+// Ids criados dos dois lados desde a base (ex.: `block` nas duas máquinas) podem coincidir em
+// tarefas diferentes: as de `theirs` ganham um id novo, e as referências a ele (dep:, next:) acompanham
+fn renumber_ids(
+    base: &[Entry],
+    ours: &[Entry],
+    theirs: &mut [Entry],
+    ours_pairs: &[Option<usize>],
+    theirs_pairs: &[Option<usize>],
+) {
+    let base_ids: HashSet<u32> = base.iter().filter_map(|entry| entry.id).collect();
+    let mut next_id = base
+        .iter()
+        .chain(ours)
+        .chain(theirs.iter())
+        .filter_map(|entry| entry.id)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut renamed: HashMap<u32, u32> = HashMap::new();
+    for (k, entry) in theirs.iter().enumerate() {
+        let Some(id) = entry.id.filter(|id| !base_ids.contains(id)) else {
+            continue;
+        };
+        let Some(j) = ours.iter().position(|other| other.id == Some(id)) else {
+            continue;
+        };
+        // A mesma tarefa da base ganhou o mesmo id dos dois lados: não é colisão
+        let theirs_base = theirs_pairs.iter().position(|pair| *pair == Some(k));
+        if theirs_base.is_some() && theirs_base == ours_pairs.iter().position(|pair| *pair == Some(j)) {
+            continue;
+        }
+        renamed.insert(id, next_id);
+        next_id += 1;
+    }
+    if renamed.is_empty() {
+        return;
+    }
+    let rename = |id: &mut u32| {
+        if let Some(new) = renamed.get(id) {
+            *id = *new;
+        }
+    };
+    for entry in theirs.iter_mut() {
+        entry.id.iter_mut().for_each(rename);
+        entry.next.iter_mut().for_each(rename);
+        entry.deps.iter_mut().for_each(rename);
+    }
+}
+
+// Fica com o lado que mudou. Se os dois mudaram de formas diferentes, devolve as duas
+// versões (a de `ours` e a de `theirs`) e marca o conflito.
+fn pick<T: PartialEq + Clone>(base: &T, ours: &T, theirs: &T, conflict: &mut bool) -> (T, T) {
+    if ours == base || ours == theirs {
+        (theirs.clone(), theirs.clone())
+    } else if theirs == base {
+        (ours.clone(), ours.clone())
+    } else {
+        *conflict = true;
+        (ours.clone(), theirs.clone())
+    }
+}
+
+fn merge_entry(base: &Entry, ours: &Entry, theirs: &Entry) -> Item {
+    let mut conflict = false;
+    let (mut left, mut right) = (ours.clone(), ours.clone());
+    macro_rules! merge_fields {
+        ($($field:ident),+) => {
+            $(
+                (left.$field, right.$field) = pick(&base.$field, &ours.$field, &theirs.$field, &mut conflict);
+            )+
+        };
+    }
+    merge_fields!(todo_entry, status, due, priority, recur, next, note, depth, id, deps, source);
+    // Concluir guarda a data: os dois campos andam juntos
+    ((left.done, left.completed), (right.done, right.completed)) = pick(
+        &(base.done, base.completed),
+        &(ours.done, ours.completed),
+        &(theirs.done, theirs.completed),
+        &mut conflict,
+    );
+    left.created = ours.created.or(theirs.created);
+    right.created = left.created;
+    if conflict {
+        Item::Conflict {
+            ours: Some(left),
+            theirs: Some(right),
+        }
+    } else {
+        Item::Entry(left)
+    }
+}
+
+fn depth(item: &Item) -> usize {
+    match item {
+        Item::Entry(entry) => entry.depth,
+        Item::Conflict { ours, theirs } => ours.as_ref().or(theirs.as_ref()).map_or(0, |entry| entry.depth),
+    }
+}
+
+pub fn merge(base: &[String], ours: &[String], theirs: &[String]) -> Merge {
+    let (base, ours, mut theirs) = (parse(base), parse(ours), parse(theirs));
+    let ours_pairs = pair_entries(&base, &ours);
+    let theirs_pairs = pair_entries(&base, &theirs);
+    renumber_ids(&base, &ours, &mut theirs, &ours_pairs, &theirs_pairs);
+
+    // Tarefa da base correspondente a cada tarefa de cada lado
+    let mut ours_base = vec![None; ours.len()];
+    for (i, pair) in ours_pairs.iter().enumerate() {
+        if let Some(j) = pair {
+            ours_base[*j] = Some(i);
+        }
+    }
+    let mut theirs_base = vec![None; theirs.len()];
+    for (i, pair) in theirs_pairs.iter().enumerate() {
+        if let Some(j) = pair {
+            theirs_base[*j] = Some(i);
+        }
+    }
+
+    // A ordem segue `ours`; cada item guarda a tarefa de `theirs` correspondente, se houver
+    let mut merged: Vec<(Item, Option<usize>)> = Vec::new();
+    for (j, entry) in ours.iter().enumerate() {
+        let Some(i) = ours_base[j] else {
+            merged.push((Item::Entry(entry.clone()), None));
+            continue;
+        };
+        match theirs_pairs[i] {
+            Some(k) => merged.push((merge_entry(&base[i], entry, &theirs[k]), Some(k))),
+            // Removida do outro lado: some, a não ser que tenha sido alterada aqui
+            None if *entry != base[i] => merged.push((
+                Item::Conflict {
+                    ours: Some(entry.clone()),
+                    theirs: None,
+                },
+                None,
+            )),
+            None => {}
+        }
+    }
+
+    for (k, entry) in theirs.iter().enumerate() {
+        let item = match theirs_base[k] {
+            None if !ours.iter().any(|ours| ours.todo_entry == entry.todo_entry) => Item::Entry(entry.clone()),
+            // Removida aqui mas alterada do outro lado
+            Some(i) if ours_pairs[i].is_none() && *entry != base[i] => Item::Conflict {
+                ours: None,
+                theirs: Some(entry.clone()),
+            },
+            _ => continue,
+        };
+        // Entra logo depois da tarefa que a precede em `theirs`, sem separar subtarefas da tarefa pai
+        let anchor = (0..k)
+            .rev()
+            .find_map(|previous| merged.iter().position(|(_, from)| *from == Some(previous)));
+        let mut pos = anchor.map_or(0, |anchor| anchor + 1);
+        while pos < merged.len() && depth(&merged[pos].0) > entry.depth {
+            pos += 1;
+        }
+        merged.insert(pos, (item, Some(k)));
+    }
+
+    let items: Vec<Item> = merged.into_iter().map(|(item, _)| item).collect();
+    let conflicts = items.iter().filter(|item| matches!(item, Item::Conflict { .. })).count();
+    Merge { items, conflicts }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn entries(values: &[&str]) -> Vec<Entry> {
+        parse(&lines(values))
+    }
+
+    fn merged(base: &[&str], ours: &[&str], theirs: &[&str]) -> Merge {
+        merge(&lines(base), &lines(ours), &lines(theirs))
+    }
+
+    #[test]
+    fn similarity_compares_character_pairs() {
+        assert_eq!(similarity("comprar pão", "comprar pão"), 1.0);
+        assert_eq!(similarity("Comprar Pão", "comprarpão"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("", ""), 0.0);
+        assert!(similarity("comprar pão", "comprar pão francês") >= SIMILARITY_THRESHOLD);
+        assert!(similarity("comprar pão", "lavar o carro") < SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn pair_entries_by_id_text_and_similarity() {
+        let base = entries(&["[ ] a id:1", "[ ] comprar pão", "[ ] lavar o carro", "[ ] sumiu"]);
+        let other = entries(&["[ ] lavar o carro", "[ ] outro texto id:1", "[ ] comprar pão francês", "[ ] nova"]);
+        assert_eq!(pair_entries(&base, &other), [Some(1), Some(2), Some(0), None]);
+    }
+
+    #[test]
+    fn clean_merge_keeps_both_sides() {
+        let base = ["[ ] comprar pão", "[ ] leite", "[ ] café"];
+        let ours = ["[*] comprar pão", "[ ] leite", "[ ] café", "[ ] ovos"];
+        let theirs = ["[ ] comprar pão francês", "[ ] leite pri:A", "[ ] açúcar"];
+        let merge = merged(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, 0);
+        assert_eq!(merge.lines(), ["[*] comprar pão francês", "[ ] leite pri:A", "[ ] açúcar", "[ ] ovos"]);
+    }
+
+    #[test]
+    fn conflicting_edits_keep_ours_and_mark_both() {
+        let merge = merged(&["[ ] comprar pão"], &["[ ] comprar pão integral"], &["[ ] comprar pão francês"]);
+        assert_eq!(merge.conflicts, 1);
+        assert_eq!(merge.lines(), ["[ ] comprar pão integral"]);
+        assert_eq!(
+            merge.marked_lines(),
+            ["<<<<<<< ours", "[ ] comprar pão integral", "=======", "[ ] comprar pão francês", ">>>>>>> theirs"]
+        );
+    }
+
+    #[test]
+    fn edit_against_removal_is_a_conflict() {
+        let base = ["[ ] pão", "[ ] leite"];
+        let merge = merged(&base, &["[ ] pão pri:A", "[ ] leite"], &["[ ] leite"]);
+        assert_eq!(merge.conflicts, 1);
+        assert_eq!(merge.lines(), ["[ ] pão pri:A", "[ ] leite"]);
+
+        let merge = merged(&base, &["[ ] leite"], &["[ ] pão pri:A", "[ ] leite"]);
+        assert_eq!(merge.conflicts, 1);
+        assert_eq!(merge.lines(), ["[ ] leite"]);
+        assert_eq!(
+            merge.marked_lines(),
+            ["<<<<<<< ours", "=======", "[ ] pão pri:A", ">>>>>>> theirs", "[ ] leite"]
+        );
+
+        // Removida de um lado e intocada do outro: some sem conflito
+        let merge = merged(&base, &["[ ] pão", "[ ] leite"], &["[ ] leite"]);
+        assert_eq!(merge.conflicts, 0);
+        assert_eq!(merge.lines(), ["[ ] leite"]);
+    }
+
+    #[test]
+    fn reordered_lists_follow_ours_and_keep_new_tasks_in_place() {
+        let base = ["[ ] a", "[ ] b", "[ ] c"];
+        let ours = ["[ ] c", "[ ] a", "[ ] b"];
+        let theirs = ["[ ] a", "[ ] b", "  [ ] b1", "[ ] c"];
+        let merge = merged(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, 0);
+        assert_eq!(merge.lines(), ["[ ] c", "[ ] a", "[ ] b", "  [ ] b1"]);
+    }
+
+    #[test]
+    fn colliding_new_ids_are_renumbered() {
+        let base = ["[ ] a"];
+        let ours = ["[ ] a dep:1", "[ ] b id:1"];
+        let theirs = ["[ ] a", "[ ] c id:1", "[ ] d dep:1"];
+        let merge = merged(&base, &ours, &theirs);
+        assert_eq!(merge.conflicts, 0);
+        assert_eq!(merge.lines(), ["[ ] a dep:1", "[ ] c id:2", "[ ] d dep:2", "[ ] b id:1"]);
+
+        // A mesma tarefa da base com o mesmo id nos dois lados continua igual
+        let merge = merged(&base, &["[ ] a id:1"], &["[ ] a id:1", "[ ] e dep:1"]);
+        assert_eq!(merge.conflicts, 0);
+        assert_eq!(merge.lines(), ["[ ] a id:1", "[ ] e dep:1"]);
+    }
+}
+
@@ -0,0 +1,82 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// Arquivo de lista de um projeto, procurado a partir do diretório atual
+pub const PROJECT_FILE: &str = ".todo";
+
+// Ligado por --global: ignora as listas de projeto e usa sempre o arquivo do home
+static FORCE_GLOBAL: AtomicBool = AtomicBool::new(false);
+
+pub fn set_global(global: bool) {
+    FORCE_GLOBAL.store(global, Ordering::Relaxed);
+}
+
+// Procura um `.todo` no diretório atual e nos pais, como o git faz com `.git`
+pub fn discover() -> Option<PathBuf> {
+    if FORCE_GLOBAL.load(Ordering::Relaxed) {
+        return None;
+    }
+    find(&env::current_dir().ok()?, dirs::home_dir().as_deref())
+}
+
+// O home fica de fora: o `.todo` de lá é a lista global, não a de um projeto
+fn find(start: &Path, home_dir: Option<&Path>) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .take_while(|dir| home_dir != Some(*dir))
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn the_nearest_project_file_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let deep = dir.path().join("app/src/bin");
+        fs::create_dir_all(&deep).unwrap();
+        assert_eq!(find(&deep, None), None);
+
+        fs::write(dir.path().join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find(&deep, None), Some(dir.path().join(PROJECT_FILE)));
+        fs::write(dir.path().join("app").join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find(&deep, None), Some(dir.path().join("app").join(PROJECT_FILE)));
+        assert_eq!(find(dir.path(), None), Some(dir.path().join(PROJECT_FILE)));
+    }
+
+    #[test]
+    fn a_directory_named_like_the_project_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(PROJECT_FILE)).unwrap();
+        assert_eq!(find(dir.path(), None), None);
+    }
+
+    #[test]
+    fn the_search_stops_at_the_home_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        let project = home.join("code/app");
+        fs::create_dir_all(&project).unwrap();
+        // O `.todo` do home e os de cima dele não são listas de projeto
+        fs::write(dir.path().join(PROJECT_FILE), "").unwrap();
+        fs::write(home.join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find(&project, Some(&home)), None);
+        assert_eq!(find(&home, Some(&home)), None);
+
+        fs::write(project.join(PROJECT_FILE), "").unwrap();
+        assert_eq!(find(&project, Some(&home)), Some(project.join(PROJECT_FILE)));
+        // Fora do home a busca vai até a raiz
+        assert_eq!(find(dir.path(), Some(&home)), Some(dir.path().join(PROJECT_FILE)));
+    }
+
+    #[test]
+    fn global_turns_discovery_off() {
+        set_global(true);
+        assert_eq!(discover(), None);
+        set_global(false);
+    }
+}
@@ -0,0 +1,200 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use std::fmt;
+
+// Regra de repetição gravada como "rec:<regra>" na linha da tarefa
+#[derive(Clone, PartialEq)]
+pub enum Recurrence {
+    // Todo dia
+    Daily,
+    // Nos dias da semana informados; sem dias, no mesmo dia da semana do vencimento
+    Weekly(Vec<Weekday>),
+    // Num dia do mês (limitado ao último dia em meses mais curtos)
+    Monthly(u32),
+    // N dias depois da conclusão
+    AfterDays(u64),
+}
+
+// Maior intervalo aceito em after:N (cerca de 100 anos)
+const MAX_AFTER_DAYS: u64 = 36500;
+
+const WEEKDAY_NAMES: [(&str, &str, Weekday); 7] = [
+    ("mon", "seg", Weekday::Mon),
+    ("tue", "ter", Weekday::Tue),
+    ("wed", "qua", Weekday::Wed),
+    ("thu", "qui", Weekday::Thu),
+    ("fri", "sex", Weekday::Fri),
+    ("sat", "sab", Weekday::Sat),
+    ("sun", "dom", Weekday::Sun),
+];
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    let text = text.to_lowercase().replace('á', "a");
+    WEEKDAY_NAMES
+        .iter()
+        .find(|(en, pt, _)| text == *en || text == *pt)
+        .map(|(_, _, weekday)| *weekday)
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    WEEKDAY_NAMES
+        .iter()
+        .find(|(_, _, day)| *day == weekday)
+        .map_or("mon", |(en, _, _)| en)
+}
+
+impl Recurrence {
+    // Aceita daily, weekly[:mon,thu], monthly:15 e after:3 (e os equivalentes em português)
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || {
+            format!(
+                "Regra de repetição inválida '{}', use daily, weekly[:mon,thu], monthly:15 ou after:3",
+                text
+            )
+        };
+        let (rule, arg) = match text.trim().split_once(':') {
+            Some((rule, arg)) => (rule, Some(arg)),
+            None => (text.trim(), None),
+        };
+
+        match (rule, arg) {
+            ("daily" | "diario", None) => Ok(Recurrence::Daily),
+            ("weekly" | "semanal", None) => Ok(Recurrence::Weekly(Vec::new())),
+            ("weekly" | "semanal", Some(days)) => {
+                let mut weekdays: Vec<Weekday> = days
+                    .split(',')
+                    .map(parse_weekday)
+                    .collect::<Option<_>>()
+                    .ok_or_else(invalid)?;
+                weekdays.sort_by_key(Weekday::num_days_from_monday);
+                weekdays.dedup();
+                Ok(Recurrence::Weekly(weekdays))
+            }
+            ("monthly" | "mensal", Some(day)) => match day.parse() {
+                Ok(day @ 1..=31) => Ok(Recurrence::Monthly(day)),
+                _ => Err(invalid()),
+            },
+            ("after" | "apos", Some(days)) => match days.parse() {
+                Ok(days @ 1..=MAX_AFTER_DAYS) => Ok(Recurrence::AfterDays(days)),
+                _ => Err(invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    }
+
+    // Próximo vencimento depois que a tarefa foi concluída em `today`
+    pub fn next_due(&self, due: Option<NaiveDate>, today: NaiveDate) -> Result<NaiveDate, String> {
+        // Regras de calendário andam a partir do vencimento, mas nunca geram uma data no passado
+        let base = due.map_or(today, |due| due.max(today));
+        let next = match self {
+            Recurrence::Daily => base.checked_add_days(Days::new(1)),
+            Recurrence::Weekly(weekdays) => {
+                let weekdays = if weekdays.is_empty() {
+                    vec![due.unwrap_or(today).weekday()]
+                } else {
+                    weekdays.clone()
+                };
+                base.iter_days().skip(1).take(7).find(|day| weekdays.contains(&day.weekday()))
+            }
+            Recurrence::Monthly(day) => match day_in_month(base, *day) {
+                Some(this_month) if this_month > base => Some(this_month),
+                _ => base.checked_add_months(Months::new(1)).and_then(|next| day_in_month(next, *day)),
+            },
+            Recurrence::AfterDays(days) => today.checked_add_days(Days::new(*days)),
+        };
+        next.ok_or_else(|| format!("O próximo vencimento da regra '{}' fica fora das datas suportadas", self))
+    }
+}
+
+// O dia `day` no mês de `date`, ou o último dia se o mês for mais curto
+fn day_in_month(date: NaiveDate, day: u32) -> Option<NaiveDate> {
+    let first = date.with_day(1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    first.with_day(day.min(last.day()))
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Recurrence::Daily => write!(f, "daily"),
+            Recurrence::Weekly(weekdays) if weekdays.is_empty() => write!(f, "weekly"),
+            Recurrence::Weekly(weekdays) => {
+                let names: Vec<&str> = weekdays.iter().map(|day| weekday_name(*day)).collect();
+                write!(f, "weekly:{}", names.join(","))
+            }
+            Recurrence::Monthly(day) => write!(f, "monthly:{}", day),
+            Recurrence::AfterDays(days) => write!(f, "after:{}", days),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn next(rule: &str, due: Option<&str>, today: &str) -> Result<NaiveDate, String> {
+        Recurrence::parse(rule).unwrap().next_due(due.map(date), date(today))
+    }
+
+    #[test]
+    fn rules_are_parsed_in_both_languages() {
+        assert!(Recurrence::parse("diario").unwrap() == Recurrence::Daily);
+        let weekly = Recurrence::Weekly(vec![Weekday::Mon, Weekday::Thu]);
+        assert!(Recurrence::parse("semanal:qui,Seg,mon").unwrap() == weekly);
+        assert!(Recurrence::parse("mensal:31").unwrap() == Recurrence::Monthly(31));
+        assert!(Recurrence::parse("after:36500").unwrap() == Recurrence::AfterDays(36500));
+        let invalid = ["", "yearly", "daily:2", "weekly:xyz", "monthly", "monthly:0", "monthly:32", "after:0", "after:-1"];
+        for rule in invalid {
+            assert!(Recurrence::parse(rule).is_err(), "{}", rule);
+        }
+        assert!(Recurrence::parse("after:36501").is_err());
+        assert!(Recurrence::parse("after:9999999999").is_err());
+        assert_eq!(Recurrence::parse("semanal:qui,seg").unwrap().to_string(), "weekly:mon,thu");
+    }
+
+    #[test]
+    fn monthly_keeps_to_the_last_day_of_shorter_months() {
+        assert_eq!(next("monthly:31", Some("2025-01-31"), "2025-01-31"), Ok(date("2025-02-28")));
+        assert_eq!(next("monthly:31", Some("2025-02-28"), "2025-02-28"), Ok(date("2025-03-31")));
+        assert_eq!(next("monthly:31", Some("2025-03-31"), "2025-03-31"), Ok(date("2025-04-30")));
+        assert_eq!(next("monthly:31", Some("2024-01-31"), "2024-01-31"), Ok(date("2024-02-29")));
+        // Ainda dá tempo neste mês
+        assert_eq!(next("monthly:15", None, "2025-06-10"), Ok(date("2025-06-15")));
+        assert_eq!(next("monthly:15", Some("2025-12-15"), "2025-12-15"), Ok(date("2026-01-15")));
+    }
+
+    #[test]
+    fn weekly_wraps_to_the_next_week() {
+        // 2025-06-13 é uma sexta
+        assert_eq!(next("weekly:mon,thu", Some("2025-06-12"), "2025-06-13"), Ok(date("2025-06-16")));
+        assert_eq!(next("weekly:mon,thu", Some("2025-06-16"), "2025-06-13"), Ok(date("2025-06-19")));
+        assert_eq!(next("weekly", Some("2025-06-13"), "2025-06-13"), Ok(date("2025-06-20")));
+        assert_eq!(next("weekly:fri", None, "2025-06-13"), Ok(date("2025-06-20")));
+    }
+
+    #[test]
+    fn calendar_rules_never_fall_in_the_past() {
+        assert_eq!(next("daily", Some("2025-01-01"), "2025-06-13"), Ok(date("2025-06-14")));
+        assert_eq!(next("daily", Some("2025-06-20"), "2025-06-13"), Ok(date("2025-06-21")));
+    }
+
+    #[test]
+    fn after_counts_from_completion() {
+        assert_eq!(next("after:3", Some("2025-01-01"), "2025-06-13"), Ok(date("2025-06-16")));
+        assert_eq!(next("after:3", Some("2025-12-01"), "2025-06-13"), Ok(date("2025-06-16")));
+    }
+
+    #[test]
+    fn dates_out_of_range_are_an_error() {
+        let last = NaiveDate::MAX.format("%Y-%m-%d").to_string();
+        assert!(next("daily", Some(&last), "2025-06-13").is_err());
+        assert!(next("weekly", Some(&last), "2025-06-13").is_err());
+        assert!(next("monthly:1", Some(&last), "2025-06-13").is_err());
+        assert!(Recurrence::AfterDays(u64::MAX).next_due(None, date("2025-06-13")).is_err());
+        assert!(next("after:36500", None, &last).is_err());
+    }
+}
+
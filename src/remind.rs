@@ -0,0 +1,465 @@
+use crate::{DATE_FORMAT, Entry, Todo, daemon, sync, today};
+use chrono::{Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+// Lembretes das tarefas com vencimento: `remind` fica rodando, relê as listas de tempos em tempos
+// e mostra uma notificação na área de trabalho (D-Bus) com a antecedência configurada.
+// Os botões da notificação concluem a tarefa ou adiam o vencimento, gravando pela lista.
+
+// Intervalo entre as releituras das listas
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+const DEFAULT_LEADS: &str = "1d,1h,0";
+const DEFAULT_DUE_TIME: &str = "09:00";
+
+pub struct RemindOptions {
+    // Listas vigiadas (None = principal)
+    pub lists: Vec<Option<String>>,
+    // Com quanto tempo de antecedência avisar
+    pub leads: Vec<TimeDelta>,
+    // O vencimento só tem a data: a tarefa vence nesta hora do dia
+    pub due_time: NaiveTime,
+}
+
+impl RemindOptions {
+    // Antecedências de TODO_REMIND_LEAD e hora de TODO_REMIND_AT, ou os padrões
+    pub fn from_env(lists: Vec<Option<String>>) -> Result<Self, String> {
+        let leads = env::var("TODO_REMIND_LEAD").unwrap_or_else(|_| DEFAULT_LEADS.to_string());
+        let due_time = env::var("TODO_REMIND_AT").unwrap_or_else(|_| DEFAULT_DUE_TIME.to_string());
+        Ok(Self {
+            lists,
+            leads: parse_leads(&leads)?,
+            due_time: parse_time(&due_time)?,
+        })
+    }
+}
+
+// Maior antecedência aceita
+const MAX_LEAD_DAYS: i64 = 365;
+
+type ToDelta = fn(i64) -> Option<TimeDelta>;
+
+// Antecedências separadas por vírgula: 15m, 2h, 1d ou 0 (na hora)
+pub fn parse_leads(text: &str) -> Result<Vec<TimeDelta>, String> {
+    let invalid = |lead: &str| format!("Antecedência inválida '{}', use por exemplo 1d,2h,15m,0", lead);
+    text.split(',')
+        .map(str::trim)
+        .filter(|lead| !lead.is_empty())
+        .map(|lead| {
+            if lead == "0" {
+                return Ok(TimeDelta::zero());
+            }
+            let units: [(char, ToDelta); 3] =
+                [('m', TimeDelta::try_minutes), ('h', TimeDelta::try_hours), ('d', TimeDelta::try_days)];
+            let Some((amount, to_delta)) = units
+                .iter()
+                .find_map(|(unit, to_delta)| Some((lead.strip_suffix(*unit)?, to_delta)))
+            else {
+                return Err(invalid(lead));
+            };
+            // Só antecedências: avisar depois do vencimento não faz sentido
+            let amount: i64 = amount.parse().ok().filter(|amount| *amount >= 0).ok_or_else(|| invalid(lead))?;
+            match to_delta(amount) {
+                Some(delta) if delta <= TimeDelta::days(MAX_LEAD_DAYS) => Ok(delta),
+                Some(_) => Err(format!("Antecedência '{}' maior que o máximo de {} dias", lead, MAX_LEAD_DAYS)),
+                None => Err(invalid(lead)),
+            }
+        })
+        .collect()
+}
+
+pub fn parse_time(text: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(text.trim(), "%H:%M").map_err(|_| format!("Hora inválida '{}', use HH:MM", text))
+}
+
+// Aviso de uma tarefa numa das antecedências
+#[derive(Clone)]
+pub struct Reminder {
+    pub list: Option<String>,
+    pub text: String,
+    pub due: NaiveDate,
+    pub due_at: NaiveDateTime,
+    lead: TimeDelta,
+    path: PathBuf,
+}
+
+// Um aviso já mostrado: lista, tarefa, vencimento e antecedência
+type Fired = (PathBuf, String, NaiveDate, TimeDelta);
+
+fn describe_span(span: TimeDelta) -> String {
+    let minutes = span.num_minutes().max(1);
+    if minutes >= 24 * 60 {
+        let days = (minutes + 12 * 60) / (24 * 60);
+        format!("{} dia(s)", days)
+    } else if minutes >= 60 {
+        format!("{} h", (minutes + 30) / 60)
+    } else {
+        format!("{} min", minutes)
+    }
+}
+
+impl Reminder {
+    fn fired(&self) -> Fired {
+        (self.path.clone(), self.text.clone(), self.due, self.lead)
+    }
+
+    pub fn summary(&self, now: NaiveDateTime) -> String {
+        let left = self.due_at - now;
+        if left > TimeDelta::zero() {
+            format!("Vence em {}", describe_span(left))
+        } else if left > -TimeDelta::hours(1) {
+            "Vence agora".to_string()
+        } else {
+            "Tarefa vencida".to_string()
+        }
+    }
+
+    pub fn body(&self) -> String {
+        let mut body = format!("{}\nVencimento: {}", self.text, self.due_at.format("%d/%m/%Y %H:%M"));
+        if let Some(list) = &self.list {
+            body.push_str(&format!("\nLista: {}", list));
+        }
+        body
+    }
+}
+
+// Ação escolhida num botão da notificação
+pub enum Action {
+    Done,
+    // Passa o vencimento para o dia seguinte (ou para amanhã, se já venceu)
+    Snooze,
+}
+
+// Avisos que já deviam ter saído: para cada tarefa, só o da menor antecedência que já passou.
+// Só as tarefas na janela de vencimento são lidas (no SQLite, pelo índice do vencimento).
+fn pending(
+    todo: &Todo,
+    list: &Option<String>,
+    options: &RemindOptions,
+    now: NaiveDateTime,
+) -> Result<Vec<Reminder>, String> {
+    let max_lead = options.leads.iter().max().copied().unwrap_or_else(TimeDelta::zero);
+    let from = now.date().pred_opt().unwrap_or(NaiveDate::MIN);
+    let until = now.checked_add_signed(max_lead).map_or(NaiveDate::MAX, |until| until.date());
+    let mut reminders = Vec::new();
+    for line in todo.storage().pending_due(from, until)? {
+        let entry = Entry::read_line(&line);
+        let Some(due) = entry.due else {
+            continue;
+        };
+        let due_at = due.and_time(options.due_time);
+        // Vencidas há mais de um dia não são lembradas de novo
+        if entry.done || due_at.checked_add_signed(TimeDelta::days(1)).is_some_and(|end| now >= end) {
+            continue;
+        }
+        let passed = |lead: &&TimeDelta| due_at.checked_sub_signed(**lead).is_some_and(|start| now >= start);
+        let Some(lead) = options.leads.iter().filter(passed).min() else {
+            continue;
+        };
+        reminders.push(Reminder {
+            list: list.clone(),
+            text: entry.todo_entry,
+            due,
+            due_at,
+            lead: *lead,
+            path: todo.todo_path.clone(),
+        });
+    }
+    Ok(reminders)
+}
+
+// Aplica a ação na tarefa da lista `todo` (já lida), que é achada de novo pelo texto e vencimento
+// (os índices podem ter mudado)
+fn act(
+    client: &mut Option<daemon::Client>,
+    todo: Todo,
+    reminder: &Reminder,
+    action: &Action,
+) -> Result<String, String> {
+    let pos = todo
+        .todo
+        .iter()
+        .map(|line| Entry::read_line(line))
+        .position(|entry| !entry.done && entry.todo_entry == reminder.text && entry.due == Some(reminder.due))
+        .ok_or(format!("A tarefa '{}' não está mais pendente na lista", reminder.text))?;
+    let index = todo.index_labels()[pos].clone();
+    let (command, args, done) = match action {
+        Action::Done => ("done", vec![index], "concluída".to_string()),
+        Action::Snooze => {
+            let due = reminder
+                .due
+                .max(today())
+                .checked_add_days(Days::new(1))
+                .ok_or("Não foi possível adiar: data fora do intervalo suportado")?;
+            let done = format!("adiada para {}", due.format("%d/%m/%Y"));
+            ("due", vec![index, due.format(DATE_FORMAT).to_string()], done)
+        }
+    };
+    let message = sync::describe(&todo, command, &args);
+    daemon::apply(client, &todo, command, &args)?;
+    let todo = todo.reload()?;
+    todo.auto_archive()?;
+    sync::record(&todo, &message)?;
+    Ok(format!("Tarefa '{}' {}", reminder.text, done))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod desktop {
+    use super::{Action, Reminder};
+    use chrono::NaiveDateTime;
+    use notify_rust::{Notification, Timeout};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::Sender;
+    use std::thread;
+
+    // A notificação some depois desse tempo, e com ela a thread que espera o botão
+    const EXPIRE_MS: u32 = 60 * 60 * 1000;
+
+    // Servidores que ignoram o tempo de expiração deixam as threads esperando até a notificação
+    // ser fechada: acima deste número, as notificações novas saem sem botões
+    const MAX_WAITING: usize = 32;
+
+    static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+    // Confere uma vez, ao iniciar, que há um servidor de notificações na sessão
+    pub fn check() -> Result<(), String> {
+        notify_rust::get_server_information()
+            .map(|_| ())
+            .map_err(|e| {
+                format!("Nenhum servidor de notificações (org.freedesktop.Notifications) na sessão: {}", e)
+            })
+    }
+
+    // Mostra a notificação pelo servidor org.freedesktop.Notifications da sessão e espera
+    // o botão escolhido numa thread própria
+    pub fn notify(reminder: &Reminder, now: NaiveDateTime, actions: Sender<(Reminder, Action)>) -> Result<(), String> {
+        let mut notification = Notification::new();
+        notification
+            .appname("todo-todo")
+            .summary(&reminder.summary(now))
+            .body(&reminder.body())
+            .icon("appointment-soon")
+            .timeout(Timeout::Milliseconds(EXPIRE_MS));
+        let with_buttons = WAITING.load(Ordering::SeqCst) < MAX_WAITING;
+        if with_buttons {
+            notification.action("done", "Concluir").action("snooze", "Adiar 1 dia");
+        }
+        let handle = notification
+            .show()
+            .map_err(|e| format!("Não foi possível mostrar a notificação: {}", e))?;
+        if !with_buttons {
+            return Ok(());
+        }
+        let reminder = reminder.clone();
+        WAITING.fetch_add(1, Ordering::SeqCst);
+        thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                let action = match action {
+                    "done" => Action::Done,
+                    "snooze" => Action::Snooze,
+                    // Fechada ou clicada fora dos botões
+                    _ => return,
+                };
+                let _ = actions.send((reminder, action));
+            });
+            WAITING.fetch_sub(1, Ordering::SeqCst);
+        });
+        Ok(())
+    }
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+mod desktop {
+    use super::{Action, Reminder};
+    use chrono::NaiveDateTime;
+    use std::sync::mpsc::Sender;
+
+    pub fn check() -> Result<(), String> {
+        Err("Os lembretes usam notificações por D-Bus, disponíveis só no Linux e BSDs".to_string())
+    }
+
+    pub fn notify(_: &Reminder, _: NaiveDateTime, _: Sender<(Reminder, Action)>) -> Result<(), String> {
+        check()
+    }
+}
+
+// Roda em primeiro plano até o processo ser encerrado
+pub fn run(options: RemindOptions) -> Result<(), String> {
+    if options.leads.is_empty() {
+        return Err("Informe pelo menos uma antecedência (ex.: --lead 1d,1h,0)".to_string());
+    }
+    desktop::check()?;
+    let (sender, receiver) = mpsc::channel::<(Reminder, Action)>();
+    let mut client = daemon::Client::connect();
+    let mut fired: HashSet<Fired> = HashSet::new();
+    println!(
+        "Lembretes ativos ({} lista(s), vencimento às {})",
+        options.lists.len(),
+        options.due_time.format("%H:%M")
+    );
+
+    loop {
+        let now = Local::now().naive_local();
+        for list in &options.lists {
+            let reminders = Todo::locate(list.as_deref()).and_then(|todo| pending(&todo, list, &options, now));
+            let reminders = match reminders {
+                Ok(reminders) => reminders,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    continue;
+                }
+            };
+            for reminder in reminders {
+                if fired.contains(&reminder.fired()) {
+                    continue;
+                }
+                // Se o servidor de notificações não responder, tenta de novo na próxima volta
+                match desktop::notify(&reminder, now, sender.clone()) {
+                    Ok(()) => {
+                        fired.insert(reminder.fired());
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+        let yesterday = today() - Days::new(1);
+        fired.retain(|(_, _, due, _)| *due >= yesterday);
+
+        // Até a próxima releitura, atende os botões das notificações
+        let deadline = Instant::now() + POLL_INTERVAL;
+        while let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+            match receiver.recv_timeout(wait) {
+                Ok((reminder, action)) => match daemon::open_list(&mut client, reminder.list.as_deref())
+                    .and_then(|todo| act(&mut client, todo, &reminder, &action))
+                {
+                    Ok(message) => println!("{}", message),
+                    Err(e) => eprintln!("{}", e),
+                },
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::temp_list;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn leads_are_parsed_and_checked() {
+        let leads = parse_leads(" 1d, 2h,,15m ,0").unwrap();
+        assert_eq!(leads, [TimeDelta::days(1), TimeDelta::hours(2), TimeDelta::minutes(15), TimeDelta::zero()]);
+        assert!(parse_leads("").unwrap().is_empty());
+        for lead in ["1", "d", "1x", "1é", "é", "-1h", "1.5h", "99999999999999d", "9223372036854775807m"] {
+            assert!(parse_leads(lead).is_err(), "{}", lead);
+        }
+        assert_eq!(parse_leads("365d").unwrap(), [TimeDelta::days(365)]);
+        for lead in ["366d", "8761h", "1000000000d", "1000000000h", "1000000000m"] {
+            assert!(parse_leads(lead).is_err(), "{}", lead);
+        }
+    }
+
+    #[test]
+    fn due_time_is_parsed() {
+        assert_eq!(parse_time(" 18:30 ").unwrap(), NaiveTime::from_hms_opt(18, 30, 0).unwrap());
+        assert!(parse_time("25:00").is_err());
+        assert!(parse_time("9h").is_err());
+    }
+
+    #[test]
+    fn pending_picks_the_smallest_lead_already_passed() {
+        let (_dir, todo) = temp_list(&[
+            "[ ] relatório due:2026-05-10",
+            "[*] pago due:2026-05-10",
+            "[ ] sem data",
+            "[ ] antiga due:2026-05-07",
+        ]);
+        let options = RemindOptions {
+            lists: vec![None],
+            leads: parse_leads("1d,1h,0").unwrap(),
+            due_time: parse_time("09:00").unwrap(),
+        };
+        let leads_at = |now: &str| -> Vec<(String, TimeDelta)> {
+            pending(&todo, &None, &options, at(now))
+                .unwrap()
+                .into_iter()
+                .map(|reminder| (reminder.text, reminder.lead))
+                .collect()
+        };
+        let relatorio = |lead| vec![("relatório".to_string(), lead)];
+        assert_eq!(leads_at("2026-05-09 08:59"), []);
+        assert_eq!(leads_at("2026-05-09 09:00"), relatorio(TimeDelta::days(1)));
+        assert_eq!(leads_at("2026-05-10 08:30"), relatorio(TimeDelta::hours(1)));
+        assert_eq!(leads_at("2026-05-10 09:00"), relatorio(TimeDelta::zero()));
+        // Vencida há mais de um dia não é lembrada
+        assert_eq!(leads_at("2026-05-11 09:00"), []);
+
+        let reminder = &pending(&todo, &None, &options, at("2026-05-10 08:30")).unwrap()[0];
+        assert_eq!(reminder.summary(at("2026-05-10 08:30")), "Vence em 30 min");
+        assert_eq!(reminder.summary(at("2026-05-10 09:30")), "Vence agora");
+        assert_eq!(reminder.summary(at("2026-05-10 11:00")), "Tarefa vencida");
+    }
+
+    #[test]
+    fn long_leads_near_the_first_supported_date_do_not_overflow() {
+        let due = NaiveDate::MIN + Days::new(2);
+        let (_dir, todo) = temp_list(&[&format!("[ ] antiga due:{}", due.format(DATE_FORMAT))]);
+        assert_eq!(Entry::read_line(&todo.todo[0]).due, Some(due));
+        let options = RemindOptions {
+            lists: vec![None],
+            leads: parse_leads("365d,0").unwrap(),
+            due_time: parse_time("09:00").unwrap(),
+        };
+        let leads: Vec<TimeDelta> = pending(&todo, &None, &options, due.and_hms_opt(9, 0, 0).unwrap())
+            .unwrap()
+            .into_iter()
+            .map(|reminder| reminder.lead)
+            .collect();
+        assert_eq!(leads, [TimeDelta::zero()]);
+        assert!(pending(&todo, &None, &options, due.and_hms_opt(8, 0, 0).unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn actions_write_back_to_the_list() {
+        let due = today();
+        let (_dir, todo) = temp_list(&[
+            "[ ] outra",
+            &format!("[ ] relatório due:{}", due.format(DATE_FORMAT)),
+            "  [ ] anexos",
+        ]);
+        let options = RemindOptions {
+            lists: vec![None],
+            leads: parse_leads("0").unwrap(),
+            due_time: parse_time("00:00").unwrap(),
+        };
+        let now = due.and_hms_opt(12, 0, 0).unwrap();
+        let reminder = pending(&todo, &None, &options, now).unwrap().remove(0);
+
+        let message = act(&mut None, todo.reload().unwrap(), &reminder, &Action::Snooze).unwrap();
+        let snoozed = due + Days::new(1);
+        assert_eq!(message, format!("Tarefa 'relatório' adiada para {}", snoozed.format("%d/%m/%Y")));
+        let todo = todo.reload().unwrap();
+        assert_eq!(Entry::read_line(&todo.todo[1]).due, Some(snoozed));
+        // O lembrete antigo não acha mais a tarefa, que mudou de vencimento
+        assert!(act(&mut None, todo.reload().unwrap(), &reminder, &Action::Done).is_err());
+
+        // A tarefa é achada pelo texto e vencimento, mesmo se mudou de posição
+        let reminder = Reminder { due: snoozed, ..reminder };
+        todo.apply("rm", &["1".to_string()]).unwrap();
+        let todo = todo.reload().unwrap();
+        let message = act(&mut None, todo.reload().unwrap(), &reminder, &Action::Done).unwrap();
+        assert_eq!(message, "Tarefa 'relatório' concluída");
+        let entries: Vec<Entry> = todo.reload().unwrap().todo.iter().map(|line| Entry::read_line(line)).collect();
+        assert_eq!(entries[0].todo_entry, "relatório");
+        assert!(entries[0].done && !entries[1].done);
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{Entry, Todo, decode_note, encode_note, today};
+use ignore::WalkBuilder;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Marcadores procurados nos comentários
+const MARKERS: [&str; 3] = ["TODO", "FIXME", "XXX"];
+
+// Como começam os comentários e as strings de uma linguagem. Um marcador só conta depois do
+// início de um comentário que esteja fora de strings (ex.: printf("%d TODO") não é um comentário).
+struct Syntax {
+    comments: &'static [&'static str],
+    quotes: &'static [char],
+}
+
+const C_LIKE: Syntax = Syntax {
+    comments: &["//", "/*"],
+    quotes: &['"', '\'', '`'],
+};
+// Em Rust o apóstrofo também marca lifetimes ('a), então não abre string
+const RUST: Syntax = Syntax {
+    comments: &["//", "/*"],
+    quotes: &['"'],
+};
+const HASH: Syntax = Syntax {
+    comments: &["#"],
+    quotes: &['"', '\''],
+};
+const DASHES: Syntax = Syntax {
+    comments: &["--"],
+    quotes: &['"', '\''],
+};
+// No texto de HTML e Markdown as aspas não delimitam nada
+const MARKUP: Syntax = Syntax {
+    comments: &["<!--"],
+    quotes: &[],
+};
+const SEMICOLON: Syntax = Syntax {
+    comments: &[";"],
+    quotes: &['"'],
+};
+const PERCENT: Syntax = Syntax {
+    comments: &["%"],
+    quotes: &[],
+};
+// Extensão desconhecida: qualquer início de comentário das linguagens acima
+const ANY: Syntax = Syntax {
+    comments: &["//", "#", "/*", "--", "<!--", ";", "%"],
+    quotes: &['"', '\''],
+};
+
+fn syntax_for(path: &Path) -> &'static Syntax {
+    let name = path.file_name().map(|name| name.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if matches!(name.as_str(), "makefile" | "dockerfile" | "cmakelists.txt" | ".gitignore") {
+        return &HASH;
+    }
+    let extension = path.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "rs" => &RUST,
+        "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "java" | "js" | "jsx" | "mjs" | "ts" | "tsx" | "go" | "swift"
+        | "kt" | "kts" | "scala" | "dart" | "css" | "scss" | "less" | "proto" | "groovy" | "zig" => &C_LIKE,
+        "py" | "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "pm" | "r" | "yaml" | "yml" | "toml" | "conf"
+        | "cfg" | "cmake" | "ps1" | "nim" | "jl" | "tf" | "ex" | "exs" => &HASH,
+        "sql" | "lua" | "hs" | "elm" | "ada" | "adb" => &DASHES,
+        "html" | "htm" | "xml" | "svg" | "vue" | "md" | "markdown" => &MARKUP,
+        "lisp" | "el" | "clj" | "cljs" | "scm" | "rkt" | "asm" | "s" | "ini" => &SEMICOLON,
+        "tex" | "sty" | "cls" | "erl" | "hrl" => &PERCENT,
+        _ => &ANY,
+    }
+}
+
+// Arquivos maiores que isso são ignorados (provavelmente gerados ou binários)
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+// Local de um comentário no código, gravado como "src:<arquivo>:<linha>"
+#[derive(Clone, PartialEq)]
+pub struct SourceRef {
+    pub path: String,
+    pub line: usize,
+}
+
+impl SourceRef {
+    pub fn parse(value: &str) -> Option<Self> {
+        let (path, line) = value.rsplit_once(':')?;
+        Some(Self {
+            path: decode_note(path)?,
+            line: line.parse().ok()?,
+        })
+    }
+
+    // O caminho pode ter espaços: é codificado como a anotação
+    pub fn token(&self) -> String {
+        format!("{}:{}", encode_note(&self.path), self.line)
+    }
+}
+
+impl fmt::Display for SourceRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)
+    }
+}
+
+// Comentário encontrado na varredura
+pub struct Found {
+    pub source: SourceRef,
+    pub text: String,
+}
+
+// Resultado de `Todo::scan`
+pub struct ScanSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub closed: usize,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Se `before` (o trecho da linha antes do marcador) abre um comentário fora de strings
+fn in_comment(before: &str, syntax: &Syntax) -> bool {
+    let mut quote = None;
+    let mut chars = before.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match quote {
+            Some(open) => {
+                if c == '\\' {
+                    chars.next();
+                } else if c == open {
+                    quote = None;
+                }
+            }
+            None if syntax.quotes.contains(&c) => quote = Some(c),
+            None if syntax.comments.iter().any(|comment| before[i..].starts_with(comment)) => return true,
+            None => {}
+        }
+    }
+    // Continuação de um comentário de bloco ("/* ... \n * TODO")
+    syntax.comments.contains(&"/*") && before.trim_start().starts_with('*')
+}
+
+// Texto da tarefa para um marcador dentro de um comentário (ex.: "FIXME trocar o unwrap")
+fn find_marker(line: &str, syntax: &Syntax) -> Option<String> {
+    let (start, marker) = MARKERS
+        .iter()
+        .flat_map(|marker| line.match_indices(marker))
+        .filter(|(start, marker)| {
+            let before = line[..*start].chars().next_back();
+            let after = line[start + marker.len()..].chars().next();
+            !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+        })
+        .filter(|(start, _)| in_comment(&line[..*start], syntax))
+        .min_by_key(|(start, _)| *start)?;
+
+    let mut rest = &line[start + marker.len()..];
+    // Autor entre parênteses, como em "TODO(ana): ..."
+    if rest.starts_with('(')
+        && let Some(end) = rest.find(')')
+    {
+        rest = &rest[end + 1..];
+    }
+    let rest = rest
+        .trim_start_matches([':', '-', ' '])
+        .trim_end()
+        .trim_end_matches("*/")
+        .trim_end_matches("-->")
+        .trim();
+    if rest.is_empty() {
+        Some(marker.to_string())
+    } else {
+        Some(format!("{} {}", marker, rest))
+    }
+}
+
+// Percorre `root` respeitando .gitignore; os caminhos ficam relativos a `base` quando possível
+pub fn scan_tree(root: &Path, base: &Path) -> Vec<Found> {
+    let mut found = Vec::new();
+    for entry in WalkBuilder::new(root).require_git(false).build().filter_map(Result::ok) {
+        let path = entry.path();
+        let is_small_file = entry
+            .metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.len() <= MAX_FILE_SIZE);
+        if !is_small_file {
+            continue;
+        }
+        // Arquivos que não são texto UTF-8 ficam de fora
+        let Ok(contents) = fs::read_to_string(path) else {
+            continue;
+        };
+        let shown = path.strip_prefix(base).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        let syntax = syntax_for(path);
+        for (number, line) in contents.lines().enumerate() {
+            if let Some(text) = find_marker(line, syntax) {
+                found.push(Found {
+                    source: SourceRef {
+                        path: shown.clone(),
+                        line: number + 1,
+                    },
+                    text,
+                });
+            }
+        }
+    }
+    found
+}
+
+impl Todo {
+    // Diretório de referência para os caminhos relativos das tarefas importadas
+    fn source_base(&self) -> PathBuf {
+        let dir = self.todo_path.parent().unwrap_or(Path::new("."));
+        dir.canonicalize().unwrap_or(dir.to_path_buf())
+    }
+
+    pub fn source_path(&self, source: &SourceRef) -> PathBuf {
+        self.source_base().join(&source.path)
+    }
+
+    // Importa os comentários TODO/FIXME/XXX de `root`. Tarefas já importadas são atualizadas
+    // (linha ou texto) e as que não têm mais comentário correspondente são concluídas.
+    pub fn scan(&self, root: &Path) -> Result<ScanSummary, String> {
+        let root = root
+            .canonicalize()
+            .map_err(|e| format!("Não foi possível ler {}: {}", root.display(), e))?;
+        let base = self.source_base();
+        let found = scan_tree(&root, &base);
+
+        let mut entries: Vec<Entry> = self.todo.iter().map(|line| Entry::read_line(line)).collect();
+        let mut changed = vec![false; entries.len()];
+        // Só as tarefas importadas de dentro de `root` participam da sincronização
+        let in_scope: Vec<bool> = entries
+            .iter()
+            .map(|entry| {
+                entry
+                    .source
+                    .as_ref()
+                    .is_some_and(|source| base.join(&source.path).starts_with(&root))
+            })
+            .collect();
+        let mut matched = vec![false; entries.len()];
+        let mut summary = ScanSummary {
+            added: 0,
+            updated: 0,
+            closed: 0,
+        };
+
+        // Primeiro casa pelo texto no mesmo arquivo (a linha pode ter mudado), depois pela linha
+        let mut pending: Vec<&Found> = Vec::new();
+        for item in &found {
+            let pos = (0..entries.len()).find(|&pos| {
+                in_scope[pos]
+                    && !matched[pos]
+                    && entries[pos].todo_entry == item.text
+                    && entries[pos].source.as_ref().is_some_and(|source| source.path == item.source.path)
+            });
+            match pos {
+                Some(pos) => {
+                    matched[pos] = true;
+                    let entry = &mut entries[pos];
+                    if entry.source.as_ref() != Some(&item.source) || entry.done {
+                        entry.source = Some(item.source.clone());
+                        entry.set_done(false);
+                        changed[pos] = true;
+                        summary.updated += 1;
+                    }
+                }
+                None => pending.push(item),
+            }
+        }
+
+        let mut added = Vec::new();
+        for item in pending {
+            let pos = (0..entries.len())
+                .find(|&pos| in_scope[pos] && !matched[pos] && entries[pos].source.as_ref() == Some(&item.source));
+            match pos {
+                Some(pos) => {
+                    matched[pos] = true;
+                    let entry = &mut entries[pos];
+                    entry.todo_entry = item.text.clone();
+                    entry.set_done(false);
+                    changed[pos] = true;
+                    summary.updated += 1;
+                }
+                None => {
+                    let mut entry = Entry::new(item.text.clone(), false);
+                    entry.created = Some(today());
+                    entry.source = Some(item.source.clone());
+                    added.push(entry);
+                    summary.added += 1;
+                }
+            }
+        }
+
+        // Comentários que sumiram: a tarefa é concluída
+        for pos in 0..entries.len() {
+            if in_scope[pos] && !matched[pos] && !entries[pos].done {
+                entries[pos].set_done(true);
+                changed[pos] = true;
+                summary.closed += 1;
+            }
+        }
+
+        let mut data = String::new();
+        for ((line, entry), changed) in self.todo.iter().zip(&entries).zip(changed) {
+            if changed {
+                data.push_str(&entry.file_line());
+            } else {
+                data.push_str(&format!("{}\n", line));
+            }
+        }
+        for entry in &added {
+            data.push_str(&entry.file_line());
+        }
+
+        self.write_file(&data)?;
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{reload, temp_list};
+
+    fn marker(line: &str, file: &str) -> Option<String> {
+        find_marker(line, syntax_for(Path::new(file)))
+    }
+
+    #[test]
+    fn markers_are_found_in_comments() {
+        assert_eq!(marker("    // TODO: trocar o unwrap", "a.rs").as_deref(), Some("TODO trocar o unwrap"));
+        assert_eq!(marker("let x = 1; // FIXME(ana) - lento", "a.rs").as_deref(), Some("FIXME lento"));
+        assert_eq!(marker("/* XXX */", "a.c").as_deref(), Some("XXX"));
+        assert_eq!(marker("   * TODO continuar o bloco", "a.js").as_deref(), Some("TODO continuar o bloco"));
+        assert_eq!(marker("x = 1  # TODO remover", "a.py").as_deref(), Some("TODO remover"));
+        assert_eq!(marker("SELECT 1; -- TODO índice", "a.sql").as_deref(), Some("TODO índice"));
+        assert_eq!(marker("<!-- TODO traduzir -->", "a.html").as_deref(), Some("TODO traduzir"));
+        assert_eq!(marker("% TODO figura", "a.tex").as_deref(), Some("TODO figura"));
+        assert_eq!(marker("fn f<'a>(x: &'a str) {} // TODO vida", "a.rs").as_deref(), Some("TODO vida"));
+        assert_eq!(marker("# TODO em qualquer lugar", "LEIAME").as_deref(), Some("TODO em qualquer lugar"));
+    }
+
+    #[test]
+    fn markers_outside_comments_are_ignored() {
+        assert_eq!(marker("let todo = TODO_LIST;", "a.rs"), None);
+        assert_eq!(marker("printf(\"%d TODO\\n\", n);", "a.c"), None);
+        assert_eq!(marker("echo \"a -- TODO\"", "a.sh"), None);
+        assert_eq!(marker("query = 'a -- TODO'", "a.py"), None);
+        assert_eq!(marker("s = \"a \\\" // TODO\"", "a.rs"), None);
+        assert_eq!(marker("x = 5 % TODO", "a.py"), None);
+        assert_eq!(marker("Lista de TODO do projeto", "a.md"), None);
+        assert_eq!(marker("print(\"# TODO\")", "a.unknown"), None);
+        // Depois da string, o comentário conta
+        assert_eq!(marker("s = \"a\" // TODO b", "a.rs").as_deref(), Some("TODO b"));
+    }
+
+    #[test]
+    fn rescan_updates_moved_comments_and_closes_removed_ones() {
+        let (dir, todo) = temp_list(&["[ ] manual"]);
+        let code = dir.path().join("src");
+        fs::create_dir(&code).unwrap();
+        let file = code.join("main.rs");
+        fs::write(&file, "fn main() {}\n// TODO primeiro\n// FIXME segundo\nlet s = \"TODO não\";\n").unwrap();
+
+        let summary = todo.scan(dir.path()).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.closed), (2, 0, 0));
+        let todo = reload(&todo);
+        let entries: Vec<Entry> = todo.todo.iter().map(|line| Entry::read_line(line)).collect();
+        assert_eq!(entries[1].todo_entry, "TODO primeiro");
+        assert_eq!(entries[1].source.as_ref().unwrap().to_string(), "src/main.rs:2");
+        assert_eq!(entries[2].source.as_ref().unwrap().line, 3);
+
+        // Sem mudanças, nada muda
+        let summary = todo.scan(dir.path()).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.closed), (0, 0, 0));
+        assert_eq!(reload(&todo).todo, todo.todo);
+
+        // O primeiro subiu uma linha e o segundo mudou de texto na mesma linha
+        fs::write(&file, "// TODO primeiro\nfn main() {}\n// FIXME segundo, revisto\n").unwrap();
+        let summary = todo.scan(dir.path()).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.closed), (0, 2, 0));
+        let entries: Vec<Entry> = reload(&todo).todo.iter().map(|line| Entry::read_line(line)).collect();
+        assert_eq!(entries[1].source.as_ref().unwrap().line, 1);
+        assert_eq!(entries[2].todo_entry, "FIXME segundo, revisto");
+        assert_eq!(entries[2].source.as_ref().unwrap().line, 3);
+
+        // O comentário sumiu: a tarefa é concluída; a tarefa manual não é tocada
+        fs::write(&file, "fn main() {}\n\n// FIXME segundo, revisto\n").unwrap();
+        let summary = reload(&todo).scan(dir.path()).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.closed), (0, 0, 1));
+        let entries: Vec<Entry> = reload(&todo).todo.iter().map(|line| Entry::read_line(line)).collect();
+        let state: Vec<(&str, bool)> = entries.iter().map(|entry| (entry.todo_entry.as_str(), entry.done)).collect();
+        assert_eq!(state, [("manual", false), ("TODO primeiro", true), ("FIXME segundo, revisto", false)]);
+
+        // Se o comentário volta, a tarefa é reaberta
+        fs::write(&file, "// TODO primeiro\n\n// FIXME segundo, revisto\n").unwrap();
+        let summary = reload(&todo).scan(dir.path()).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.closed), (0, 1, 0));
+        assert!(reload(&todo).todo.iter().all(|line| !Entry::read_line(line).done));
+    }
+}
+
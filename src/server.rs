@@ -0,0 +1,621 @@
+use crate::{DATE_FORMAT, Entry, Todo, ical, sync};
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const DEFAULT_PORT: u16 = 7878;
+
+// Limite do corpo das requisições; as tarefas são texto curto
+const MAX_BODY: usize = 64 * 1024;
+
+pub struct ServeOptions {
+    pub host: String,
+    pub port: u16,
+    pub token: String,
+    pub list: Option<String>,
+}
+
+// Token aleatório para quando nenhum é informado (128 bits do gerador do sistema)
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// Compara sem parar no primeiro byte diferente, para o tempo não revelar o token
+fn same_token(given: &[u8], token: &[u8]) -> bool {
+    given.len() == token.len() && given.iter().zip(token).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+// A ETag muda sempre que o conteúdo da lista muda
+fn etag(todo: &Todo) -> String {
+    let mut hasher = DefaultHasher::new();
+    todo.todo.hash(&mut hasher);
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+#[derive(Serialize)]
+struct TaskJson {
+    index: String,
+    text: String,
+    done: bool,
+    depth: usize,
+    status: Option<String>,
+    due: Option<String>,
+    priority: Option<char>,
+    recur: Option<String>,
+    created: Option<String>,
+    completed: Option<String>,
+    note: Option<String>,
+}
+
+fn task_json(index: String, entry: Entry) -> TaskJson {
+    let date = |date: Option<chrono::NaiveDate>| date.map(|date| date.format(DATE_FORMAT).to_string());
+    TaskJson {
+        index,
+        done: entry.done,
+        depth: entry.depth,
+        due: date(entry.due),
+        created: date(entry.created),
+        completed: date(entry.completed),
+        priority: entry.priority,
+        recur: entry.recur.map(|recur| recur.to_string()),
+        status: entry.status,
+        note: entry.note,
+        text: entry.todo_entry,
+    }
+}
+
+#[derive(Deserialize)]
+struct AddBody {
+    text: String,
+    parent: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct EditBody {
+    text: String,
+}
+
+#[derive(Deserialize, Default)]
+struct ToggleBody {
+    #[serde(default)]
+    recursive: bool,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            content_type: "application/json; charset=utf-8",
+            body: body.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self::json(status, json!({ "error": message.into() }))
+    }
+
+    fn calendar(body: String) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            content_type: "text/calendar; charset=utf-8",
+            body,
+        }
+    }
+
+    fn multistatus(responses: &str) -> Self {
+        Self {
+            status: 207,
+            headers: Vec::new(),
+            content_type: "application/xml; charset=utf-8",
+            body: format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\" xmlns:cs=\"http://calendarserver.org/ns/\">\n{}</d:multistatus>\n",
+                responses
+            ),
+        }
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        207 => "Multi-Status",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        428 => "Precondition Required",
+        _ => "Internal Server Error",
+    }
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| Response::error(400, "Requisição inválida"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(Response::error(400, "Requisição inválida"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+            Err(_) => return Err(Response::error(400, "Cabeçalhos inválidos")),
+        }
+    }
+
+    let length: usize = headers.get("content-length").and_then(|len| len.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY {
+        return Err(Response::error(413, "Corpo da requisição grande demais"));
+    }
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| Response::error(400, "Corpo da requisição incompleto"))?;
+
+    Ok(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+fn write_response(mut stream: &TcpStream, response: Response) {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        reason(response.status),
+        response.content_type,
+        response.body.len()
+    );
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str("\r\n");
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(response.body.as_bytes());
+}
+
+fn parse_body<'a, T: Deserialize<'a>>(body: &'a [u8]) -> Result<T, Response> {
+    serde_json::from_slice(body).map_err(|e| Response::error(400, format!("JSON inválido: {}", e)))
+}
+
+fn list_response(todo: &Todo, status: u16) -> Response {
+    let tasks: Vec<TaskJson> = todo
+        .index_labels()
+        .into_iter()
+        .zip(&todo.todo)
+        .map(|(index, line)| task_json(index, Entry::read_line(line)))
+        .collect();
+    let etag = etag(todo);
+    let mut response = Response::json(status, json!({ "etag": etag, "tasks": tasks }));
+    response.headers.push(("ETag", etag));
+    response
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.trim().trim_end_matches('=').bytes() {
+        let value = BASE64.iter().position(|&c| c == byte)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+// Bearer TOKEN ou, para os apps de calendário, Basic com o token como senha (qualquer usuário)
+fn is_authorized(request: &Request, token: &str) -> bool {
+    let Some(value) = request.headers.get("authorization") else {
+        return false;
+    };
+    if let Some(bearer) = value.strip_prefix("Bearer ") {
+        return same_token(bearer.as_bytes(), token.as_bytes());
+    }
+    value
+        .strip_prefix("Basic ")
+        .and_then(decode_base64)
+        .and_then(|credentials| String::from_utf8(credentials).ok())
+        .is_some_and(|credentials| credentials.split_once(':').is_some_and(|(_, password)| same_token(password.as_bytes(), token.as_bytes())))
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+// A ETag de cada tarefa ignora o DTSTAMP, que muda a cada exportação
+fn item_etag(component: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    for line in component.lines().filter(|line| !line.starts_with("DTSTAMP:")) {
+        line.hash(&mut hasher);
+    }
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+fn item_href(uid: &str) -> String {
+    format!("/calendar/{}.ics", uid)
+}
+
+// CalDAV somente leitura: a coleção /calendar/ com um recurso .ics por tarefa
+fn propfind(todo: &Todo, depth: &str) -> Response {
+    let mut responses = format!(
+        "<d:response><d:href>/calendar/</d:href><d:propstat><d:prop>\
+         <d:resourcetype><d:collection/><c:calendar/></d:resourcetype>\
+         <d:displayname>todo-todo</d:displayname>\
+         <c:supported-calendar-component-set><c:comp name=\"VTODO\"/></c:supported-calendar-component-set>\
+         <cs:getctag>{}</cs:getctag><d:getetag>{}</d:getetag>\
+         </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>\n",
+        xml_escape(&etag(todo)),
+        xml_escape(&etag(todo))
+    );
+    if depth != "0" {
+        for (uid, component) in todo.vtodos() {
+            responses.push_str(&format!(
+                "<d:response><d:href>{}</d:href><d:propstat><d:prop>\
+                 <d:resourcetype/><d:getcontenttype>text/calendar; charset=utf-8; component=vtodo</d:getcontenttype>\
+                 <d:getetag>{}</d:getetag>\
+                 </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>\n",
+                xml_escape(&item_href(&uid)),
+                xml_escape(&item_etag(&component))
+            ));
+        }
+    }
+    Response::multistatus(&responses)
+}
+
+// calendar-query devolve todas as tarefas; calendar-multiget só as pedidas no corpo
+fn report(todo: &Todo, body: &[u8]) -> Response {
+    let body = String::from_utf8_lossy(body);
+    let multiget = body.contains("calendar-multiget");
+    let mut responses = String::new();
+    for (uid, component) in todo.vtodos() {
+        let href = item_href(&uid);
+        if multiget && !body.contains(&href) && !body.contains(&xml_escape(&href)) {
+            continue;
+        }
+        responses.push_str(&format!(
+            "<d:response><d:href>{}</d:href><d:propstat><d:prop>\
+             <d:getetag>{}</d:getetag><c:calendar-data>{}</c:calendar-data>\
+             </d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>\n",
+            xml_escape(&href),
+            xml_escape(&item_etag(&component)),
+            xml_escape(&ical::calendar(&[component]))
+        ));
+    }
+    Response::multistatus(&responses)
+}
+
+// O texto vira uma linha do arquivo: quebras de linha e outros caracteres de controle
+// criariam tarefas ou metadados a mais
+fn task_text(text: String) -> Result<String, Response> {
+    if text.chars().any(char::is_control) {
+        return Err(Response::error(400, "O texto da tarefa não pode ter quebras de linha nem caracteres de controle"));
+    }
+    Ok(text)
+}
+
+fn handle(request: &Request, options: &ServeOptions) -> Response {
+    if !is_authorized(request, &options.token) {
+        let mut response = Response::error(401, "Token ausente ou inválido (use Authorization: Bearer TOKEN)");
+        response.headers.push(("WWW-Authenticate", "Basic realm=\"todo-todo\"".to_string()));
+        return response;
+    }
+
+    match Todo::open_list(options.list.as_deref()) {
+        Ok(todo) => respond(request, &todo),
+        Err(e) => Response::error(500, e),
+    }
+}
+
+// Atende uma requisição já autorizada sobre a lista lida do disco
+fn respond(request: &Request, todo: &Todo) -> Response {
+    let segments: Vec<&str> = request
+        .path
+        .split('?')
+        .next()
+        .unwrap_or("")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let method = request.method.as_str();
+
+    // Alterações por índice exigem If-Match com a ETag da lista que o cliente viu
+    let needs_etag = !matches!(
+        (method, segments.as_slice()),
+        ("GET" | "OPTIONS" | "PROPFIND" | "REPORT", _) | ("POST", ["tasks"]) | (_, ["calendar", ..] | ["calendar.ics"])
+    );
+    if needs_etag {
+        match request.headers.get("if-match") {
+            None => return Response::error(428, "Envie If-Match com a ETag da última leitura"),
+            Some(tag) if *tag != etag(todo) && tag != "*" => {
+                return Response::error(412, "A lista mudou desde a última leitura; busque de novo");
+            }
+            Some(_) => {}
+        }
+    }
+    let exists = |index: &str| !todo.positions(&[index.to_string()]).is_empty();
+
+    let (command, args) = match (method, segments.as_slice()) {
+        ("GET", ["tasks"]) => return list_response(todo, 200),
+        ("GET", ["calendar.ics"]) => return Response::calendar(todo.to_ics()),
+        ("OPTIONS", _) => {
+            let mut response = Response::json(200, json!({}));
+            response.headers.push(("DAV", "1, calendar-access".to_string()));
+            response.headers.push(("Allow", "OPTIONS, GET, POST, PATCH, DELETE, PROPFIND, REPORT".to_string()));
+            return response;
+        }
+        ("PROPFIND", ["calendar"]) => {
+            let depth = request.headers.get("depth").map_or("0", String::as_str);
+            return propfind(todo, depth);
+        }
+        ("REPORT", ["calendar"]) => return report(todo, &request.body),
+        ("GET", ["calendar", file]) => {
+            // Alguns clientes codificam o '@' do UID
+            let uid = file.strip_suffix(".ics").unwrap_or(file).replace("%40", "@");
+            return match todo.vtodos().into_iter().find(|(item, _)| *item == uid) {
+                Some((_, component)) => {
+                    let mut response = Response::calendar(ical::calendar(std::slice::from_ref(&component)));
+                    response.headers.push(("ETag", item_etag(&component)));
+                    response
+                }
+                None => Response::error(404, format!("Tarefa {} não encontrada", uid)),
+            };
+        }
+        (_, ["calendar", ..] | ["calendar.ics"]) => return Response::error(405, "O calendário é somente leitura"),
+        ("POST", ["tasks"]) => match parse_body::<AddBody>(&request.body) {
+            Ok(AddBody { parent: Some(parent), .. }) if !exists(&parent) => {
+                return Response::error(404, format!("Tarefa {} não encontrada", parent));
+            }
+            Ok(AddBody { text, parent }) => match task_text(text) {
+                Ok(text) => match parent {
+                    None => ("mk", vec![text]),
+                    Some(parent) => ("sub", vec![parent, text]),
+                },
+                Err(response) => return response,
+            },
+            Err(response) => return response,
+        },
+        (_, ["tasks", index, ..]) if !exists(index) => {
+            return Response::error(404, format!("Tarefa {} não encontrada", index));
+        }
+        ("PATCH", ["tasks", index]) => match parse_body::<EditBody>(&request.body).and_then(|body| task_text(body.text)) {
+            Ok(text) => ("edit", vec![index.to_string(), text]),
+            Err(response) => return response,
+        },
+        ("DELETE", ["tasks", index]) => ("rm", vec![index.to_string()]),
+        ("POST", ["tasks", index, "toggle"]) => {
+            let body = if request.body.is_empty() {
+                Ok(ToggleBody::default())
+            } else {
+                parse_body::<ToggleBody>(&request.body)
+            };
+            match body {
+                Ok(body) => {
+                    let mut args = vec![index.to_string()];
+                    if body.recursive {
+                        args.push("-r".to_string());
+                    }
+                    ("done", args)
+                }
+                Err(response) => return response,
+            }
+        }
+        ("POST", ["sort"]) => ("sort", Vec::new()),
+        ("POST", ["reset"]) => ("reset", Vec::new()),
+        (_, ["tasks", ..] | ["sort"] | ["reset"]) => return Response::error(405, "Método não suportado"),
+        _ => return Response::error(404, "Rota desconhecida"),
+    };
+
+    // Como na CLI: a mensagem do sync é descrita antes de aplicar, e depois vêm
+    // o arquivamento automático e o commit
+    let message = sync::describe(todo, command, &args);
+    if let Err(e) = todo.apply(command, &args) {
+        return Response::error(400, e);
+    }
+    let changed = todo.reload().and_then(|changed| {
+        if changed.todo == todo.todo {
+            return Ok(changed);
+        }
+        changed.auto_archive()?;
+        sync::record(&changed, &message)?;
+        changed.reload()
+    });
+    // A resposta traz a lista atualizada e a nova ETag
+    match changed {
+        Ok(changed) => list_response(&changed, if command == "mk" || command == "sub" { 201 } else { 200 }),
+        Err(e) => Response::error(500, e),
+    }
+}
+
+// Atende até o processo ser encerrado. Uma requisição por vez mexe no arquivo,
+// para a checagem da ETag e a gravação acontecerem juntas.
+pub fn serve(options: ServeOptions) -> Result<(), String> {
+    let address = format!("{}:{}", options.host, options.port);
+    let listener = TcpListener::bind(&address).map_err(|e| format!("Não foi possível escutar em {}: {}", address, e))?;
+    println!("Servindo a API em http://{}", address);
+
+    let options = Arc::new(options);
+    let lock = Arc::new(Mutex::new(()));
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let options = Arc::clone(&options);
+        let lock = Arc::clone(&lock);
+        thread::spawn(move || {
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(10)));
+            let response = match read_request(&stream) {
+                Ok(request) => {
+                    let _guard = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    handle(&request, &options)
+                }
+                Err(response) => response,
+            };
+            write_response(&stream, response);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{temp_list, texts};
+
+    fn request(method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> Request {
+        Request {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn random_tokens_are_128_bit_hex() {
+        let (a, b) = (random_token(), random_token());
+        assert_eq!(a.len(), 32);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn authorization_accepts_bearer_and_basic() {
+        let token = "segredo";
+        let authorized = |value: &str| is_authorized(&request("GET", "/tasks", &[("authorization", value)], ""), token);
+        assert!(authorized("Bearer segredo"));
+        assert!(!authorized("Bearer segred"));
+        assert!(!authorized("Bearer segredo2"));
+        // "qualquer:segredo" em base64
+        assert!(authorized("Basic cXVhbHF1ZXI6c2VncmVkbw=="));
+        assert!(!authorized("Basic cXVhbHF1ZXI6b3V0cm8="));
+        assert!(!is_authorized(&request("GET", "/tasks", &[], ""), token));
+        assert!(same_token(b"abc", b"abc") && !same_token(b"abc", b"abd") && !same_token(b"ab", b"abc"));
+    }
+
+    #[test]
+    fn unauthorized_requests_are_rejected_before_reading_the_list() {
+        let options = ServeOptions {
+            host: "127.0.0.1".to_string(),
+            port: 0,
+            token: "segredo".to_string(),
+            list: Some("lista-que-não-existe".to_string()),
+        };
+        assert_eq!(handle(&request("GET", "/tasks", &[], ""), &options).status, 401);
+    }
+
+    #[test]
+    fn task_text_with_control_characters_is_rejected() {
+        let (_dir, todo) = temp_list(&["[ ] a"]);
+        let body = r#"{"text": "b\n[ ] injetada status:x"}"#;
+        assert_eq!(respond(&request("POST", "/tasks", &[], body), &todo).status, 400);
+        let patch = request("PATCH", "/tasks/1", &[("if-match", "*")], r#"{"text": "a\tb"}"#);
+        assert_eq!(respond(&patch, &todo).status, 400);
+        assert_eq!(texts(&todo.reload().unwrap()), ["a"]);
+    }
+
+    #[test]
+    fn changes_return_the_updated_list() {
+        let (_dir, todo) = temp_list(&["[ ] a"]);
+        let response = respond(&request("POST", "/tasks", &[], r#"{"text": "b", "parent": "1"}"#), &todo);
+        assert_eq!(response.status, 201);
+        let todo = todo.reload().unwrap();
+        assert_eq!(todo.index_labels(), ["1", "1.1"]);
+
+        let stale = request("DELETE", "/tasks/1.1", &[("if-match", "\"velha\"")], "");
+        assert_eq!(respond(&stale, &todo).status, 412);
+        let delete = request("DELETE", "/tasks/1.1", &[("if-match", &etag(&todo))], "");
+        assert_eq!(respond(&delete, &todo).status, 200);
+        assert_eq!(texts(&todo.reload().unwrap()), ["a"]);
+    }
+
+    #[test]
+    fn caldav_lists_every_task_with_a_stable_href() {
+        let (_dir, todo) = temp_list(&["[ ] a", "  [ ] b", "[ ] c id:7"]);
+        let original = todo.todo.clone();
+        let response = respond(&request("PROPFIND", "/calendar/", &[("depth", "1")], ""), &todo);
+        assert_eq!(response.status, 207);
+        for (uid, _) in todo.vtodos() {
+            let href = format!("<d:href>/calendar/{}.ics</d:href>", uid);
+            assert!(response.body.contains(&href), "{}", href);
+        }
+        assert!(response.body.contains("<d:href>/calendar/todo-todo-7@todo-todo.ics</d:href>"));
+        // Ler o calendário não grava nada, e a próxima leitura devolve os mesmos UIDs
+        let todo = todo.reload().unwrap();
+        assert_eq!(todo.todo, original);
+        let again = respond(&request("PROPFIND", "/calendar/", &[("depth", "1")], ""), &todo);
+        assert_eq!(again.body, response.body);
+
+        let depth_zero = respond(&request("PROPFIND", "/calendar/", &[], ""), &todo);
+        assert!(!depth_zero.body.contains(".ics"));
+    }
+
+    #[test]
+    fn caldav_report_returns_the_requested_tasks() {
+        let (_dir, todo) = temp_list(&["[ ] a id:1", "  [ ] b id:2"]);
+        let query = respond(&request("REPORT", "/calendar/", &[], "<c:calendar-query/>"), &todo);
+        assert_eq!(query.status, 207);
+        assert!(query.body.contains("SUMMARY:a") && query.body.contains("SUMMARY:b"));
+        assert!(query.body.contains("RELATED-TO;RELTYPE=PARENT:todo-todo-1@todo-todo"));
+
+        let body = "<c:calendar-multiget><d:href>/calendar/todo-todo-2@todo-todo.ics</d:href></c:calendar-multiget>";
+        let multiget = respond(&request("REPORT", "/calendar/", &[], body), &todo);
+        assert!(multiget.body.contains("SUMMARY:b"));
+        assert!(!multiget.body.contains("SUMMARY:a"));
+
+        let item = respond(&request("GET", "/calendar/todo-todo-2%40todo-todo.ics", &[], ""), &todo);
+        assert_eq!(item.status, 200);
+        assert!(item.headers.iter().any(|(name, _)| *name == "ETag"));
+        let put = request("PUT", "/calendar/todo-todo-2@todo-todo.ics", &[], "");
+        assert_eq!(respond(&put, &todo).status, 405);
+    }
+}
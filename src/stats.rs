@@ -0,0 +1,315 @@
+use crate::{DATE_FORMAT, Entry, Todo, read_archive, today};
+use chrono::{Datelike, Days, NaiveDate};
+use colored::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// Quantos dias e semanas aparecem nos gráficos de tarefas criadas x concluídas
+const DAYS_SHOWN: u64 = 14;
+const WEEKS_SHOWN: u64 = 8;
+
+// Largura máxima das barras no terminal
+const BAR_WIDTH: usize = 30;
+
+#[derive(Serialize)]
+pub struct Period {
+    pub start: String,
+    pub added: usize,
+    pub completed: usize,
+}
+
+#[derive(Serialize)]
+pub struct Breakdown {
+    pub name: String,
+    pub pending: usize,
+    pub done: usize,
+}
+
+// Resumo da lista e do arquivo morto
+#[derive(Serialize)]
+pub struct Stats {
+    pub pending: usize,
+    pub done: usize,
+    pub archived: usize,
+    pub completion_rate: f64,
+    pub average_days_to_complete: Option<f64>,
+    pub days: Vec<Period>,
+    pub weeks: Vec<Period>,
+    pub by_tag: Vec<Breakdown>,
+    pub by_priority: Vec<Breakdown>,
+}
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Days::new(day.weekday().num_days_from_monday() as u64)
+}
+
+// Tarefas criadas e concluídas em cada período de `length` dias a partir de `starts`
+fn periods(entries: &[Entry], starts: Vec<NaiveDate>, length: u64) -> Vec<Period> {
+    let within = |day: Option<NaiveDate>, start: NaiveDate| {
+        day.is_some_and(|day| day >= start && day < start + Days::new(length))
+    };
+    starts
+        .into_iter()
+        .map(|start| Period {
+            start: start.format(DATE_FORMAT).to_string(),
+            added: entries.iter().filter(|entry| within(entry.created, start)).count(),
+            completed: entries.iter().filter(|entry| within(entry.completed, start)).count(),
+        })
+        .collect()
+}
+
+fn breakdown<'a>(entries: &'a [Entry], keys: impl Fn(&'a Entry) -> Vec<String>) -> Vec<Breakdown> {
+    let mut groups: BTreeMap<String, Breakdown> = BTreeMap::new();
+    for entry in entries {
+        for name in keys(entry) {
+            let group = groups.entry(name.clone()).or_insert(Breakdown {
+                name,
+                pending: 0,
+                done: 0,
+            });
+            if entry.done {
+                group.done += 1;
+            } else {
+                group.pending += 1;
+            }
+        }
+    }
+    groups.into_values().collect()
+}
+
+impl Stats {
+    // Tarefas arquivadas contam como concluídas
+    pub fn collect(todo_lines: &[String], archive_lines: &[String], today: NaiveDate) -> Self {
+        let current: Vec<Entry> = todo_lines.iter().map(|line| Entry::read_line(line)).collect();
+        let archived: Vec<Entry> = read_archive(archive_lines)
+            .into_iter()
+            .flat_map(|task| task.lines)
+            .collect();
+
+        let pending = current.iter().filter(|entry| !entry.done).count();
+        let done = current.len() - pending;
+        let total = current.len() + archived.len();
+        let completion_rate = if total == 0 {
+            0.0
+        } else {
+            (done + archived.len()) as f64 / total as f64
+        };
+
+        let mut entries = current;
+        entries.extend(archived.into_iter().map(|mut entry| {
+            entry.done = true;
+            entry
+        }));
+
+        let durations: Vec<i64> = entries
+            .iter()
+            .filter_map(|entry| Some((entry.completed? - entry.created?).num_days()))
+            .collect();
+        let average_days_to_complete = if durations.is_empty() {
+            None
+        } else {
+            Some(durations.iter().sum::<i64>() as f64 / durations.len() as f64)
+        };
+
+        let days = (0..DAYS_SHOWN).rev().map(|back| today - Days::new(back)).collect();
+        let this_week = monday_of(today);
+        let weeks = (0..WEEKS_SHOWN).rev().map(|back| this_week - Days::new(7 * back)).collect();
+
+        let by_tag = breakdown(&entries, |entry| entry.tags().iter().map(|tag| tag.to_string()).collect());
+        let by_priority = breakdown(&entries, |entry| {
+            vec![entry.priority.map_or("sem prioridade".to_string(), |priority| priority.to_string())]
+        });
+
+        Self {
+            pending,
+            done,
+            archived: total - pending - done,
+            completion_rate,
+            average_days_to_complete,
+            days: periods(&entries, days, 1),
+            weeks: periods(&entries, weeks, 7),
+            by_tag,
+            by_priority,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Erro ao gerar JSON: {}", e))
+    }
+
+    pub fn report(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("{}\n", "Resumo".bold()));
+        out.push_str(&format!("  Pendentes:   {}\n", self.pending));
+        out.push_str(&format!("  Concluídas:  {}\n", self.done));
+        out.push_str(&format!("  Arquivadas:  {}\n", self.archived));
+        out.push_str(&format!("  Conclusão:   {:.0}%\n", self.completion_rate * 100.0));
+        match self.average_days_to_complete {
+            Some(days) => out.push_str(&format!("  Tempo médio até concluir: {:.1} dia(s)\n", days)),
+            None => out.push_str("  Tempo médio até concluir: sem dados\n"),
+        }
+
+        let max = self
+            .days
+            .iter()
+            .chain(&self.weeks)
+            .map(|period| period.added.max(period.completed))
+            .max()
+            .unwrap_or(0);
+        out.push_str(&format!("\n{} ({} criadas, {} concluídas)\n", "Por dia".bold(), "█".blue(), "█".green()));
+        for period in &self.days {
+            out.push_str(&period_rows(period, max));
+        }
+        out.push_str(&format!("\n{}\n", "Por semana (a partir da segunda)".bold()));
+        for period in &self.weeks {
+            out.push_str(&period_rows(period, max));
+        }
+
+        for (title, groups) in [("Por tag", &self.by_tag), ("Por prioridade", &self.by_priority)] {
+            out.push_str(&format!("\n{} ({} pendentes, {} concluídas)\n", title.bold(), "█".yellow(), "█".green()));
+            if groups.is_empty() {
+                out.push_str("  nenhuma\n");
+            }
+            let max = groups.iter().map(|group| group.pending + group.done).max().unwrap_or(0);
+            let width = groups.iter().map(|group| group.name.chars().count()).max().unwrap_or(0);
+            for group in groups {
+                out.push_str(&format!(
+                    "  {:<width$} {}{} {}/{}\n",
+                    group.name,
+                    bar(group.pending, max).yellow(),
+                    bar(group.done, max).green(),
+                    group.pending,
+                    group.done,
+                ));
+            }
+        }
+        out
+    }
+}
+
+fn bar(value: usize, max: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    "█".repeat((value * BAR_WIDTH).div_ceil(max))
+}
+
+fn period_rows(period: &Period, max: usize) -> String {
+    format!(
+        "  {} {} {}\n  {} {} {}\n",
+        period.start,
+        bar(period.added, max).blue(),
+        period.added,
+        " ".repeat(period.start.len()),
+        bar(period.completed, max).green(),
+        period.completed,
+    )
+}
+
+impl Todo {
+    pub fn stats(&self) -> Stats {
+        Stats::collect(&self.todo, &self.archived_lines(), today())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::temp_list;
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, DATE_FORMAT).unwrap()
+    }
+
+    fn counts(periods: &[Period]) -> Vec<(&str, usize, usize)> {
+        periods.iter().map(|period| (period.start.as_str(), period.added, period.completed)).collect()
+    }
+
+    // Quarta-feira; a semana começa na segunda, 2026-05-04
+    const TODAY: &str = "2026-05-06";
+
+    fn sample() -> Stats {
+        let todo = lines(&[
+            "[ ] pendente #casa pri:A added:2026-05-04",
+            "[*] feita #casa #mercado added:2026-05-01 done:2026-05-05",
+            "[*] feita hoje added:2026-05-06 done:2026-05-06",
+            "[ ] antiga added:2026-03-01",
+        ]);
+        let archive = lines(&[
+            "[*] arquivada #casa pri:B added:2026-04-20 done:2026-04-30",
+            "  [ ] subtarefa arquivada added:2026-04-20",
+        ]);
+        Stats::collect(&todo, &archive, date(TODAY))
+    }
+
+    #[test]
+    fn counts_and_rates_include_the_archive() {
+        let stats = sample();
+        assert_eq!((stats.pending, stats.done, stats.archived), (2, 2, 2));
+        assert_eq!(stats.completion_rate, 4.0 / 6.0);
+        // (5 - 1) + (6 - 6) + (30 - 20) dias, em três tarefas com as duas datas
+        assert_eq!(stats.average_days_to_complete, Some(14.0 / 3.0));
+
+        let empty = Stats::collect(&[], &[], date(TODAY));
+        assert_eq!(empty.completion_rate, 0.0);
+        assert_eq!(empty.average_days_to_complete, None);
+    }
+
+    #[test]
+    fn added_and_completed_are_counted_per_day_and_week() {
+        let stats = sample();
+        assert_eq!(stats.days.len(), DAYS_SHOWN as usize);
+        assert_eq!(stats.days.first().unwrap().start, "2026-04-23");
+        assert_eq!(counts(&stats.days[stats.days.len() - 3..]), [
+            ("2026-05-04", 1, 0),
+            ("2026-05-05", 0, 1),
+            ("2026-05-06", 1, 1),
+        ]);
+
+        assert_eq!(stats.weeks.len(), WEEKS_SHOWN as usize);
+        assert_eq!(counts(&stats.weeks[stats.weeks.len() - 3..]), [
+            ("2026-04-20", 2, 0),
+            ("2026-04-27", 1, 1),
+            ("2026-05-04", 2, 2),
+        ]);
+        // A semana de 2026-03-01 (um domingo) ficou fora das oito mostradas
+        assert_eq!(stats.weeks[0].start, "2026-03-16");
+        assert_eq!(stats.weeks.iter().map(|week| week.added).sum::<usize>(), 5);
+    }
+
+    #[test]
+    fn tags_and_priorities_are_broken_down() {
+        let stats = sample();
+        let groups = |groups: &[Breakdown]| -> Vec<(String, usize, usize)> {
+            groups.iter().map(|group| (group.name.clone(), group.pending, group.done)).collect()
+        };
+        assert_eq!(groups(&stats.by_tag), [("#casa".to_string(), 1, 2), ("#mercado".to_string(), 0, 1)]);
+        // Tudo que foi arquivado conta como concluído, inclusive a subtarefa pendente
+        assert_eq!(groups(&stats.by_priority), [
+            ("A".to_string(), 1, 0),
+            ("B".to_string(), 0, 1),
+            ("sem prioridade".to_string(), 1, 3),
+        ]);
+    }
+
+    #[test]
+    fn the_report_and_json_show_the_same_numbers() {
+        let stats = sample();
+        let json: serde_json::Value = serde_json::from_str(&stats.to_json().unwrap()).unwrap();
+        assert_eq!(json["pending"], 2);
+        assert_eq!(json["weeks"][7]["completed"], 2);
+        colored::control::set_override(false);
+        let report = stats.report();
+        assert!(report.contains("Pendentes:   2\n") && report.contains("Conclusão:   67%\n"));
+        assert!(report.contains("Tempo médio até concluir: 4.7 dia(s)"));
+
+        let (_dir, todo) = temp_list(&["[ ] a", "[*] b"]);
+        let stats = todo.stats();
+        assert_eq!((stats.pending, stats.done, stats.archived), (1, 1, 0));
+    }
+}
+
@@ -0,0 +1,708 @@
+use crate::crypto::{self, Key, Salt};
+use crate::diff::{Change, diff};
+use crate::{DATE_FORMAT, Entry, Todo};
+use chrono::{Local, NaiveDate};
+use rusqlite::{Connection, params};
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Onde as linhas de uma lista ficam guardadas. O formato de texto de sempre é o padrão;
+// o SQLite guarda também o histórico de alterações e o criptografado cifra o texto inteiro.
+
+// Começo de todo arquivo SQLite
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    Text,
+    Sqlite,
+    // Só se chega a ele com `todo encrypt`, que pede a senha
+    Encrypted,
+}
+
+impl Backend {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "text" | "texto" => Ok(Self::Text),
+            "sqlite" => Ok(Self::Sqlite),
+            _ => Err(format!("Formato desconhecido '{}', use text ou sqlite", name)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Text => "text",
+            Self::Sqlite => "sqlite",
+            Self::Encrypted => "encrypted",
+        }
+    }
+
+    // Formato das listas novas (TODO_BACKEND); o padrão é o de texto
+    pub fn configured() -> Self {
+        env::var("TODO_BACKEND")
+            .ok()
+            .and_then(|name| Self::parse(&name).ok())
+            .unwrap_or(Self::Text)
+    }
+
+    // Formato de um arquivo existente, pelo cabeçalho; vazio ou inexistente segue o configurado
+    pub fn detect(path: &Path) -> Self {
+        let mut header = [0; SQLITE_HEADER.len()];
+        match File::open(path).and_then(|mut file| file.read(&mut header)) {
+            Ok(0) | Err(_) => Self::configured(),
+            Ok(_) if header == SQLITE_HEADER => Self::Sqlite,
+            Ok(_) if crypto::is_encrypted(&header) => Self::Encrypted,
+            Ok(_) => Self::Text,
+        }
+    }
+}
+
+// Uma alteração registrada no histórico do SQLite
+pub struct HistoryEntry {
+    pub at: String,
+    pub added: bool,
+    pub line: String,
+}
+
+pub trait Storage {
+    // Linhas da lista, criando o armazenamento vazio se ele não existir
+    fn load(&self) -> Result<Vec<String>, String>;
+    // Substitui a lista inteira
+    fn save(&self, lines: &[String]) -> Result<(), String>;
+    // Acrescenta linhas no fim
+    fn append(&self, lines: &[String]) -> Result<(), String>;
+    // Esvazia a lista (reset)
+    fn clear(&self) -> Result<(), String>;
+    fn backup(&self) -> Result<(), String>;
+    fn restore(&self) -> Result<(), String>;
+
+    // Linhas das tarefas pendentes com vencimento entre `from` e `until`, na ordem da lista
+    fn pending_due(&self, from: NaiveDate, until: NaiveDate) -> Result<Vec<String>, String> {
+        let lines = self.load()?;
+        Ok(lines
+            .into_iter()
+            .filter(|line| {
+                let entry = Entry::read_line(line);
+                !entry.done && entry.due.is_some_and(|due| from <= due && due <= until)
+            })
+            .collect())
+    }
+
+    // Últimas alterações, da mais recente para a mais antiga
+    fn history(&self, _limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        Err("O histórico de alterações só existe no formato SQLite (veja todo migrate)".to_string())
+    }
+}
+
+pub fn open(backend: Backend, path: &Path, backup: &Path) -> Box<dyn Storage> {
+    match backend {
+        Backend::Text => Box::new(TextStorage {
+            path: path.to_path_buf(),
+            backup: backup.to_path_buf(),
+            keep_file: false,
+        }),
+        Backend::Sqlite => Box::new(SqliteStorage {
+            path: path.to_path_buf(),
+        }),
+        Backend::Encrypted => Box::new(EncryptedStorage {
+            path: path.to_path_buf(),
+            backup: backup.to_path_buf(),
+            salt_from: path.to_path_buf(),
+        }),
+    }
+}
+
+fn to_data(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+}
+
+// Uma tarefa por linha, com os metadados no fim (o formato original)
+pub struct TextStorage {
+    path: PathBuf,
+    backup: PathBuf,
+    // O reset esvazia o arquivo em vez de apagá-lo
+    keep_file: bool,
+}
+
+impl Storage for TextStorage {
+    fn load(&self) -> Result<Vec<String>, String> {
+        // Cria o arquivo se não existir
+        let todofile = OpenOptions::new()
+            .write(true)
+            .read(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .map_err(|e| format!("Não foi possível abrir o arquivo TODO: {}", e))?;
+
+        let mut buf_reader = BufReader::new(&todofile);
+        let mut contents = String::new();
+        buf_reader
+            .read_to_string(&mut contents)
+            .map_err(|e| format!("Erro ao ler o arquivo TODO: {}", e))?;
+        Ok(contents.lines().map(str::to_string).collect())
+    }
+
+    fn save(&self, lines: &[String]) -> Result<(), String> {
+        let mut todofile = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)
+            .map_err(|e| format!("Não foi possível abrir o arquivo: {}", e))?;
+
+        todofile
+            .write_all(to_data(lines).as_bytes())
+            .map_err(|e| format!("Falha na gravação: {}", e))
+    }
+
+    fn append(&self, lines: &[String]) -> Result<(), String> {
+        let mut todofile = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Não foi possível abrir o arquivo: {}", e))?;
+
+        todofile
+            .write_all(to_data(lines).as_bytes())
+            .map_err(|e| format!("Falha na gravação: {}", e))
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        if self.keep_file {
+            return self.save(&[]);
+        }
+        fs::remove_file(&self.path).map_err(|e| format!("Erro ao apagar arquivo: {}", e))
+    }
+
+    fn backup(&self) -> Result<(), String> {
+        fs::copy(&self.path, &self.backup)
+            .map(|_| ())
+            .map_err(|_| "Não foi possível fazer backup do arquivo".to_string())
+    }
+
+    fn restore(&self) -> Result<(), String> {
+        fs::copy(&self.backup, &self.path)
+            .map(|_| ())
+            .map_err(|e| format!("Não foi possível restaurar o arquivo de backup: {}", e))
+    }
+}
+
+// O formato de texto cifrado inteiro a cada gravação (veja `crypto`)
+pub struct EncryptedStorage {
+    path: PathBuf,
+    backup: PathBuf,
+    // Arquivo de onde vem o sal da chave: a própria lista, ou a lista dona do arquivo morto
+    salt_from: PathBuf,
+}
+
+fn write_encrypted(path: &Path, salt: &Salt, key: &Key, lines: &[String]) -> Result<(), String> {
+    let data = crypto::encrypt(key, salt, to_data(lines).as_bytes())?;
+    // Grava ao lado e renomeia: uma falha no meio não deixa a lista pela metade
+    let mut temp = path.to_path_buf().into_os_string();
+    temp.push(".tmp");
+    fs::write(&temp, data).map_err(|e| format!("Falha na gravação: {}", e))?;
+    fs::rename(&temp, path).map_err(|e| format!("Falha na gravação: {}", e))
+}
+
+// Conteúdo do arquivo criptografado e a chave que o abriu
+fn open_encrypted(path: &Path) -> Result<(Key, Vec<u8>), String> {
+    let data = fs::read(path).map_err(|e| format!("Não foi possível abrir o arquivo TODO: {}", e))?;
+    crypto::open(&data)
+}
+
+fn read_encrypted(path: &Path) -> Result<Vec<String>, String> {
+    let (_, plaintext) = open_encrypted(path)?;
+    let contents = String::from_utf8(plaintext).map_err(|e| format!("Erro ao ler o arquivo TODO: {}", e))?;
+    Ok(contents.lines().map(str::to_string).collect())
+}
+
+impl Storage for EncryptedStorage {
+    fn load(&self) -> Result<Vec<String>, String> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        read_encrypted(&self.path)
+    }
+
+    fn save(&self, lines: &[String]) -> Result<(), String> {
+        // Só grava com uma chave que abre a lista: uma senha errada não cifra o arquivo com outra chave
+        let salt = crypto::read_salt(&self.salt_from)?;
+        let (key, _) = open_encrypted(&self.salt_from)?;
+        write_encrypted(&self.path, &salt, &key, lines)
+    }
+
+    fn append(&self, lines: &[String]) -> Result<(), String> {
+        let mut all = self.load()?;
+        all.extend_from_slice(lines);
+        self.save(&all)
+    }
+
+    // A lista vazia continua criptografada
+    fn clear(&self) -> Result<(), String> {
+        self.save(&[])
+    }
+
+    fn backup(&self) -> Result<(), String> {
+        fs::copy(&self.path, &self.backup)
+            .map(|_| ())
+            .map_err(|_| "Não foi possível fazer backup do arquivo".to_string())
+    }
+
+    fn restore(&self) -> Result<(), String> {
+        fs::copy(&self.backup, &self.path)
+            .map(|_| ())
+            .map_err(|e| format!("Não foi possível restaurar o arquivo de backup: {}", e))
+    }
+}
+
+// Banco SQLite no caminho da lista: as tarefas com os metadados em colunas indexadas,
+// o backup numa tabela própria e cada linha adicionada ou removida no histórico
+pub struct SqliteStorage {
+    path: PathBuf,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS tasks (
+        position INTEGER PRIMARY KEY,
+        line TEXT NOT NULL,
+        text TEXT NOT NULL,
+        done INTEGER NOT NULL,
+        depth INTEGER NOT NULL,
+        status TEXT,
+        due TEXT,
+        created TEXT,
+        completed TEXT,
+        priority TEXT,
+        task_id INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS tasks_done ON tasks (done);
+    CREATE INDEX IF NOT EXISTS tasks_due ON tasks (due);
+    CREATE INDEX IF NOT EXISTS tasks_priority ON tasks (priority);
+    CREATE INDEX IF NOT EXISTS tasks_status ON tasks (status);
+    CREATE TABLE IF NOT EXISTS backup (
+        position INTEGER PRIMARY KEY,
+        line TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        at TEXT NOT NULL,
+        added INTEGER NOT NULL,
+        line TEXT NOT NULL
+    );
+";
+
+fn sql_error(e: rusqlite::Error) -> String {
+    format!("Erro no banco SQLite: {}", e)
+}
+
+fn select_lines(conn: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut statement = conn
+        .prepare(&format!("SELECT line FROM {} ORDER BY position", table))
+        .map_err(sql_error)?;
+    let lines = statement
+        .query_map([], |row| row.get(0))
+        .map_err(sql_error)?
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(sql_error)?;
+    Ok(lines)
+}
+
+// Troca as linhas a partir de `start`, que é o começo do trecho alterado
+fn write_tasks(conn: &Connection, start: usize, lines: &[String]) -> Result<(), String> {
+    conn.execute("DELETE FROM tasks WHERE position >= ?1", params![start as i64])
+        .map_err(sql_error)?;
+    let mut insert = conn
+        .prepare(
+            "INSERT INTO tasks (position, line, text, done, depth, status, due, created, completed, priority, task_id)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        )
+        .map_err(sql_error)?;
+    let date = |date: Option<chrono::NaiveDate>| date.map(|date| date.format(DATE_FORMAT).to_string());
+    for (pos, line) in lines.iter().enumerate().skip(start) {
+        let entry = Entry::read_line(line);
+        insert
+            .execute(params![
+                pos as i64,
+                line,
+                entry.todo_entry,
+                entry.done,
+                entry.depth as i64,
+                entry.status,
+                date(entry.due),
+                date(entry.created),
+                date(entry.completed),
+                entry.priority.map(String::from),
+                entry.id,
+            ])
+            .map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+fn record_history(conn: &Connection, old: &[String], new: &[String]) -> Result<(), String> {
+    let changes: Vec<(bool, &str)> = diff(old, new)
+        .into_iter()
+        .filter_map(|change| match change {
+            Change::Same(_) => None,
+            Change::Added(line) => Some((true, line)),
+            Change::Removed(line) => Some((false, line)),
+        })
+        .collect();
+
+    let at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let mut insert = conn
+        .prepare("INSERT INTO history (at, added, line) VALUES (?1, ?2, ?3)")
+        .map_err(sql_error)?;
+    for (added, line) in changes {
+        insert.execute(params![at, added, line]).map_err(sql_error)?;
+    }
+    Ok(())
+}
+
+impl SqliteStorage {
+    fn connect(&self) -> Result<Connection, String> {
+        let conn = Connection::open(&self.path)
+            .map_err(|e| format!("Não foi possível abrir o banco {}: {}", self.path.display(), e))?;
+        conn.execute_batch(SCHEMA).map_err(sql_error)?;
+        Ok(conn)
+    }
+
+    // Cada gravação é uma transação: a lista e o histórico mudam juntos ou não mudam
+    fn replace(&self, lines: &[String]) -> Result<(), String> {
+        let mut conn = self.connect()?;
+        let transaction = conn.transaction().map_err(sql_error)?;
+        let old = select_lines(&transaction, "tasks")?;
+        let start = old.iter().zip(lines).take_while(|(a, b)| a == b).count();
+        if start == old.len() && start == lines.len() {
+            return Ok(());
+        }
+        record_history(&transaction, &old, lines)?;
+        write_tasks(&transaction, start, lines)?;
+        transaction.commit().map_err(sql_error)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self) -> Result<Vec<String>, String> {
+        select_lines(&self.connect()?, "tasks")
+    }
+
+    fn save(&self, lines: &[String]) -> Result<(), String> {
+        self.replace(lines)
+    }
+
+    fn append(&self, lines: &[String]) -> Result<(), String> {
+        let mut all = self.load()?;
+        all.extend_from_slice(lines);
+        self.replace(&all)
+    }
+
+    fn clear(&self) -> Result<(), String> {
+        self.replace(&[])
+    }
+
+    fn backup(&self) -> Result<(), String> {
+        let mut conn = self.connect()?;
+        let transaction = conn.transaction().map_err(sql_error)?;
+        transaction
+            .execute_batch("DELETE FROM backup; INSERT INTO backup (position, line) SELECT position, line FROM tasks;")
+            .map_err(sql_error)?;
+        transaction.commit().map_err(sql_error)
+    }
+
+    fn restore(&self) -> Result<(), String> {
+        let backup = select_lines(&self.connect()?, "backup")?;
+        self.replace(&backup)
+    }
+
+    // Pelo índice do vencimento, sem ler a lista inteira (as datas em texto ordenam como datas)
+    fn pending_due(&self, from: NaiveDate, until: NaiveDate) -> Result<Vec<String>, String> {
+        let conn = self.connect()?;
+        let mut statement = conn
+            .prepare("SELECT line FROM tasks WHERE due BETWEEN ?1 AND ?2 AND done = 0 ORDER BY position")
+            .map_err(sql_error)?;
+        let lines = statement
+            .query_map(
+                params![from.format(DATE_FORMAT).to_string(), until.format(DATE_FORMAT).to_string()],
+                |row| row.get(0),
+            )
+            .map_err(sql_error)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(sql_error)?;
+        Ok(lines)
+    }
+
+    fn history(&self, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.connect()?;
+        let mut statement = conn
+            .prepare("SELECT at, added, line FROM history ORDER BY id DESC LIMIT ?1")
+            .map_err(sql_error)?;
+        let entries = statement
+            .query_map(params![limit as i64], |row| {
+                Ok(HistoryEntry {
+                    at: row.get(0)?,
+                    added: row.get(1)?,
+                    line: row.get(2)?,
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(sql_error)?;
+        Ok(entries)
+    }
+}
+
+impl Todo {
+    pub fn backend(&self) -> Backend {
+        Backend::detect(&self.todo_path)
+    }
+
+    pub fn storage(&self) -> Box<dyn Storage> {
+        match self.backend() {
+            // Apagar o .todo de um projeto faria os próximos comandos caírem no TODO principal
+            Backend::Text if self.project => Box::new(TextStorage {
+                path: self.todo_path.clone(),
+                backup: self.todo_bak.clone(),
+                keep_file: true,
+            }),
+            backend => open(backend, &self.todo_path, &self.todo_bak),
+        }
+    }
+
+    // O arquivo morto é sempre texto, cifrado com a chave da lista se ela for criptografada.
+    // Ele não tem backup: o caminho de backup não é usado.
+    pub fn archive_storage(&self) -> Box<dyn Storage> {
+        let path = self.archive_path();
+        let backend = if path.exists() { Backend::detect(&path) } else { self.backend() };
+        match backend {
+            Backend::Encrypted => Box::new(EncryptedStorage {
+                path: path.clone(),
+                backup: path,
+                salt_from: self.todo_path.clone(),
+            }),
+            _ => open(Backend::Text, &path, &path),
+        }
+    }
+
+    // Lista criptografada sem chave que a abra (veja `crypto::open`)
+    pub fn is_locked(&self) -> bool {
+        self.backend() == Backend::Encrypted && open_encrypted(&self.todo_path).is_err()
+    }
+
+    // Criptografa a lista, o arquivo morto e o backup com uma chave derivada da senha.
+    // Cada arquivo é regravado no lugar: nenhuma cópia em texto fica para trás.
+    pub fn encrypt(&self, passphrase: &str) -> Result<(), String> {
+        match self.backend() {
+            Backend::Text => {}
+            Backend::Encrypted => return Err("A lista já está criptografada".to_string()),
+            Backend::Sqlite => {
+                return Err("Só listas no formato de texto podem ser criptografadas: use todo migrate text".to_string());
+            }
+        }
+        let salt = crypto::new_salt();
+        let key = crypto::derive_key(passphrase, &salt)?;
+        let lines = self.storage().load()?;
+        for path in [self.archive_path(), self.todo_bak.clone()] {
+            if path.is_file() && Backend::detect(&path) == Backend::Text {
+                let lines = open(Backend::Text, &path, &path).load()?;
+                write_encrypted(&path, &salt, &key, &lines)?;
+            }
+        }
+        write_encrypted(&self.todo_path, &salt, &key, &lines)?;
+        crypto::remember(salt, key);
+        Ok(())
+    }
+
+    // Volta a lista, o arquivo morto e o backup para texto puro
+    pub fn decrypt(&self) -> Result<(), String> {
+        if self.backend() != Backend::Encrypted {
+            return Err("A lista não está criptografada".to_string());
+        }
+        let lines = self.storage().load()?;
+        for path in [self.archive_path(), self.todo_bak.clone()] {
+            if path.is_file() && Backend::detect(&path) == Backend::Encrypted {
+                let lines = read_encrypted(&path)?;
+                open(Backend::Text, &path, &path).save(&lines)?;
+            }
+        }
+        open(Backend::Text, &self.todo_path, &self.todo_bak).save(&lines)
+    }
+
+    // Converte a lista para outro formato no mesmo caminho. Durante a conversão o arquivo antigo
+    // fica ao lado, com o nome do formato (ex.: ~/.todo.text); ele é apagado depois que a lista
+    // convertida é relida igual, para não sobrar uma cópia em texto de uma lista criptografada depois.
+    pub fn migrate(&self, target: Backend) -> Result<(), String> {
+        let current = self.backend();
+        if current == Backend::Encrypted {
+            return Err("A lista está criptografada: use todo decrypt antes de converter".to_string());
+        }
+        if current == target {
+            return Err(format!("A lista já está no formato {}", target.name()));
+        }
+        let lines = self.storage().load()?;
+        let mut old = self.todo_path.clone().into_os_string();
+        old.push(format!(".{}", current.name()));
+        let old = PathBuf::from(old);
+        fs::rename(&self.todo_path, &old)
+            .map_err(|e| format!("Não foi possível guardar o arquivo antigo em {}: {}", old.display(), e))?;
+        let storage = open(target, &self.todo_path, &self.todo_bak);
+        let converted = storage.save(&lines).and_then(|()| storage.load());
+        if converted.as_ref() != Ok(&lines) {
+            let _ = fs::remove_file(&self.todo_path);
+            let _ = fs::rename(&old, &self.todo_path);
+            return Err(converted.err().unwrap_or("A lista convertida não confere com a original".to_string()));
+        }
+        fs::remove_file(&old)
+            .map_err(|e| format!("Lista convertida, mas o arquivo antigo {} não foi apagado: {}", old.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{reload, temp_list};
+
+    #[test]
+    fn migrate_converts_in_place_without_leftovers() {
+        let (dir, todo) = temp_list(&["[ ] a due:2026-05-01", "  [*] b"]);
+        todo.migrate(Backend::Sqlite).unwrap();
+        let sqlite = reload(&todo);
+        assert!(sqlite.backend() == Backend::Sqlite);
+        assert_eq!(sqlite.todo, todo.todo);
+        assert!(sqlite.migrate(Backend::Sqlite).is_err());
+
+        sqlite.apply("done", &["1".to_string()]).unwrap();
+        let history = sqlite.storage().history(10).unwrap();
+        assert!(history.iter().any(|entry| entry.added && entry.line.starts_with("[*] a")));
+
+        sqlite.migrate(Backend::Text).unwrap();
+        let text = reload(&todo);
+        assert!(text.backend() == Backend::Text);
+        assert_eq!(text.todo, reload(&sqlite).todo);
+        let files: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(files, ["TODO"]);
+    }
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    fn sqlite_at(dir: &Path) -> Box<dyn Storage> {
+        open(Backend::Sqlite, &dir.join("TODO.db"), &dir.join("TODO.bak"))
+    }
+
+    #[test]
+    fn sqlite_writes_are_recorded_in_the_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = sqlite_at(dir.path());
+        assert!(storage.load().unwrap().is_empty());
+
+        storage.append(&lines(&["[ ] a", "[ ] b"])).unwrap();
+        storage.append(&lines(&["[ ] c"])).unwrap();
+        assert_eq!(storage.load().unwrap(), ["[ ] a", "[ ] b", "[ ] c"]);
+
+        storage.save(&lines(&["[ ] a", "[*] b", "[ ] c"])).unwrap();
+        assert_eq!(storage.load().unwrap(), ["[ ] a", "[*] b", "[ ] c"]);
+        // Gravar a mesma lista não muda nada nem entra no histórico
+        let before = storage.history(100).unwrap().len();
+        storage.save(&lines(&["[ ] a", "[*] b", "[ ] c"])).unwrap();
+        assert_eq!(storage.history(100).unwrap().len(), before);
+
+        let history = storage.history(2).unwrap();
+        let changes: Vec<(bool, &str)> = history.iter().map(|entry| (entry.added, entry.line.as_str())).collect();
+        assert_eq!(changes, [(true, "[*] b"), (false, "[ ] b")]);
+
+        storage.backup().unwrap();
+        storage.clear().unwrap();
+        assert!(storage.load().unwrap().is_empty());
+        assert!(Backend::detect(&dir.path().join("TODO.db")) == Backend::Sqlite);
+        storage.restore().unwrap();
+        assert_eq!(storage.load().unwrap(), ["[ ] a", "[*] b", "[ ] c"]);
+    }
+
+    #[test]
+    fn pending_due_is_the_same_on_every_backend() {
+        let (dir, todo) = temp_list(&[
+            "[ ] antes due:2026-04-30",
+            "[ ] dentro due:2026-05-01",
+            "  [ ] sub dentro due:2026-05-03",
+            "[*] feita due:2026-05-02",
+            "[ ] sem data",
+            "[ ] depois due:2026-05-04",
+        ]);
+        let from = NaiveDate::from_ymd_opt(2026, 5, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2026, 5, 3).unwrap();
+        let expected = ["[ ] dentro due:2026-05-01", "  [ ] sub dentro due:2026-05-03"];
+        assert_eq!(todo.storage().pending_due(from, until).unwrap(), expected);
+
+        let sqlite = sqlite_at(dir.path());
+        sqlite.save(&todo.todo).unwrap();
+        assert_eq!(sqlite.pending_due(from, until).unwrap(), expected);
+
+        // A consulta usa o índice do vencimento
+        let conn = Connection::open(dir.path().join("TODO.db")).unwrap();
+        let plan: String = conn
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT line FROM tasks WHERE due BETWEEN ?1 AND ?2 AND done = 0",
+                params!["2026-05-01", "2026-05-03"],
+                |row| row.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("tasks_due") || plan.contains("tasks_done"), "{}", plan);
+    }
+
+    #[test]
+    fn an_encrypted_list_keeps_its_archive_and_backup_encrypted() {
+        let (_dir, mut todo) = temp_list(&["[ ] pendente", "[*] feita done:2026-01-02"]);
+        todo.no_backup = false;
+        fs::write(todo.archive_path(), "[*] antiga done:2025-12-01\n").unwrap();
+        todo.encrypt("senha").unwrap();
+        let encrypted = |path: &Path| Backend::detect(path) == Backend::Encrypted;
+        assert!(encrypted(&todo.todo_path) && encrypted(&todo.archive_path()));
+
+        let todo = Todo { no_backup: false, ..reload(&todo) };
+        assert!(!todo.is_locked());
+        assert_eq!(todo.todo, ["[ ] pendente", "[*] feita done:2026-01-02"]);
+        assert_eq!(todo.archive(None).unwrap(), 1);
+        assert_eq!(
+            todo.archive_storage().load().unwrap(),
+            ["[*] antiga done:2025-12-01", "[*] feita done:2026-01-02"]
+        );
+        assert!(encrypted(&todo.archive_path()));
+
+        let todo = Todo { no_backup: false, ..reload(&todo) };
+        todo.reset().unwrap();
+        assert!(encrypted(&todo.todo_bak) && encrypted(&todo.todo_path));
+        assert!(reload(&todo).todo.is_empty());
+        todo.restore().unwrap();
+        assert_eq!(reload(&todo).todo, ["[ ] pendente"]);
+
+        todo.decrypt().unwrap();
+        for path in [&todo.todo_path, &todo.todo_bak, &todo.archive_path()] {
+            assert!(Backend::detect(path) == Backend::Text);
+        }
+        assert_eq!(fs::read_to_string(&todo.todo_path).unwrap(), "[ ] pendente\n");
+    }
+
+    #[test]
+    fn reset_empties_a_project_list_and_removes_any_other() {
+        let (_dir, todo) = temp_list(&["[ ] a"]);
+        todo.reset().unwrap();
+        assert!(!todo.todo_path.exists());
+
+        let (_dir, mut todo) = temp_list(&["[ ] a"]);
+        todo.project = true;
+        todo.reset().unwrap();
+        assert_eq!(fs::read_to_string(&todo.todo_path).unwrap(), "");
+        // Pelo daemon, que roda em outro diretório, a lista continua sendo de projeto
+        let location = crate::daemon::Location::of(&todo);
+        assert!(location.project);
+    }
+}
+
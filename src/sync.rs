@@ -0,0 +1,397 @@
+use crate::storage::Backend;
+use crate::{Todo, merge};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// Histórico e sincronização da lista por um repositório git no diretório do arquivo.
+// `sync init` liga o commit automático depois de cada comando que altera a lista.
+
+// Chave do `git config` do repositório que liga o commit automático
+const ENABLED_KEY: &str = "todo-todo.sync";
+
+const REMOTE: &str = "origin";
+
+// Resultado de `sync`
+pub struct SyncSummary {
+    pub upstream: String,
+    pub pulled: bool,
+    pub pushed: bool,
+    pub conflicts: usize,
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Não foi possível executar o git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("git {} falhou: {}", args.join(" "), stderr.trim()))
+    }
+}
+
+// Diretório do repositório e nomes dos arquivos versionados (a lista e o arquivo morto)
+struct Repo {
+    dir: PathBuf,
+    files: Vec<String>,
+}
+
+impl Repo {
+    fn of(todo: &Todo) -> Self {
+        let dir = todo.todo_path.parent().unwrap_or(Path::new(".")).to_path_buf();
+        let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let mut files = vec![name(&todo.todo_path)];
+        if todo.archive_path().exists() {
+            files.push(name(&todo.archive_path()));
+        }
+        Self { dir, files }
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, String> {
+        git(&self.dir, args)
+    }
+
+    // Raiz do repositório git em que o diretório está, se houver
+    fn toplevel(&self) -> Option<PathBuf> {
+        self.git(&["rev-parse", "--show-toplevel"]).ok().map(PathBuf::from)
+    }
+
+    // O sync só usa um repositório cuja raiz é o próprio diretório da lista: um acima dele
+    // (a pasta pessoal versionada, o projeto do usuário) ganharia um commit a cada comando
+    fn is_own(&self) -> bool {
+        self.toplevel().is_some_and(|top| same_dir(&top, &self.dir))
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.is_own()
+            && self
+                .git(&["config", "--local", "--get", ENABLED_KEY])
+                .is_ok_and(|value| value == "true")
+    }
+
+    // Grava um commit só com os arquivos da lista, se eles mudaram
+    fn commit(&self, message: &str) -> Result<bool, String> {
+        let mut args = vec!["status", "--porcelain", "--"];
+        args.extend(self.files.iter().map(String::as_str));
+        if self.git(&args)?.is_empty() {
+            return Ok(false);
+        }
+        let mut args = vec!["add", "--"];
+        args.extend(self.files.iter().map(String::as_str));
+        self.git(&args)?;
+        let mut args = vec!["commit", "-q", "-m", message, "--"];
+        args.extend(self.files.iter().map(String::as_str));
+        self.git(&args)?;
+        Ok(true)
+    }
+
+    // Conteúdo de um arquivo numa revisão; vazio se ele não existia
+    fn show(&self, rev: &str, file: &str) -> Vec<String> {
+        let prefix = self.git(&["rev-parse", "--show-prefix"]).unwrap_or_default();
+        self.git(&["show", &format!("{}:{}{}", rev, prefix, file)])
+            .map(|contents| contents.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
+
+// Mensagem do commit: o comando e o texto das tarefas afetadas (ex.: "done: comprar leite")
+pub fn describe(todo: &Todo, command: &str, args: &[String]) -> String {
+    let subject = match command {
+        "mk" => args.join(", "),
+        "sub" => args.get(1..).unwrap_or_default().join(", "),
+        _ => {
+            let texts: Vec<String> = args.iter().filter_map(|arg| todo.entry_text(arg).ok()).collect();
+            if texts.is_empty() { args.join(" ") } else { texts.join(", ") }
+        }
+    };
+    if subject.is_empty() {
+        command.to_string()
+    } else {
+        format!("{}: {}", command, subject)
+    }
+}
+
+// Chamado depois de cada comando que altera a lista; não faz nada se o sync não foi ligado
+pub fn record(todo: &Todo, message: &str) -> Result<(), String> {
+    let repo = Repo::of(todo);
+    if !repo.is_enabled() {
+        return Ok(());
+    }
+    repo.commit(message).map(|_| ())
+}
+
+// Cria o repositório (se preciso), liga o commit automático e configura o remoto
+pub fn init(todo: &Todo, remote: Option<&str>) -> Result<(), String> {
+    let repo = Repo::of(todo);
+    match repo.toplevel() {
+        Some(top) if !same_dir(&top, &repo.dir) => {
+            return Err(format!(
+                "A lista está dentro do repositório git {}: o sync precisa de um repositório só para ela. \
+                 Use TODO_PATH para colocar a lista num diretório próprio",
+                top.display()
+            ));
+        }
+        Some(_) => {}
+        None if dirs::home_dir().is_some_and(|home| same_dir(&home, &repo.dir)) => {
+            return Err(format!(
+                "A lista fica direto em {}: o sync criaria um repositório git com toda a pasta pessoal. \
+                 Use TODO_PATH para colocar a lista num diretório próprio",
+                repo.dir.display()
+            ));
+        }
+        None => {
+            repo.git(&["init", "-q"])?;
+        }
+    }
+    repo.git(&["config", ENABLED_KEY, "true"])?;
+    if let Some(url) = remote {
+        if repo.git(&["remote", "get-url", REMOTE]).is_ok() {
+            repo.git(&["remote", "set-url", REMOTE, url])?;
+        } else {
+            repo.git(&["remote", "add", REMOTE, url])?;
+        }
+    }
+    repo.commit("sync init")?;
+    Ok(())
+}
+
+// Tarefas arquivadas só são acrescentadas: junta as linhas novas dos dois lados
+fn union(base: &[String], ours: &[String], theirs: &[String]) -> Vec<String> {
+    let mut lines = ours.to_vec();
+    lines.extend(
+        theirs
+            .iter()
+            .filter(|line| !base.contains(line) && !ours.contains(line))
+            .cloned(),
+    );
+    lines
+}
+
+fn write_lines(path: &Path, lines: &[String]) -> Result<(), String> {
+    let data: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+    fs::write(path, data).map_err(|e| format!("Falha na gravação: {}", e))
+}
+
+// Grava as alterações pendentes, traz as do remoto (mesclando a lista tarefa a tarefa) e envia
+pub fn sync(todo: &Todo) -> Result<SyncSummary, String> {
+    match todo.backend() {
+        Backend::Text => {}
+        Backend::Sqlite => return Err("O sync só mescla listas no formato de texto: use todo migrate text".to_string()),
+        Backend::Encrypted => return Err("O sync não mescla listas criptografadas: use todo decrypt".to_string()),
+    }
+    let repo = Repo::of(todo);
+    if !repo.is_enabled() {
+        return Err("O sync não está ligado para esta lista: use todo sync init [REMOTO]".to_string());
+    }
+    repo.git(&["remote", "get-url", REMOTE])
+        .map_err(|_| format!("Nenhum remoto '{}' configurado: use todo sync init REMOTO", REMOTE))?;
+    repo.commit("sync")?;
+
+    let branch = repo.git(&["rev-parse", "--abbrev-ref", "HEAD"])?;
+    let upstream = format!("{}/{}", REMOTE, branch);
+    let mut summary = SyncSummary {
+        upstream: upstream.clone(),
+        pulled: false,
+        pushed: false,
+        conflicts: 0,
+    };
+    repo.git(&["fetch", "-q", REMOTE])?;
+
+    if repo.git(&["rev-parse", "--verify", "-q", &format!("refs/remotes/{}", upstream)]).is_ok() {
+        let is_ancestor = |a: &str, b: &str| repo.git(&["merge-base", "--is-ancestor", a, b]).is_ok();
+        if is_ancestor(&upstream, "HEAD") {
+            // Nada novo no remoto
+        } else if is_ancestor("HEAD", &upstream) {
+            repo.git(&["merge", "-q", "--ff-only", &upstream])?;
+            summary.pulled = true;
+        } else {
+            let base = repo.git(&["merge-base", "HEAD", &upstream])?;
+            // Conflitos de texto do git nestes arquivos são resolvidos logo abaixo; HEAD ainda não mudou
+            let _ = repo.git(&["merge", "-q", "--no-commit", "--no-ff", &upstream]);
+            for path in [todo.todo_path.clone(), todo.archive_path()] {
+                let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                let (base, ours, theirs) = (repo.show(&base, &name), repo.show("HEAD", &name), repo.show(&upstream, &name));
+                if ours.is_empty() && theirs.is_empty() {
+                    continue;
+                }
+                if path == todo.todo_path {
+                    let merged = merge::merge(&base, &ours, &theirs);
+                    summary.conflicts = merged.conflicts;
+                    write_lines(&path, &merged.lines())?;
+                } else {
+                    write_lines(&path, &union(&base, &ours, &theirs))?;
+                }
+                repo.git(&["add", "--", &name])?;
+            }
+            let unmerged = repo.git(&["diff", "--name-only", "--diff-filter=U"])?;
+            if !unmerged.is_empty() {
+                let _ = repo.git(&["merge", "--abort"]);
+                return Err(format!("Conflito em arquivos que não são da lista:\n{}", unmerged));
+            }
+            repo.git(&["commit", "-q", "-m", &format!("sync: mescla com {}", upstream)])?;
+            summary.pulled = true;
+        }
+        if is_ancestor("HEAD", &upstream) {
+            return Ok(summary);
+        }
+    }
+
+    repo.git(&["push", "-q", "-u", REMOTE, &branch])?;
+    summary.pushed = true;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Entry;
+    use crate::tests::reload;
+    use tempfile::TempDir;
+
+    fn open_at(dir: &Path) -> Todo {
+        let todo = Todo {
+            todo: Vec::new(),
+            todo_path: dir.join("TODO"),
+            todo_bak: dir.join("TODO.bak"),
+            no_backup: true,
+            project: false,
+        };
+        todo.load().unwrap()
+    }
+
+    fn list_at(dir: &Path, lines: &[&str]) -> Todo {
+        let data: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+        fs::write(dir.join("TODO"), data).unwrap();
+        open_at(dir)
+    }
+
+    // Autor configurado no próprio repositório, para os commits não dependerem do git do usuário
+    fn set_author(dir: &Path) {
+        git(dir, &["config", "user.name", "teste"]).unwrap();
+        git(dir, &["config", "user.email", "teste@example.com"]).unwrap();
+    }
+
+    fn git_repo(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        git(dir, &["init", "-q"]).unwrap();
+        set_author(dir);
+    }
+
+    fn subjects(dir: &Path) -> Vec<String> {
+        git(dir, &["log", "--format=%s"]).unwrap().lines().map(str::to_string).collect()
+    }
+
+    fn entries(todo: &Todo) -> Vec<(String, bool)> {
+        reload(todo)
+            .todo
+            .iter()
+            .map(|line| Entry::read_line(line))
+            .map(|entry| (entry.todo_entry, entry.done))
+            .collect()
+    }
+
+    fn change(todo: &Todo, command: &str, args: &[&str]) {
+        let todo = reload(todo);
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let message = describe(&todo, command, &args);
+        todo.apply(command, &args).unwrap();
+        record(&reload(&todo), &message).unwrap();
+    }
+
+    // Duas cópias da lista (a e b) ligadas a um remoto bare, já com o primeiro envio feito
+    fn two_copies(lines: &[&str]) -> (TempDir, Todo, Todo) {
+        let root = tempfile::tempdir().unwrap();
+        git(root.path(), &["init", "-q", "--bare", "remoto.git"]).unwrap();
+        let remote = root.path().join("remoto.git");
+
+        let a_dir = root.path().join("a");
+        git_repo(&a_dir);
+        let a = list_at(&a_dir, lines);
+        init(&a, Some(&remote.to_string_lossy())).unwrap();
+        let summary = sync(&a).unwrap();
+        assert!(summary.pushed && !summary.pulled);
+
+        git(root.path(), &["clone", "-q", &remote.to_string_lossy(), "b"]).unwrap();
+        let b_dir = root.path().join("b");
+        set_author(&b_dir);
+        let b = open_at(&b_dir);
+        init(&b, None).unwrap();
+        (root, a, b)
+    }
+
+    #[test]
+    fn init_refuses_a_repository_above_the_list() {
+        let root = tempfile::tempdir().unwrap();
+        git_repo(root.path());
+        let dir = root.path().join("notas");
+        fs::create_dir(&dir).unwrap();
+        let todo = list_at(&dir, &["[ ] a"]);
+        assert!(init(&todo, None).is_err());
+
+        // Nem com o sync ligado à mão no repositório de cima o commit automático grava nele
+        git(root.path(), &["config", ENABLED_KEY, "true"]).unwrap();
+        change(&todo, "done", &["1"]);
+        assert!(git(root.path(), &["log"]).is_err());
+        assert!(sync(&todo).is_err());
+    }
+
+    #[test]
+    fn record_commits_only_the_list_files() {
+        let root = tempfile::tempdir().unwrap();
+        git_repo(root.path());
+        fs::write(root.path().join("outro.txt"), "x").unwrap();
+        let todo = list_at(root.path(), &["[ ] pão"]);
+        init(&todo, None).unwrap();
+        change(&todo, "done", &["1"]);
+        // Sem alteração, nenhum commit
+        record(&reload(&todo), "nada").unwrap();
+        assert_eq!(subjects(root.path()), ["done: pão", "sync init"]);
+        assert_eq!(git(root.path(), &["status", "--porcelain"]).unwrap(), "?? outro.txt");
+    }
+
+    #[test]
+    fn sync_pushes_pulls_and_merges_task_by_task() {
+        let (_root, a, b) = two_copies(&["[ ] pão id:1", "[ ] leite id:2"]);
+
+        change(&b, "done", &["1"]);
+        let summary = sync(&reload(&b)).unwrap();
+        assert!(summary.pushed && !summary.pulled);
+
+        // Cada lado mexeu numa tarefa diferente: as duas alterações ficam
+        change(&a, "edit", &["2", "leite integral"]);
+        let summary = sync(&reload(&a)).unwrap();
+        assert!(summary.pulled && summary.pushed);
+        assert_eq!(summary.conflicts, 0);
+        let merged = [("pão".to_string(), true), ("leite integral".to_string(), false)];
+        assert_eq!(entries(&a), merged);
+
+        let summary = sync(&reload(&b)).unwrap();
+        assert!(summary.pulled && !summary.pushed);
+        assert_eq!(entries(&b), merged);
+        assert_eq!(reload(&b).todo, reload(&a).todo);
+    }
+
+    #[test]
+    fn conflicting_edits_keep_the_local_version() {
+        let (_root, a, b) = two_copies(&["[ ] pão id:1"]);
+        change(&b, "edit", &["1", "pão francês"]);
+        sync(&reload(&b)).unwrap();
+
+        change(&a, "edit", &["1", "pão integral"]);
+        change(&a, "done", &["1"]);
+        let summary = sync(&reload(&a)).unwrap();
+        assert_eq!(summary.conflicts, 1);
+        assert_eq!(entries(&a), [("pão integral".to_string(), true)]);
+        assert!(git(a.todo_path.parent().unwrap(), &["status", "--porcelain"]).unwrap().is_empty());
+    }
+}
+